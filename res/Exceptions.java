@@ -0,0 +1,105 @@
+// compiled with `javac --release 17 Exceptions.java`, run by
+// tests/exceptions.rs on top of the JDK's own classes and of the stubs in
+// res/stubs
+public class Exceptions {
+
+    static class Custom extends Error {
+        final int value;
+
+        Custom(int value) {
+            this.value = value;
+        }
+    }
+
+    static int caught() {
+        try {
+            throw new IllegalStateException("bad state");
+        } catch (RuntimeException e) {
+            return e.getMessage().length();
+        }
+    }
+
+    static boolean fillReturnsThis() {
+        Throwable t = new UnsupportedOperationException();
+        return t.fillInStackTrace() == t;
+    }
+
+    static void recurse(int n) {
+        if (n == 0) {
+            throw new Custom(42);
+        }
+        recurse(n - 1);
+    }
+
+    static int caughtDeeper() {
+        try {
+            recurse(3);
+        } catch (Custom e) {
+            return e.value;
+        }
+        return -1;
+    }
+
+    static int depth;
+
+    static void deeper() {
+        depth++;
+        deeper();
+    }
+
+    static int overflow() {
+        try {
+            deeper();
+        } catch (StackOverflowError e) {
+            return depth;
+        }
+        return -1;
+    }
+
+    static void overflowUncaught() {
+        deeper();
+    }
+
+    static void uncaught() {
+        throw new IllegalArgumentException("nope");
+    }
+
+    static String divideByZero() {
+        int zero = 0;
+        try {
+            zero = 1 / zero;
+        } catch (ArithmeticException e) {
+            return e.getMessage();
+        }
+        return null;
+    }
+
+    static String indexOutOfBounds() {
+        int[] array = new int[2];
+        try {
+            array[3] = 1;
+        } catch (ArrayIndexOutOfBoundsException e) {
+            return e.getMessage();
+        }
+        return null;
+    }
+
+    static String classCast() {
+        Object object = new Object();
+        try {
+            return (String) object;
+        } catch (ClassCastException e) {
+            return e.getMessage();
+        }
+    }
+
+    static int suppressed() {
+        try {
+            Object array = new int[0];
+            return ((Object[]) array).length;
+        } catch (ClassCastException e) {
+            e.addSuppressed(new IllegalStateException());
+            return e.getSuppressed().length;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// compiled with `javac --release 17 Natives.java`, run by tests/natives.rs
+// on top of the JDK's own classes and of the stubs in res/stubs
+public class Natives {
+
+    static String threadName() {
+        return Thread.currentThread().getName();
+    }
+
+    static int threadPriority() {
+        return Thread.currentThread().getPriority();
+    }
+
+    static String threadGroup() {
+        return Thread.currentThread().getThreadGroup().getName();
+    }
+
+    static String parentThreadGroup() {
+        return Thread.currentThread().getThreadGroup().getParent().getName();
+    }
+
+    static boolean sameThread() {
+        return Thread.currentThread() == Thread.currentThread();
+    }
+
+    static int[] copyForward() {
+        int[] a = {1, 2, 3, 4, 5};
+        System.arraycopy(a, 0, a, 1, 4);
+        return a;
+    }
+
+    static int[] copyBackward() {
+        int[] a = {1, 2, 3, 4, 5};
+        System.arraycopy(a, 1, a, 0, 4);
+        return a;
+    }
+
+    static String copyOutOfBounds(int srcPos, int destPos, int length) {
+        try {
+            System.arraycopy(new int[5], srcPos, new int[3], destPos, length);
+            return null;
+        } catch (ArrayIndexOutOfBoundsException e) {
+            return e.getMessage();
+        }
+    }
+
+    static Object[] copyUntilStoreFails() {
+        Object[] src = {"a", "b", new Object(), "c"};
+        String[] dest = new String[4];
+        try {
+            System.arraycopy(src, 0, dest, 0, 4);
+        } catch (ArrayStoreException e) {
+            return dest;
+        }
+        return null;
+    }
+
+    static boolean copyMismatchedPrimitives() {
+        try {
+            System.arraycopy(new int[1], 0, new long[1], 0, 1);
+            return false;
+        } catch (ArrayStoreException e) {
+            return true;
+        }
+    }
+}
@@ -0,0 +1,57 @@
+import java.lang.annotation.*;
+import java.util.List;
+import java.util.function.Supplier;
+
+// compiled with `javac -g -parameters --release 17 RoundTrip.java`, the
+// class file is the fixture for writing classes back byte for byte
+@RoundTrip.Tag(name = "fixture", values = {1, 2}, kind = ElementType.TYPE)
+public sealed class RoundTrip<T extends Comparable<T>> permits RoundTrip.Sub {
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Tag {
+        String name() default "none";
+        int[] values() default {};
+        ElementType kind() default ElementType.FIELD;
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target(ElementType.TYPE_USE)
+    @interface Use {}
+
+    static final class Sub extends RoundTrip<String> {}
+
+    static final long BIG = 0x0123_4567_89AB_CDEFL;
+    static final double NEG_ZERO = -0.0;
+    static final String LONE = "\uD800 and \uDFFF";
+    static final String PAIR = "\uD83D\uDE00\u0000";
+
+    @Deprecated
+    List<@Use String> names;
+
+    long wide(long a, double b) {
+        double d = 1.0e300 + b;
+        return a * 0x7FFF_FFFF_FFFFL + (long) d;
+    }
+
+    int branches(@Tag int x) throws Exception {
+        int total = 0;
+        for (int i = 0; i < x; i++) {
+            switch (i % 3) {
+                case 0 -> total += i;
+                case 1 -> total -= 1;
+                default -> {
+                    try {
+                        total /= i;
+                    } catch (ArithmeticException e) {
+                        throw new Exception(e);
+                    }
+                }
+            }
+        }
+        return total;
+    }
+
+    Supplier<String> lambda(T value) {
+        return () -> "value " + value + LONE;
+    }
+}
@@ -0,0 +1,6 @@
+// stand-ins for the few classes of java.base that the tests in tests/
+// need, compiled with `javac --patch-module java.base=. -d . java/lang/*.java`
+package java.lang;
+
+public class Object {
+}
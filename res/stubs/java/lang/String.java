@@ -0,0 +1,16 @@
+package java.lang;
+
+// the fields the interpreter fills in, laid out like the JDK's
+public final class String {
+    private final byte[] value;
+    private final byte coder;
+
+    private String(byte[] value, byte coder) {
+        this.value = value;
+        this.coder = coder;
+    }
+
+    public int length() {
+        return value.length >> coder;
+    }
+}
@@ -0,0 +1,31 @@
+package java.lang;
+
+public class Thread {
+    public static final int MAX_PRIORITY = 10;
+
+    private volatile String name;
+    private int priority;
+    private ThreadGroup group;
+    private volatile int threadStatus;
+
+    public Thread(ThreadGroup group, String name) {
+        Thread parent = currentThread();
+        this.name = name;
+        this.group = group;
+        this.priority = parent.getPriority();
+    }
+
+    public static native Thread currentThread();
+
+    public final String getName() {
+        return name;
+    }
+
+    public final int getPriority() {
+        return priority;
+    }
+
+    public final ThreadGroup getThreadGroup() {
+        return group;
+    }
+}
@@ -0,0 +1,27 @@
+package java.lang;
+
+public class ThreadGroup {
+    private final ThreadGroup parent;
+    String name;
+    int maxPriority;
+
+    private ThreadGroup() {
+        this.name = "system";
+        this.maxPriority = Thread.MAX_PRIORITY;
+        this.parent = null;
+    }
+
+    public ThreadGroup(ThreadGroup parent, String name) {
+        this.name = name;
+        this.maxPriority = parent.maxPriority;
+        this.parent = parent;
+    }
+
+    public final String getName() {
+        return name;
+    }
+
+    public final ThreadGroup getParent() {
+        return parent;
+    }
+}
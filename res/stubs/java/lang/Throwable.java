@@ -0,0 +1,36 @@
+package java.lang;
+
+public class Throwable {
+    private String detailMessage;
+    private Throwable[] suppressed = new Throwable[0];
+
+    public Throwable() {
+        fillInStackTrace();
+    }
+
+    public Throwable(String message) {
+        fillInStackTrace();
+        detailMessage = message;
+    }
+
+    public String getMessage() {
+        return detailMessage;
+    }
+
+    public Throwable fillInStackTrace() {
+        return fillInStackTrace(0);
+    }
+
+    private native Throwable fillInStackTrace(int dummy);
+
+    public final void addSuppressed(Throwable exception) {
+        Throwable[] more = new Throwable[suppressed.length + 1];
+        System.arraycopy(suppressed, 0, more, 0, suppressed.length);
+        more[suppressed.length] = exception;
+        suppressed = more;
+    }
+
+    public final Throwable[] getSuppressed() {
+        return suppressed;
+    }
+}
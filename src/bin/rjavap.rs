@@ -0,0 +1,52 @@
+use rusty_jvm::jvm::class::{javap::JavapOptions, Class};
+
+fn main() {
+    let mut options = JavapOptions::default();
+    let mut files = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-c" => options.code = true,
+            "-v" | "-verbose" => options.verbose = true,
+            "-p" | "-private" => options.private = true,
+            "-l" => options.lines = true,
+            flag if flag.starts_with('-') => {
+                eprintln!("rjavap: unknown option {}", flag);
+                eprintln!("usage: rjavap [-c] [-v] [-p] [-l] <classfile>...");
+                std::process::exit(2);
+            }
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() {
+        eprintln!("usage: rjavap [-c] [-v] [-p] [-l] <classfile>...");
+        std::process::exit(2);
+    }
+
+    let mut failed = false;
+    for path in files {
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) => {
+                eprintln!("rjavap: {}: {}", path, err);
+                failed = true;
+                continue;
+            }
+        };
+        let class = match Class::new(&data) {
+            Ok(class) => class,
+            Err(err) => {
+                eprintln!("rjavap: {}: {:?}", path, err);
+                failed = true;
+                continue;
+            }
+        };
+        if options.verbose {
+            let path = std::fs::canonicalize(&path).map_or(path, |p| p.display().to_string());
+            println!("Classfile {}", path);
+        }
+        print!("{}", class.javap(options));
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,192 @@
+use super::{ClassBuilderError, ClassFileIter, FromClassFileIter};
+
+#[derive(Debug)]
+pub struct Annotation {
+    pub type_index: u16,
+    //len u16
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+#[derive(Debug)]
+pub struct ElementValuePair {
+    pub element_name_index: u16,
+    pub value: ElementValue,
+}
+
+#[derive(Debug)]
+pub enum ElementValue {
+    /// one of the tags `B C D F I J S Z s`, the tag decides which kind of
+    /// constant `const_value_index` points to
+    Const {
+        tag: u8,
+        const_value_index: u16,
+    },
+    Enum {
+        type_name_index: u16,
+        const_name_index: u16,
+    },
+    Class {
+        class_info_index: u16,
+    },
+    Annotation(Annotation),
+    //len u16
+    Array(Vec<ElementValue>),
+}
+
+#[derive(Debug)]
+pub struct TypeAnnotation {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    //len u8
+    pub target_path: Vec<TypePathEntry>,
+    pub annotation: Annotation,
+}
+
+#[derive(Debug)]
+pub enum TargetInfo {
+    TypeParameter {
+        type_parameter_index: u8,
+    },
+    Supertype {
+        supertype_index: u16,
+    },
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    Empty,
+    FormalParameter {
+        formal_parameter_index: u8,
+    },
+    Throws {
+        throws_type_index: u16,
+    },
+    //len u16
+    Localvar(Vec<LocalvarTargetEntry>),
+    Catch {
+        exception_table_index: u16,
+    },
+    Offset {
+        offset: u16,
+    },
+    TypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+#[derive(Debug)]
+pub struct LocalvarTargetEntry {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+#[derive(Debug)]
+pub struct TypePathEntry {
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
+
+impl FromClassFileIter for Annotation {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(Annotation {
+            type_index: iter.next_u16()?,
+            element_value_pairs: FromClassFileIter::from_arr(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for ElementValuePair {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(ElementValuePair {
+            element_name_index: iter.next_u16()?,
+            value: ElementValue::from_iter(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for ElementValue {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        let tag = iter.next_u8()?;
+        match tag {
+            b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+                Ok(ElementValue::Const {
+                    tag,
+                    const_value_index: iter.next_u16()?,
+                })
+            }
+            b'e' => Ok(ElementValue::Enum {
+                type_name_index: iter.next_u16()?,
+                const_name_index: iter.next_u16()?,
+            }),
+            b'c' => Ok(ElementValue::Class {
+                class_info_index: iter.next_u16()?,
+            }),
+            b'@' => Ok(ElementValue::Annotation(Annotation::from_iter(iter)?)),
+            b'[' => Ok(ElementValue::Array(FromClassFileIter::from_arr(iter)?)),
+            c => Err(ClassBuilderError::InvalidElementValueTag(c)),
+        }
+    }
+}
+
+impl FromClassFileIter for TypeAnnotation {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        let target_type = iter.next_u8()?;
+        let target_info = match target_type {
+            0x00 | 0x01 => TargetInfo::TypeParameter {
+                type_parameter_index: iter.next_u8()?,
+            },
+            0x10 => TargetInfo::Supertype {
+                supertype_index: iter.next_u16()?,
+            },
+            0x11 | 0x12 => TargetInfo::TypeParameterBound {
+                type_parameter_index: iter.next_u8()?,
+                bound_index: iter.next_u8()?,
+            },
+            0x13..=0x15 => TargetInfo::Empty,
+            0x16 => TargetInfo::FormalParameter {
+                formal_parameter_index: iter.next_u8()?,
+            },
+            0x17 => TargetInfo::Throws {
+                throws_type_index: iter.next_u16()?,
+            },
+            0x40 | 0x41 => TargetInfo::Localvar(FromClassFileIter::from_arr(iter)?),
+            0x42 => TargetInfo::Catch {
+                exception_table_index: iter.next_u16()?,
+            },
+            0x43..=0x46 => TargetInfo::Offset {
+                offset: iter.next_u16()?,
+            },
+            0x47..=0x4B => TargetInfo::TypeArgument {
+                offset: iter.next_u16()?,
+                type_argument_index: iter.next_u8()?,
+            },
+            c => return Err(ClassBuilderError::InvalidTargetType(c)),
+        };
+        let path_len = iter.next_u8()?;
+        let mut target_path = Vec::with_capacity(path_len as usize);
+        for _ in 0..path_len {
+            target_path.push(TypePathEntry {
+                type_path_kind: iter.next_u8()?,
+                type_argument_index: iter.next_u8()?,
+            });
+        }
+        Ok(TypeAnnotation {
+            target_type,
+            target_info,
+            target_path,
+            annotation: Annotation::from_iter(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for LocalvarTargetEntry {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(LocalvarTargetEntry {
+            start_pc: iter.next_u16()?,
+            length: iter.next_u16()?,
+            index: iter.next_u16()?,
+        })
+    }
+}
@@ -1,4 +1,9 @@
-use super::{ClassBuilderError, ClassFileIter, FromClassFileIter};
+use super::{
+    annotation::{Annotation, ElementValue, TypeAnnotation},
+    constant::ConstantPool,
+    stack_map::StackMapFrame,
+    ClassBuilderError, ClassFileIter, FromClassFileIter,
+};
 
 #[derive(Debug)]
 pub struct AttributeEntry {
@@ -6,37 +11,21 @@ pub struct AttributeEntry {
     pub info: AttributeInfo,
 }
 impl AttributeEntry {
-    pub fn parse(&mut self, constants: &mut super::constant::ConstantPool) {
+    /// Decodes a `Raw` attribute in place. Attributes we dont know about are
+    /// left as `Raw`, a known one that fails to parse is an error
+    pub fn parse(&mut self, constants: &ConstantPool) -> Result<(), ClassBuilderError> {
         if let AttributeInfo::Raw(vec) = &self.info {
             let name = constants.get_const_utd8(self.name_index).unwrap_or("");
-            match name {
-                "Code" => {
-                    let mut data = ClassFileIter::new(vec);
-
-                    let res: Result<AttributeInfo, ClassBuilderError> = (|| {
-                        Ok(AttributeInfo::Code {
-                            max_stack: data.next_u16()?,
-                            max_locals: data.next_u16()?,
-                            code: {
-                                let tmp = data.next_u32()? as usize;
-                                data.next_n_u8(tmp)?
-                            },
-                            // exception_table: (),
-                            // attributs: ()
-                        })
-                    })();
-                    if let Ok(res) = res {
-                        self.info = res;
-                    }
-                }
-                "dflkadflkfda" => {
-                    panic!(); //so clippy doesnt complain for now :)
-                }
-                _ => {
-                    //for now we ignore anything we dont know
+            let mut data = ClassFileIter::new(vec);
+
+            if let Some(res) = AttributeInfo::parse_named(name, &mut data, constants)? {
+                if !data.is_empty() {
+                    return Err(ClassBuilderError::InvalidAttributeLength);
                 }
+                self.info = res;
             }
         }
+        Ok(())
     }
 }
 
@@ -52,28 +41,377 @@ pub enum AttributeInfo {
         //len u32
         code: Vec<u8>,
         //len u16
-        // start_pc: u16, end_pc: u16, handler_pc: u16, catch_type: u16
-        // exception_table: Vec<()>,
-        // attributs: Vec<AttributeInfo>,
+        exception_table: Vec<ExceptionTableEntry>,
+        //len u16
+        attributes: Vec<AttributeEntry>,
+    },
+    StackMapTable {
+        //len u16
+        entries: Vec<StackMapFrame>,
+    },
+    Exceptions {
+        //len u16
+        exception_index_table: Vec<u16>,
+    },
+    InnerClasses {
+        //len u16
+        classes: Vec<InnerClassEntry>,
+    },
+    EnclosingMethod {
+        class_index: u16,
+        method_index: u16,
     },
-    StackMapTable,
-    Exceptions,
-    InnerClasses,
-    EnclosingMethod,
     Synthetic,
-    Signature,
-    SourceFile,
-    SourceDebugExtension,
-    LineNumberTable,
-    LocalVariableTable,
-    LocalVariableTypeTable,
+    Signature {
+        signature_index: u16,
+    },
+    SourceFile {
+        sourcefile_index: u16,
+    },
+    SourceDebugExtension {
+        debug_extension: Vec<u8>,
+    },
+    LineNumberTable {
+        //len u16
+        line_number_table: Vec<LineNumberEntry>,
+    },
+    LocalVariableTable {
+        //len u16
+        local_variable_table: Vec<LocalVariableEntry>,
+    },
+    LocalVariableTypeTable {
+        //len u16
+        local_variable_type_table: Vec<LocalVariableTypeEntry>,
+    },
     Deprecated,
-    RuntimeVisibleAnnotations,
-    RuntimeInvisibleAnnotations,
-    RuntimeVisibleParameterAnnotations,
-    RuntimeInvisibleParameterAnnotations,
-    AnnotationDefault,
-    BootstrapMethods,
+    RuntimeVisibleAnnotations {
+        //len u16
+        annotations: Vec<Annotation>,
+    },
+    RuntimeInvisibleAnnotations {
+        //len u16
+        annotations: Vec<Annotation>,
+    },
+    RuntimeVisibleParameterAnnotations {
+        //len u8, each inner len u16
+        parameter_annotations: Vec<Vec<Annotation>>,
+    },
+    RuntimeInvisibleParameterAnnotations {
+        //len u8, each inner len u16
+        parameter_annotations: Vec<Vec<Annotation>>,
+    },
+    RuntimeVisibleTypeAnnotations {
+        //len u16
+        annotations: Vec<TypeAnnotation>,
+    },
+    RuntimeInvisibleTypeAnnotations {
+        //len u16
+        annotations: Vec<TypeAnnotation>,
+    },
+    AnnotationDefault {
+        default_value: ElementValue,
+    },
+    BootstrapMethods {
+        //len u16
+        bootstrap_methods: Vec<BootstrapMethod>,
+    },
+    MethodParameters {
+        //len u8
+        parameters: Vec<MethodParameter>,
+    },
+    Module(Box<ModuleInfo>),
+    ModulePackages {
+        //len u16
+        package_index: Vec<u16>,
+    },
+    ModuleMainClass {
+        main_class_index: u16,
+    },
+    NestHost {
+        host_class_index: u16,
+    },
+    NestMembers {
+        //len u16
+        classes: Vec<u16>,
+    },
+    Record {
+        //len u16
+        components: Vec<RecordComponent>,
+    },
+    PermittedSubclasses {
+        //len u16
+        classes: Vec<u16>,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExceptionTableEntry {
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    /// 0 means the handler catches everything (used for `finally`)
+    pub catch_type: u16,
+}
+
+impl ExceptionTableEntry {
+    /// The internal name of the caught class, or `None` for a catch-all handler
+    pub fn catch_type_name<'a>(&self, constants: &'a ConstantPool) -> Option<&'a str> {
+        if self.catch_type == 0 {
+            None
+        } else {
+            constants.get_class_name(self.catch_type)
+        }
+    }
+
+    /// Whether `pc` lies in the range `[start_pc, end_pc)` this handler covers
+    pub fn covers(&self, pc: usize) -> bool {
+        (self.start_pc as usize) <= pc && pc < self.end_pc as usize
+    }
+}
+
+#[derive(Debug)]
+pub struct InnerClassEntry {
+    pub inner_class_info_index: u16,
+    pub outer_class_info_index: u16,
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LineNumberEntry {
+    pub start_pc: u16,
+    pub line_number: u16,
+}
+
+#[derive(Debug)]
+pub struct LocalVariableEntry {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+#[derive(Debug)]
+pub struct LocalVariableTypeEntry {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub signature_index: u16,
+    pub index: u16,
+}
+
+#[derive(Debug)]
+pub struct BootstrapMethod {
+    pub bootstrap_method_ref: u16,
+    //len u16
+    pub bootstrap_arguments: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub struct MethodParameter {
+    pub name_index: u16,
+    pub access_flags: u16,
+}
+
+#[derive(Debug)]
+pub struct ModuleInfo {
+    pub module_name_index: u16,
+    pub module_flags: u16,
+    pub module_version_index: u16,
+    //len u16
+    pub requires: Vec<ModuleRequires>,
+    //len u16
+    pub exports: Vec<ModuleExports>,
+    //len u16
+    pub opens: Vec<ModuleOpens>,
+    //len u16
+    pub uses_index: Vec<u16>,
+    //len u16
+    pub provides: Vec<ModuleProvides>,
+}
+
+#[derive(Debug)]
+pub struct ModuleRequires {
+    pub requires_index: u16,
+    pub requires_flags: u16,
+    pub requires_version_index: u16,
+}
+
+#[derive(Debug)]
+pub struct ModuleExports {
+    pub exports_index: u16,
+    pub exports_flags: u16,
+    //len u16
+    pub exports_to_index: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub struct ModuleOpens {
+    pub opens_index: u16,
+    pub opens_flags: u16,
+    //len u16
+    pub opens_to_index: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub struct ModuleProvides {
+    pub provides_index: u16,
+    //len u16
+    pub provides_with_index: Vec<u16>,
+}
+
+#[derive(Debug)]
+pub struct RecordComponent {
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    //len u16
+    pub attributes: Vec<AttributeEntry>,
+}
+
+impl AttributeInfo {
+    /// Decodes the body of an attribute named `name`. Returns `Ok(None)` for
+    /// attributes we dont know about
+    fn parse_named(
+        name: &str,
+        data: &mut ClassFileIter,
+        constants: &ConstantPool,
+    ) -> Result<Option<Self>, ClassBuilderError> {
+        Ok(Some(match name {
+            "ConstantValue" => AttributeInfo::ConstantValue {
+                constantvalue_indx: data.next_u16()?,
+            },
+            "Code" => AttributeInfo::Code {
+                max_stack: data.next_u16()?,
+                max_locals: data.next_u16()?,
+                code: {
+                    let tmp = data.next_u32()? as usize;
+                    data.next_n_u8(tmp)?
+                },
+                exception_table: FromClassFileIter::from_arr(data)?,
+                attributes: {
+                    let mut attributes = AttributeEntry::from_arr(data)?;
+                    for attr in &mut attributes {
+                        attr.parse(constants)?;
+                    }
+                    attributes
+                },
+            },
+            "StackMapTable" => AttributeInfo::StackMapTable {
+                entries: FromClassFileIter::from_arr(data)?,
+            },
+            "Exceptions" => AttributeInfo::Exceptions {
+                exception_index_table: FromClassFileIter::from_arr(data)?,
+            },
+            "InnerClasses" => AttributeInfo::InnerClasses {
+                classes: FromClassFileIter::from_arr(data)?,
+            },
+            "EnclosingMethod" => AttributeInfo::EnclosingMethod {
+                class_index: data.next_u16()?,
+                method_index: data.next_u16()?,
+            },
+            "Synthetic" => AttributeInfo::Synthetic,
+            "Signature" => AttributeInfo::Signature {
+                signature_index: data.next_u16()?,
+            },
+            "SourceFile" => AttributeInfo::SourceFile {
+                sourcefile_index: data.next_u16()?,
+            },
+            "SourceDebugExtension" => AttributeInfo::SourceDebugExtension {
+                debug_extension: data.next_n_u8(data.remaining())?,
+            },
+            "LineNumberTable" => AttributeInfo::LineNumberTable {
+                line_number_table: FromClassFileIter::from_arr(data)?,
+            },
+            "LocalVariableTable" => AttributeInfo::LocalVariableTable {
+                local_variable_table: FromClassFileIter::from_arr(data)?,
+            },
+            "LocalVariableTypeTable" => AttributeInfo::LocalVariableTypeTable {
+                local_variable_type_table: FromClassFileIter::from_arr(data)?,
+            },
+            "Deprecated" => AttributeInfo::Deprecated,
+            "RuntimeVisibleAnnotations" => AttributeInfo::RuntimeVisibleAnnotations {
+                annotations: FromClassFileIter::from_arr(data)?,
+            },
+            "RuntimeInvisibleAnnotations" => AttributeInfo::RuntimeInvisibleAnnotations {
+                annotations: FromClassFileIter::from_arr(data)?,
+            },
+            "RuntimeVisibleParameterAnnotations" => {
+                AttributeInfo::RuntimeVisibleParameterAnnotations {
+                    parameter_annotations: parse_parameter_annotations(data)?,
+                }
+            }
+            "RuntimeInvisibleParameterAnnotations" => {
+                AttributeInfo::RuntimeInvisibleParameterAnnotations {
+                    parameter_annotations: parse_parameter_annotations(data)?,
+                }
+            }
+            "RuntimeVisibleTypeAnnotations" => AttributeInfo::RuntimeVisibleTypeAnnotations {
+                annotations: FromClassFileIter::from_arr(data)?,
+            },
+            "RuntimeInvisibleTypeAnnotations" => AttributeInfo::RuntimeInvisibleTypeAnnotations {
+                annotations: FromClassFileIter::from_arr(data)?,
+            },
+            "AnnotationDefault" => AttributeInfo::AnnotationDefault {
+                default_value: ElementValue::from_iter(data)?,
+            },
+            "BootstrapMethods" => AttributeInfo::BootstrapMethods {
+                bootstrap_methods: FromClassFileIter::from_arr(data)?,
+            },
+            "MethodParameters" => {
+                let len = data.next_u8()?;
+                let mut parameters = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    parameters.push(MethodParameter::from_iter(data)?);
+                }
+                AttributeInfo::MethodParameters { parameters }
+            }
+            "Module" => AttributeInfo::Module(Box::new(ModuleInfo {
+                module_name_index: data.next_u16()?,
+                module_flags: data.next_u16()?,
+                module_version_index: data.next_u16()?,
+                requires: FromClassFileIter::from_arr(data)?,
+                exports: FromClassFileIter::from_arr(data)?,
+                opens: FromClassFileIter::from_arr(data)?,
+                uses_index: FromClassFileIter::from_arr(data)?,
+                provides: FromClassFileIter::from_arr(data)?,
+            })),
+            "ModulePackages" => AttributeInfo::ModulePackages {
+                package_index: FromClassFileIter::from_arr(data)?,
+            },
+            "ModuleMainClass" => AttributeInfo::ModuleMainClass {
+                main_class_index: data.next_u16()?,
+            },
+            "NestHost" => AttributeInfo::NestHost {
+                host_class_index: data.next_u16()?,
+            },
+            "NestMembers" => AttributeInfo::NestMembers {
+                classes: FromClassFileIter::from_arr(data)?,
+            },
+            "Record" => {
+                let mut components = RecordComponent::from_arr(data)?;
+                for attr in components.iter_mut().flat_map(|c| &mut c.attributes) {
+                    attr.parse(constants)?;
+                }
+                AttributeInfo::Record { components }
+            }
+            "PermittedSubclasses" => AttributeInfo::PermittedSubclasses {
+                classes: FromClassFileIter::from_arr(data)?,
+            },
+            _ => return Ok(None),
+        }))
+    }
+}
+
+fn parse_parameter_annotations(
+    data: &mut ClassFileIter,
+) -> Result<Vec<Vec<Annotation>>, ClassBuilderError> {
+    let len = data.next_u8()?;
+    let mut parameter_annotations = Vec::with_capacity(len as usize);
+    for _ in 0..len {
+        parameter_annotations.push(FromClassFileIter::from_arr(data)?);
+    }
+    Ok(parameter_annotations)
 }
 
 impl FromClassFileIter for AttributeInfo {
@@ -95,3 +433,178 @@ impl FromClassFileIter for AttributeEntry {
         })
     }
 }
+
+impl FromClassFileIter for u16 {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        iter.next_u16()
+    }
+}
+
+impl FromClassFileIter for ExceptionTableEntry {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(ExceptionTableEntry {
+            start_pc: iter.next_u16()?,
+            end_pc: iter.next_u16()?,
+            handler_pc: iter.next_u16()?,
+            catch_type: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for InnerClassEntry {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(InnerClassEntry {
+            inner_class_info_index: iter.next_u16()?,
+            outer_class_info_index: iter.next_u16()?,
+            inner_name_index: iter.next_u16()?,
+            inner_class_access_flags: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for LineNumberEntry {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(LineNumberEntry {
+            start_pc: iter.next_u16()?,
+            line_number: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for LocalVariableEntry {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(LocalVariableEntry {
+            start_pc: iter.next_u16()?,
+            length: iter.next_u16()?,
+            name_index: iter.next_u16()?,
+            descriptor_index: iter.next_u16()?,
+            index: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for LocalVariableTypeEntry {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(LocalVariableTypeEntry {
+            start_pc: iter.next_u16()?,
+            length: iter.next_u16()?,
+            name_index: iter.next_u16()?,
+            signature_index: iter.next_u16()?,
+            index: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for BootstrapMethod {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(BootstrapMethod {
+            bootstrap_method_ref: iter.next_u16()?,
+            bootstrap_arguments: FromClassFileIter::from_arr(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for MethodParameter {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(MethodParameter {
+            name_index: iter.next_u16()?,
+            access_flags: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for ModuleRequires {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(ModuleRequires {
+            requires_index: iter.next_u16()?,
+            requires_flags: iter.next_u16()?,
+            requires_version_index: iter.next_u16()?,
+        })
+    }
+}
+
+impl FromClassFileIter for ModuleExports {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(ModuleExports {
+            exports_index: iter.next_u16()?,
+            exports_flags: iter.next_u16()?,
+            exports_to_index: FromClassFileIter::from_arr(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for ModuleOpens {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(ModuleOpens {
+            opens_index: iter.next_u16()?,
+            opens_flags: iter.next_u16()?,
+            opens_to_index: FromClassFileIter::from_arr(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for ModuleProvides {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(ModuleProvides {
+            provides_index: iter.next_u16()?,
+            provides_with_index: FromClassFileIter::from_arr(iter)?,
+        })
+    }
+}
+
+impl FromClassFileIter for RecordComponent {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(RecordComponent {
+            name_index: iter.next_u16()?,
+            descriptor_index: iter.next_u16()?,
+            attributes: AttributeEntry::from_arr(iter)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jvm::class::constant::ConstantPoolEntry;
+
+    fn attribute(name: &str, info: &[u8]) -> Result<AttributeInfo, ClassBuilderError> {
+        let constants = ConstantPool::new(vec![ConstantPoolEntry::Utf8(name.into())]);
+        let mut attr = AttributeEntry {
+            name_index: 1,
+            info: AttributeInfo::Raw(info.to_vec()),
+        };
+        attr.parse(&constants)?;
+        Ok(attr.info)
+    }
+
+    #[test]
+    fn unknown_attributes_stay_raw() {
+        assert!(matches!(
+            attribute("Unknown", &[1, 2, 3]),
+            Ok(AttributeInfo::Raw(data)) if data == [1, 2, 3]
+        ));
+    }
+
+    #[test]
+    fn malformed_known_attributes_are_errors() {
+        assert!(matches!(
+            attribute("SourceFile", &[0, 1]),
+            Ok(AttributeInfo::SourceFile {
+                sourcefile_index: 1
+            })
+        ));
+        assert!(matches!(
+            attribute("SourceFile", &[0]),
+            Err(ClassBuilderError::ReachedEndOfFile)
+        ));
+        assert!(matches!(
+            attribute("SourceFile", &[0, 1, 2]),
+            Err(ClassBuilderError::InvalidAttributeLength)
+        ));
+        // the code claims 4 bytes but only has 2
+        assert!(matches!(
+            attribute("Code", &[0, 1, 0, 1, 0, 0, 0, 4, 0xB1, 0xB1]),
+            Err(ClassBuilderError::ReachedEndOfFile)
+        ));
+    }
+}
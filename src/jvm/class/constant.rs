@@ -1,4 +1,4 @@
-use super::{ClassBuilderError, FromClassFileIter};
+use super::{mutf8, ClassBuilderError, FromClassFileIter};
 
 #[derive(Debug)]
 pub enum ConstantPoolEntry {
@@ -30,6 +30,9 @@ pub enum ConstantPoolEntry {
         descriptor_index: u16,
     },
     Utf8(String),
+    /// A `CONSTANT_Utf8` holding unpaired surrogates, which Modified UTF-8
+    /// allows but a Rust `String` can't represent
+    Utf16(Vec<u16>),
     MethodHandle {
         reference_kind: ReferenceKind,
         reference_index: u16,
@@ -37,10 +40,20 @@ pub enum ConstantPoolEntry {
     MethodType {
         descriptor_index: u16,
     },
+    Dynamic {
+        bootstrap_method_attr_index: u16,
+        name_and_type_index: u16,
+    },
     InvokeDynamic {
         bootstrap_method_attr_index: u16,
         name_and_type_index: u16,
     },
+    Module {
+        name_index: u16,
+    },
+    Package {
+        name_index: u16,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -71,6 +84,20 @@ impl ReferenceKind {
             _ => return None,
         })
     }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::GetField => 1,
+            Self::GetStatic => 2,
+            Self::PutField => 3,
+            Self::PutStatic => 4,
+            Self::InvokeVirtual => 5,
+            Self::InvokeStatic => 6,
+            Self::InvokeSpecial => 7,
+            Self::NewInvokeSpecial => 8,
+            Self::InvokeInterface => 9,
+        }
+    }
 }
 
 impl ConstantPoolEntry {
@@ -80,6 +107,15 @@ impl ConstantPoolEntry {
             _ => None,
         }
     }
+
+    /// The contents of either kind of `CONSTANT_Utf8` as UTF-16 code units
+    pub fn get_utf16(&self) -> Option<Vec<u16>> {
+        match self {
+            Self::Utf8(str) => Some(str.encode_utf16().collect()),
+            Self::Utf16(units) => Some(units.clone()),
+            _ => None,
+        }
+    }
 }
 
 impl FromClassFileIter for ConstantPoolEntry {
@@ -137,52 +173,36 @@ impl FromClassFileIter for ConstantPoolEntry {
             }),
             1 => {
                 let len = iter.next_u16()?;
-                let mut string = String::with_capacity(len as usize);
-                let mut i = 0;
-                while i < len {
-                    let x = iter.next_u8()?;
-                    match x {
-                        b'\x01'..=b'\x7F' => {
-                            i += 1;
-                            string.push(x as char);
-                        }
-                        #[allow(clippy::unusual_byte_groupings)]
-                        0b110_00000..=0b110_11111 => {
-                            i += 2;
-                            let y = iter.next_u8()?;
-                            let c = ((x as u32 & 0x1f) << 6) + (y as u32 & 0x3f);
-                            let c = char::from_u32(c)
-                                .map_or(Err(ClassBuilderError::StringError), Ok)?;
-                            string.push(c);
-                        }
-                        0b1110_0000..=0b1110_1111 => {
-                            i += 2;
-                            let y = iter.next_u8()?;
-                            let z = iter.next_u8()?;
-                            let c = ((x as u32 & 0xf) << 12)
-                                + ((y as u32 & 0x3f) << 6)
-                                + (z as u32 & 0x3f);
-                            let c = char::from_u32(c)
-                                .map_or(Err(ClassBuilderError::StringError), Ok)?;
-                            string.push(c);
-                        }
-                        _ => return Err(ClassBuilderError::StringError),
-                    }
+                let bytes = iter.next_n_u8(len as usize)?;
+                match mutf8::decode_string(&bytes)
+                    .map_err(ClassBuilderError::InvalidModifiedUtf8)?
+                {
+                    Ok(string) => Ok(ConstantPoolEntry::Utf8(string)),
+                    Err(units) => Ok(ConstantPoolEntry::Utf16(units)),
                 }
-                Ok(ConstantPoolEntry::Utf8(string))
             }
             15 => Ok(ConstantPoolEntry::MethodHandle {
                 reference_kind: ReferenceKind::from_u8(iter.next_u8()?)
-                    .map_or(Err(ClassBuilderError::InvalidReferenceKind), Ok)?,
+                    .ok_or(ClassBuilderError::InvalidReferenceKind)?,
                 reference_index: iter.next_u16()?,
             }),
             16 => Ok(ConstantPoolEntry::MethodType {
                 descriptor_index: iter.next_u16()?,
             }),
+            17 => Ok(ConstantPoolEntry::Dynamic {
+                bootstrap_method_attr_index: iter.next_u16()?,
+                name_and_type_index: iter.next_u16()?,
+            }),
             18 => Ok(ConstantPoolEntry::InvokeDynamic {
                 bootstrap_method_attr_index: iter.next_u16()?,
                 name_and_type_index: iter.next_u16()?,
             }),
+            19 => Ok(ConstantPoolEntry::Module {
+                name_index: iter.next_u16()?,
+            }),
+            20 => Ok(ConstantPoolEntry::Package {
+                name_index: iter.next_u16()?,
+            }),
             c => Err(ClassBuilderError::InvalidConstantType(c)),
         }
     }
@@ -213,12 +233,62 @@ impl ConstantPool {
     }
 
     pub fn get_class_name(&self, index: u16) -> Option<&str> {
-        if let Some(i) = self.get_constant(index) {
-            if let ConstantPoolEntry::Class { name_index } = i {
-                self.get_const_utd8(*name_index)
-            } else {
-                None
+        if let Some(ConstantPoolEntry::Class { name_index }) = self.get_constant(index) {
+            self.get_const_utd8(*name_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_name_and_type(&self, index: u16) -> Option<(&str, &str)> {
+        if let Some(ConstantPoolEntry::NameAndType {
+            name_index,
+            descriptor_index,
+        }) = self.get_constant(index)
+        {
+            Some((
+                self.get_const_utd8(*name_index)?,
+                self.get_const_utd8(*descriptor_index)?,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// The class name, member name and descriptor of a `Fieldref`,
+    /// `Methodref` or `InterfaceMethodref`
+    pub fn get_member_ref(&self, index: u16) -> Option<(&str, &str, &str)> {
+        match self.get_constant(index)? {
+            ConstantPoolEntry::Fieldref {
+                class_index,
+                name_and_type_index,
             }
+            | ConstantPoolEntry::Methodref {
+                class_index,
+                name_and_type_index,
+            }
+            | ConstantPoolEntry::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            } => {
+                let (name, desc) = self.get_name_and_type(*name_and_type_index)?;
+                Some((self.get_class_name(*class_index)?, name, desc))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_module_name(&self, index: u16) -> Option<&str> {
+        if let Some(ConstantPoolEntry::Module { name_index }) = self.get_constant(index) {
+            self.get_const_utd8(*name_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_package_name(&self, index: u16) -> Option<&str> {
+        if let Some(ConstantPoolEntry::Package { name_index }) = self.get_constant(index) {
+            self.get_const_utd8(*name_index)
         } else {
             None
         }
@@ -1,6 +1,8 @@
 use crate::jvm::class::constant::ConstantPoolEntry;
 
-use super::Class;
+use super::{
+    attribute::AttributeEntry, field::FieldEntry, method::MethodEntry, Class, DebugFmtWithNames,
+};
 
 impl std::fmt::Debug for Class {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -11,9 +13,8 @@ impl std::fmt::Debug for Class {
                 Some(self.constant_pool.get_class_name_invalid(self.super_class))
             };
 
-            return f
-                .debug_struct("ClassPrettyPrint")
-                .field("minor_version", &self.major_version)
+            f.debug_struct("ClassPrettyPrint")
+                .field("minor_version", &self.minor_version)
                 .field("major_version", &self.major_version)
                 .field("constat_pool", &ConstantNamePrint { class: self })
                 .field("access_flags", &self.access_flags)
@@ -22,11 +23,14 @@ impl std::fmt::Debug for Class {
                     &self.constant_pool.get_class_name_invalid(self.this_class),
                 )
                 .field("super", &super_class)
-                // .field("interfaces", &self.interfaces)
-                // .field("field_info", &self.field_info)
-                // .field("method_info", &self.method_info)
-                // .field("attribute_info", &self.attribute_info)
-                .finish();
+                .field("interfaces", &self.interface_names().collect::<Vec<_>>())
+                .field("field_info", &NamedList::new(self, &self.field_info))
+                .field("method_info", &NamedList::new(self, &self.method_info))
+                .field(
+                    "attribute_info",
+                    &NamedList::new(self, &self.attribute_info),
+                )
+                .finish()
         } else {
             f.debug_struct("Class")
                 .field("minor_version", &self.minor_version)
@@ -44,6 +48,93 @@ impl std::fmt::Debug for Class {
     }
 }
 
+struct NamedList<'a, T> {
+    class: &'a Class,
+    items: &'a [T],
+}
+
+impl<'a, T> NamedList<'a, T> {
+    fn new(class: &'a Class, items: &'a [T]) -> Self {
+        Self { class, items }
+    }
+}
+
+impl<'a, T: DebugFmtWithNames> std::fmt::Debug for NamedList<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.items.iter().map(|item| WithNames {
+                class: self.class,
+                item,
+            }))
+            .finish()
+    }
+}
+
+struct WithNames<'a, T> {
+    class: &'a Class,
+    item: &'a T,
+}
+
+impl<'a, T: DebugFmtWithNames> std::fmt::Debug for WithNames<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.item.fmt(self.class, f)
+    }
+}
+
+impl DebugFmtWithNames for FieldEntry {
+    fn fmt(&self, class: &Class, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Field")
+            .field("access_flags", &self.access_flags)
+            .field(
+                "name",
+                &class
+                    .constant_pool
+                    .get_const_utd8_or_invalid(self.name_index),
+            )
+            .field(
+                "descriptor",
+                &class
+                    .constant_pool
+                    .get_const_utd8_or_invalid(self.descriptor_index),
+            )
+            .field("attributes", &NamedList::new(class, &self.attributes))
+            .finish()
+    }
+}
+
+impl DebugFmtWithNames for MethodEntry {
+    fn fmt(&self, class: &Class, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Method")
+            .field("access_flags", &self.access_flags)
+            .field(
+                "name",
+                &class
+                    .constant_pool
+                    .get_const_utd8_or_invalid(self.name_index),
+            )
+            .field(
+                "descriptor",
+                &class
+                    .constant_pool
+                    .get_const_utd8_or_invalid(self.descriptor_index),
+            )
+            .field("attributes", &NamedList::new(class, &self.attributes))
+            .finish()
+    }
+}
+
+impl DebugFmtWithNames for AttributeEntry {
+    fn fmt(&self, class: &Class, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(
+            class
+                .constant_pool
+                .get_const_utd8_or_invalid(self.name_index),
+        )
+        .field("info", &self.info)
+        .finish()
+    }
+}
+
 struct ConstantNamePrint<'a> {
     class: &'a Class,
 }
@@ -100,6 +191,33 @@ impl<'a> std::fmt::Debug for ConstantEntryNamePrint<'a> {
                 .finish()
         }
 
+        fn dynamic_helper(
+            class: &Class,
+            f: &mut std::fmt::Formatter<'_>,
+            bootstrap_method_attr_index: u16,
+            name_and_type_index: u16,
+            struct_name: &str,
+        ) -> Result<(), std::fmt::Error> {
+            let t = class.constant_pool.get_constant(name_and_type_index);
+            let (name, type_str) = match t {
+                Some(ConstantPoolEntry::NameAndType {
+                    name_index,
+                    descriptor_index,
+                }) => (
+                    class.constant_pool.get_const_utd8_or_invalid(*name_index),
+                    class
+                        .constant_pool
+                        .get_const_utd8_or_invalid(*descriptor_index),
+                ),
+                _ => ("##NOT_NAME_AND_TYPE##", "##NOT_NAME_AND_TYPE##"),
+            };
+            f.debug_struct(struct_name)
+                .field("bootstrap_method_attr_index", &bootstrap_method_attr_index)
+                .field("name", &name)
+                .field("type", &type_str)
+                .finish()
+        }
+
         match self.constant {
             ConstantPoolEntry::Empty => f.debug_struct("Empty").finish(),
             ConstantPoolEntry::Class { name_index } => f
@@ -176,6 +294,10 @@ impl<'a> std::fmt::Debug for ConstantEntryNamePrint<'a> {
                 )
                 .finish(),
             ConstantPoolEntry::Utf8(_) => self.constant.fmt(f),
+            ConstantPoolEntry::Utf16(units) => f
+                .debug_tuple("Utf8")
+                .field(&String::from_utf16_lossy(units))
+                .finish(),
             ConstantPoolEntry::MethodHandle {
                 reference_kind,
                 reference_index,
@@ -207,31 +329,46 @@ impl<'a> std::fmt::Debug for ConstantEntryNamePrint<'a> {
                         .get_const_utd8_or_invalid(*descriptor_index),
                 )
                 .finish(),
+            ConstantPoolEntry::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => dynamic_helper(
+                self.class,
+                f,
+                *bootstrap_method_attr_index,
+                *name_and_type_index,
+                "Dynamic",
+            ),
             ConstantPoolEntry::InvokeDynamic {
                 bootstrap_method_attr_index,
                 name_and_type_index,
-            } => {
-                let t = self.class.constant_pool.get_constant(*name_and_type_index);
-                let (name, type_str) = match t {
-                    Some(ConstantPoolEntry::NameAndType {
-                        name_index,
-                        descriptor_index,
-                    }) => (
-                        self.class
-                            .constant_pool
-                            .get_const_utd8_or_invalid(*name_index),
-                        self.class
-                            .constant_pool
-                            .get_const_utd8_or_invalid(*descriptor_index),
-                    ),
-                    _ => ("##NOT_NAME_AND_TYPE##", "##NOT_NAME_AND_TYPE##"),
-                };
-                f.debug_struct("InvokeDynamic")
-                    .field("bootstrap_method_attr_index", bootstrap_method_attr_index)
-                    .field("name", &name)
-                    .field("type", &type_str)
-                    .finish()
-            }
+            } => dynamic_helper(
+                self.class,
+                f,
+                *bootstrap_method_attr_index,
+                *name_and_type_index,
+                "InvokeDynamic",
+            ),
+            ConstantPoolEntry::Module { name_index } => f
+                .debug_struct("Module")
+                .field(
+                    "module_name",
+                    &self
+                        .class
+                        .constant_pool
+                        .get_const_utd8_or_invalid(*name_index),
+                )
+                .finish(),
+            ConstantPoolEntry::Package { name_index } => f
+                .debug_struct("Package")
+                .field(
+                    "package_name",
+                    &self
+                        .class
+                        .constant_pool
+                        .get_const_utd8_or_invalid(*name_index),
+                )
+                .finish(),
         }
     }
 }
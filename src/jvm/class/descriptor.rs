@@ -0,0 +1,349 @@
+//! Field and method descriptors (JVMS 4.3)
+
+use std::fmt::{Display, Formatter};
+
+use super::{constant::ConstantPool, field::FieldEntry, method::MethodEntry, ClassBuilderError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+}
+
+impl BaseType {
+    pub fn from_char(c: u8) -> Option<Self> {
+        Some(match c {
+            b'B' => Self::Byte,
+            b'C' => Self::Char,
+            b'D' => Self::Double,
+            b'F' => Self::Float,
+            b'I' => Self::Int,
+            b'J' => Self::Long,
+            b'S' => Self::Short,
+            b'Z' => Self::Boolean,
+            _ => return None,
+        })
+    }
+
+    pub fn to_char(&self) -> char {
+        match self {
+            Self::Byte => 'B',
+            Self::Char => 'C',
+            Self::Double => 'D',
+            Self::Float => 'F',
+            Self::Int => 'I',
+            Self::Long => 'J',
+            Self::Short => 'S',
+            Self::Boolean => 'Z',
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Byte => "byte",
+            Self::Char => "char",
+            Self::Double => "double",
+            Self::Float => "float",
+            Self::Int => "int",
+            Self::Long => "long",
+            Self::Short => "short",
+            Self::Boolean => "boolean",
+        }
+    }
+
+    /// long and double take two local variable and operand stack slots
+    pub fn slots(&self) -> usize {
+        match self {
+            Self::Long | Self::Double => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    Base(BaseType),
+    /// binary class name with `/` separators
+    Object(String),
+    Array(Box<FieldType>),
+}
+
+impl FieldType {
+    pub fn parse(desc: &str) -> Result<Self, ClassBuilderError> {
+        let mut reader = Reader::new(desc);
+        let ty = Self::read(&mut reader).ok_or(ClassBuilderError::InvalidDescriptor)?;
+        if !reader.is_empty() {
+            return Err(ClassBuilderError::InvalidDescriptor);
+        }
+        Ok(ty)
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        match reader.next()? {
+            b'L' => {
+                let name = reader.take_until(b';')?;
+                if name.is_empty() {
+                    return None;
+                }
+                Some(Self::Object(name.into()))
+            }
+            b'[' => Some(Self::Array(Box::new(Self::read(reader)?))),
+            c => Some(Self::Base(BaseType::from_char(c)?)),
+        }
+    }
+
+    pub fn slots(&self) -> usize {
+        match self {
+            Self::Base(base) => base.slots(),
+            _ => 1,
+        }
+    }
+
+    pub fn is_reference(&self) -> bool {
+        !matches!(self, Self::Base(_))
+    }
+
+    /// The number of array dimensions, zero when this isn't an array
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Self::Array(component) => 1 + component.dimensions(),
+            _ => 0,
+        }
+    }
+
+    /// Renders the type back to descriptor form
+    pub fn descriptor(&self) -> String {
+        match self {
+            Self::Base(base) => base.to_char().to_string(),
+            Self::Object(name) => format!("L{};", name),
+            Self::Array(component) => format!("[{}", component.descriptor()),
+        }
+    }
+}
+
+/// Renders the type as java source would write it
+impl Display for FieldType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Base(base) => write!(f, "{}", base.name()),
+            Self::Object(name) => write!(f, "{}", name.replace('/', ".")),
+            Self::Array(component) => write!(f, "{}[]", component),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodDescriptor {
+    pub parameters: Vec<FieldType>,
+    /// `None` for void
+    pub return_type: Option<FieldType>,
+}
+
+impl MethodDescriptor {
+    pub fn parse(desc: &str) -> Result<Self, ClassBuilderError> {
+        Self::read(&mut Reader::new(desc)).ok_or(ClassBuilderError::InvalidDescriptor)
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        reader.expect(b'(')?;
+        let mut parameters = Vec::new();
+        while reader.peek()? != b')' {
+            parameters.push(FieldType::read(reader)?);
+        }
+        reader.expect(b')')?;
+        let return_type = if reader.peek()? == b'V' {
+            reader.next();
+            None
+        } else {
+            Some(FieldType::read(reader)?)
+        };
+        reader.is_empty().then_some(Self {
+            parameters,
+            return_type,
+        })
+    }
+
+    /// The local variable slots taken by the arguments, not counting `this`
+    pub fn arg_slots(&self) -> usize {
+        self.parameters.iter().map(FieldType::slots).sum()
+    }
+
+    pub fn descriptor(&self) -> String {
+        let mut str = String::from("(");
+        for param in &self.parameters {
+            str.push_str(&param.descriptor());
+        }
+        str.push(')');
+        match &self.return_type {
+            Some(ty) => str.push_str(&ty.descriptor()),
+            None => str.push('V'),
+        }
+        str
+    }
+}
+
+/// Renders as `return_type(param, param)` in java syntax
+impl Display for MethodDescriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.return_type {
+            Some(ty) => write!(f, "{}", ty)?,
+            None => write!(f, "void")?,
+        }
+        write!(f, "(")?;
+        for (i, param) in self.parameters.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", param)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl FieldEntry {
+    pub fn field_type(&self, constants: &ConstantPool) -> Result<FieldType, ClassBuilderError> {
+        let desc = constants
+            .get_const_utd8(self.descriptor_index)
+            .ok_or(ClassBuilderError::InvalidDescriptor)?;
+        FieldType::parse(desc)
+    }
+}
+
+impl MethodEntry {
+    pub fn descriptor(
+        &self,
+        constants: &ConstantPool,
+    ) -> Result<MethodDescriptor, ClassBuilderError> {
+        let desc = constants
+            .get_const_utd8(self.descriptor_index)
+            .ok_or(ClassBuilderError::InvalidDescriptor)?;
+        MethodDescriptor::parse(desc)
+    }
+}
+
+/// Byte cursor shared by the descriptor and signature parsers, everything
+/// significant in either grammar is ascii
+pub(super) struct Reader<'a> {
+    str: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(str: &'a str) -> Self {
+        Self { str, pos: 0 }
+    }
+
+    pub(super) fn peek(&self) -> Option<u8> {
+        self.str.as_bytes().get(self.pos).copied()
+    }
+
+    pub(super) fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    pub(super) fn expect(&mut self, c: u8) -> Option<()> {
+        (self.next()? == c).then_some(())
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.pos >= self.str.len()
+    }
+
+    /// Takes everything up to `end` and skips over `end`
+    pub(super) fn take_until(&mut self, end: u8) -> Option<&'a str> {
+        let len = self.str[self.pos..].bytes().position(|c| c == end)?;
+        let str = &self.str[self.pos..self.pos + len];
+        self.pos += len + 1;
+        Some(str)
+    }
+
+    /// Takes an identifier, stopping before any of `ends`
+    pub(super) fn take_ident(&mut self, ends: &[u8]) -> Option<&'a str> {
+        let len = self.str[self.pos..]
+            .bytes()
+            .position(|c| ends.contains(&c))
+            .unwrap_or(self.str.len() - self.pos);
+        if len == 0 {
+            return None;
+        }
+        let str = &self.str[self.pos..self.pos + len];
+        self.pos += len;
+        Some(str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(name: &str) -> FieldType {
+        FieldType::Object(name.into())
+    }
+
+    #[test]
+    fn parses_field_types() {
+        assert_eq!(
+            FieldType::parse("J").unwrap(),
+            FieldType::Base(BaseType::Long)
+        );
+        assert_eq!(
+            FieldType::parse("[[Ljava/lang/String;").unwrap(),
+            FieldType::Array(Box::new(FieldType::Array(Box::new(object(
+                "java/lang/String"
+            )))))
+        );
+    }
+
+    #[test]
+    fn parses_method_descriptors() {
+        let desc = MethodDescriptor::parse("(IJ[DLjava/lang/Object;)V").unwrap();
+        assert_eq!(
+            desc.parameters,
+            [
+                FieldType::Base(BaseType::Int),
+                FieldType::Base(BaseType::Long),
+                FieldType::Array(Box::new(FieldType::Base(BaseType::Double))),
+                object("java/lang/Object"),
+            ]
+        );
+        assert_eq!(desc.return_type, None);
+        assert_eq!(desc.arg_slots(), 5);
+        assert_eq!(desc.descriptor(), "(IJ[DLjava/lang/Object;)V");
+        assert_eq!(
+            MethodDescriptor::parse("()[I").unwrap().return_type,
+            Some(FieldType::Array(Box::new(FieldType::Base(BaseType::Int))))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_descriptors() {
+        for desc in ["L;", "[", "V", "I;", "Ljava/lang/Object"] {
+            assert!(
+                matches!(
+                    FieldType::parse(desc),
+                    Err(ClassBuilderError::InvalidDescriptor)
+                ),
+                "{}",
+                desc
+            );
+        }
+        for desc in ["(I", "()VV", "()Ljava/lang/Object", "(V)V", "I)V", "()"] {
+            assert!(
+                matches!(
+                    MethodDescriptor::parse(desc),
+                    Err(ClassBuilderError::InvalidDescriptor)
+                ),
+                "{}",
+                desc
+            );
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! `Float.toString` and `Double.toString` as JDK 17 implements them
+//!
+//! Java's `FloatingDecimal` does not always produce the shortest digits
+//! that round trip: `2^30` as a float prints as `1.07374182E9` where Rust
+//! gives `1.0737418E9`. javap prints constants through it, so this is a
+//! port of its conversion, quirks included.
+
+use std::cmp::Ordering;
+
+const EXP_SHIFT: i32 = 52;
+const FRACT_HOB: u64 = 1 << EXP_SHIFT;
+const SIGNIF_MASK: u64 = FRACT_HOB - 1;
+const EXP_ONE: u64 = 0x3FF0_0000_0000_0000;
+const MAX_SMALL_BIN_EXP: i32 = 62;
+const MIN_SMALL_BIN_EXP: i32 = -(63 / 3);
+
+/// Decimal digits below an integer's `2^p2` granularity, by `p2`
+const INSIGNIFICANT_DIGITS: [u32; 64] = [
+    0, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7, 8, 8, 8, 9, 9,
+    9, 9, 10, 10, 10, 11, 11, 11, 12, 12, 12, 12, 13, 13, 13, 14, 14, 14, 15, 15, 15, 15, 16, 16,
+    16, 17, 17, 17, 18, 18, 18, 19,
+];
+
+/// The bit length of `5^i`
+const N_5_BITS: [i32; 27] = [
+    0, 3, 5, 7, 10, 12, 14, 17, 19, 21, 24, 26, 28, 31, 33, 35, 38, 40, 42, 45, 47, 49, 52, 54, 56,
+    59, 61,
+];
+
+pub fn float_to_string(val: f32) -> String {
+    let bits = val.to_bits();
+    let negative = bits >> 31 != 0;
+    let mut fract = bits & 0x007F_FFFF;
+    let exp = ((bits >> 23) & 0xFF) as i32;
+    if exp == 0xFF {
+        return special(negative, fract == 0);
+    }
+    let (bin_exp, significant_bits) = if exp == 0 {
+        if fract == 0 {
+            return zero(negative);
+        }
+        let leading = fract.leading_zeros() as i32;
+        let shift = leading - (31 - 23);
+        fract <<= shift;
+        (1 - shift, 32 - leading)
+    } else {
+        fract |= 1 << 23;
+        (exp, 24)
+    };
+    let fract = (fract as u64) << (EXP_SHIFT - 23);
+    Digits::new(bin_exp - 127, fract, significant_bits).to_java_string(negative)
+}
+
+pub fn double_to_string(val: f64) -> String {
+    let bits = val.to_bits();
+    let negative = bits >> 63 != 0;
+    let mut fract = bits & SIGNIF_MASK;
+    let exp = ((bits >> EXP_SHIFT) & 0x7FF) as i32;
+    if exp == 0x7FF {
+        return special(negative, fract == 0);
+    }
+    let (bin_exp, significant_bits) = if exp == 0 {
+        if fract == 0 {
+            return zero(negative);
+        }
+        let leading = fract.leading_zeros() as i32;
+        let shift = leading - (63 - EXP_SHIFT);
+        fract <<= shift;
+        (1 - shift, 64 - leading)
+    } else {
+        fract |= FRACT_HOB;
+        (exp, EXP_SHIFT + 1)
+    };
+    Digits::new(bin_exp - 1023, fract, significant_bits).to_java_string(negative)
+}
+
+fn special(negative: bool, infinite: bool) -> String {
+    match (infinite, negative) {
+        (false, _) => "NaN",
+        (true, false) => "Infinity",
+        (true, true) => "-Infinity",
+    }
+    .into()
+}
+
+fn zero(negative: bool) -> String {
+    if negative { "-0.0" } else { "0.0" }.into()
+}
+
+fn n_5_bits(i: i32) -> i32 {
+    N_5_BITS.get(i as usize).copied().unwrap_or(i * 3)
+}
+
+/// Decimal digits `0.d1d2d3... * 10^dec_exponent`
+struct Digits {
+    /// ascii digits, may end in zeros after rounding up
+    digits: Vec<u8>,
+    dec_exponent: i32,
+}
+
+impl Digits {
+    /// `FloatingDecimal.BinaryToASCIIBuffer.dtoa`, `fract_bits` is the
+    /// significand with its leading one at bit 52
+    fn new(bin_exp: i32, fract_bits: u64, significant_bits: i32) -> Self {
+        let tail_zeros = fract_bits.trailing_zeros() as i32;
+        let fract_bit_count = EXP_SHIFT + 1 - tail_zeros;
+        let tiny_bits = (fract_bit_count - bin_exp - 1).max(0);
+
+        // integers that fit in a long are printed from their digits
+        if (MIN_SMALL_BIN_EXP..=MAX_SMALL_BIN_EXP).contains(&bin_exp) && tiny_bits == 0 {
+            let insignificant = if bin_exp > significant_bits {
+                let p2 = bin_exp - significant_bits - 1;
+                if p2 > 1 {
+                    INSIGNIFICANT_DIGITS.get(p2 as usize).copied().unwrap_or(0)
+                } else {
+                    0
+                }
+            } else {
+                0
+            };
+            let value = if bin_exp >= EXP_SHIFT {
+                fract_bits << (bin_exp - EXP_SHIFT)
+            } else {
+                fract_bits >> (EXP_SHIFT - bin_exp)
+            };
+            return Self::from_long(value, insignificant);
+        }
+
+        let mut dec_exp = estimate_dec_exp(fract_bits, bin_exp);
+        let b5 = (-dec_exp).max(0);
+        let mut b2 = b5 + tiny_bits + bin_exp;
+        let s5 = dec_exp.max(0);
+        let mut s2 = s5 + tiny_bits;
+        let m5 = b5;
+        let mut m2 = b2 - significant_bits;
+
+        let fract_bits = fract_bits >> tail_zeros;
+        b2 -= fract_bit_count - 1;
+        let common = b2.min(s2);
+        b2 -= common;
+        s2 -= common;
+        m2 -= common;
+        // below a power of two the next smaller value is only half as far
+        if fract_bit_count == 1 {
+            m2 -= 1;
+        }
+        if m2 < 0 {
+            b2 -= m2;
+            s2 -= m2;
+            m2 = 0;
+        }
+
+        // the digits are generated with int, long or big integer
+        // arithmetic, whichever is large enough, and the three differ
+        // slightly in how they stop
+        let b_bits = fract_bit_count + b2 + n_5_bits(b5);
+        let ten_s_bits = s2 + 1 + n_5_bits(s5 + 1);
+        let mut digits = Vec::new();
+        let (low, high, low_digit_difference);
+        if b_bits < 32 && ten_s_bits < 32 {
+            let pow5 = |p: i32| 5i32.pow(p as u32);
+            let mut b = (fract_bits as i32)
+                .wrapping_mul(pow5(b5))
+                .wrapping_shl(b2 as u32);
+            let s = pow5(s5).wrapping_shl(s2 as u32);
+            let mut m = pow5(m5).wrapping_shl(m2 as u32);
+            let tens = s.wrapping_mul(10);
+
+            let q = b / s;
+            b = (b % s).wrapping_mul(10);
+            m = m.wrapping_mul(10);
+            let (mut l, mut h) = (b < m, b.wrapping_add(m) > tens);
+            if q == 0 && !h {
+                dec_exp -= 1;
+            } else {
+                digits.push(b'0' + q as u8);
+            }
+            // the E form always has a digit after the point
+            if !(-3..8).contains(&dec_exp) {
+                (l, h) = (false, false);
+            }
+            while !l && !h {
+                let q = b / s;
+                b = (b % s).wrapping_mul(10);
+                m = m.wrapping_mul(10);
+                if m > 0 {
+                    (l, h) = (b < m, b.wrapping_add(m) > tens);
+                } else {
+                    // m overflowed, so it is certainly past b
+                    (l, h) = (true, true);
+                }
+                digits.push(b'0' + q as u8);
+            }
+            low_digit_difference = (b << 1).wrapping_sub(tens) as i64;
+            (low, high) = (l, h);
+        } else if b_bits < 64 && ten_s_bits < 64 {
+            let pow5 = |p: i32| 5i64.pow(p as u32);
+            let mut b = (fract_bits as i64)
+                .wrapping_mul(pow5(b5))
+                .wrapping_shl(b2 as u32);
+            let s = pow5(s5).wrapping_shl(s2 as u32);
+            let mut m = pow5(m5).wrapping_shl(m2 as u32);
+            let tens = s.wrapping_mul(10);
+
+            let q = b / s;
+            b = (b % s).wrapping_mul(10);
+            m = m.wrapping_mul(10);
+            let (mut l, mut h) = (b < m, b.wrapping_add(m) > tens);
+            if q == 0 && !h {
+                dec_exp -= 1;
+            } else {
+                digits.push(b'0' + q as u8);
+            }
+            if !(-3..8).contains(&dec_exp) {
+                (l, h) = (false, false);
+            }
+            while !l && !h {
+                let q = b / s;
+                b = (b % s).wrapping_mul(10);
+                m = m.wrapping_mul(10);
+                if m > 0 {
+                    (l, h) = (b < m, b.wrapping_add(m) > tens);
+                } else {
+                    (l, h) = (true, true);
+                }
+                digits.push(b'0' + q as u8);
+            }
+            low_digit_difference = (b << 1).wrapping_sub(tens);
+            (low, high) = (l, h);
+        } else {
+            let s = Big::pow52(s5, s2);
+            let mut b = Big::from(fract_bits).mul_pow52(b5, b2);
+            let mut m = Big::pow52(m5 + 1, m2 + 1);
+            let ten_s = Big::pow52(s5 + 1, s2 + 1);
+
+            let q = b.quo_rem_iteration(&s);
+            let (mut l, mut h) = (b < m, b.add(&m) >= ten_s);
+            if q == 0 && !h {
+                dec_exp -= 1;
+            } else {
+                digits.push(b'0' + q);
+            }
+            if !(-3..8).contains(&dec_exp) {
+                (l, h) = (false, false);
+            }
+            while !l && !h {
+                let q = b.quo_rem_iteration(&s);
+                m.mul_small(10);
+                (l, h) = (b < m, b.add(&m) >= ten_s);
+                digits.push(b'0' + q);
+            }
+            low_digit_difference = if h && l {
+                b.mul_small(2);
+                match b.cmp(&ten_s) {
+                    Ordering::Less => -1,
+                    Ordering::Equal => 0,
+                    Ordering::Greater => 1,
+                }
+            } else {
+                0
+            };
+            (low, high) = (l, h);
+        }
+
+        let mut result = Self {
+            digits,
+            dec_exponent: dec_exp + 1,
+        };
+        // the last digit is rounded by how the loop stopped
+        if high {
+            if low {
+                let odd = result.digits.last().is_some_and(|d| d & 1 != 0);
+                if low_digit_difference > 0 || (low_digit_difference == 0 && odd) {
+                    result.round_up();
+                }
+            } else {
+                result.round_up();
+            }
+        }
+        result
+    }
+
+    /// `developLongDigits`, dropping `insignificant` low digits with rounding
+    fn from_long(mut value: u64, insignificant: u32) -> Self {
+        if insignificant != 0 {
+            let pow10 = 10u64.pow(insignificant);
+            let residue = value % pow10;
+            value /= pow10;
+            if residue >= pow10 >> 1 {
+                value += 1;
+            }
+        }
+        let all = value.to_string();
+        let digits = all.trim_end_matches('0').as_bytes().to_vec();
+        Self {
+            digits,
+            dec_exponent: all.len() as i32 + insignificant as i32,
+        }
+    }
+
+    fn round_up(&mut self) {
+        let mut i = self.digits.len() - 1;
+        while self.digits[i] == b'9' && i > 0 {
+            self.digits[i] = b'0';
+            i -= 1;
+        }
+        if self.digits[i] == b'9' {
+            // carried out of the first digit, the rest are zeros already
+            self.dec_exponent += 1;
+            self.digits[0] = b'1';
+        } else {
+            self.digits[i] += 1;
+        }
+    }
+
+    /// `getChars`, plain notation for `10^-3 <= |val| < 10^7` and the E
+    /// form outside of it
+    fn to_java_string(&self, negative: bool) -> String {
+        let digits = std::str::from_utf8(&self.digits).unwrap_or_default();
+        let exp = self.dec_exponent;
+        let mut str = String::from(if negative { "-" } else { "" });
+        if exp > 0 && exp < 8 {
+            let int_len = digits.len().min(exp as usize);
+            str.push_str(&digits[..int_len]);
+            if int_len < exp as usize {
+                str.push_str(&"0".repeat(exp as usize - int_len));
+                str.push_str(".0");
+            } else if int_len < digits.len() {
+                str.push('.');
+                str.push_str(&digits[int_len..]);
+            } else {
+                str.push_str(".0");
+            }
+        } else if exp <= 0 && exp > -3 {
+            str.push_str("0.");
+            str.push_str(&"0".repeat(-exp as usize));
+            str.push_str(digits);
+        } else {
+            str.push_str(&digits[..1]);
+            str.push('.');
+            if digits.len() > 1 {
+                str.push_str(&digits[1..]);
+            } else {
+                str.push('0');
+            }
+            str.push_str(&format!("E{}", exp - 1));
+        }
+        str
+    }
+}
+
+/// `estimateDecExp`, the floor of an approximate `log10(value)`
+// Java's truncated log10(2), not `LOG10_2`, so the estimates agree
+#[allow(clippy::approx_constant)]
+fn estimate_dec_exp(fract_bits: u64, bin_exp: i32) -> i32 {
+    let d2 = f64::from_bits(EXP_ONE | (fract_bits & SIGNIF_MASK));
+    let d = (d2 - 1.5) * 0.289529654 + 0.176091259 + bin_exp as f64 * 0.301029995663981;
+    d.floor() as i32
+}
+
+/// Just enough of an unsigned big integer for `dtoa`, little endian words
+#[derive(Clone, PartialEq, Eq)]
+struct Big(Vec<u32>);
+
+impl From<u64> for Big {
+    fn from(val: u64) -> Self {
+        let mut big = Big(vec![val as u32, (val >> 32) as u32]);
+        big.trim();
+        big
+    }
+}
+
+impl Big {
+    /// `5^p5 * 2^p2`
+    fn pow52(p5: i32, p2: i32) -> Self {
+        Big::from(1).mul_pow52(p5, p2)
+    }
+
+    fn mul_pow52(mut self, p5: i32, p2: i32) -> Self {
+        for _ in 0..p5 {
+            self.mul_small(5);
+        }
+        self.shl(p2 as u32);
+        self
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    fn mul_small(&mut self, factor: u32) {
+        let mut carry = 0u64;
+        for word in &mut self.0 {
+            let product = *word as u64 * factor as u64 + carry;
+            *word = product as u32;
+            carry = product >> 32;
+        }
+        if carry != 0 {
+            self.0.push(carry as u32);
+        }
+    }
+
+    fn shl(&mut self, bits: u32) {
+        let (words, bits) = ((bits / 32) as usize, bits % 32);
+        if bits != 0 {
+            let mut carry = 0;
+            for word in &mut self.0 {
+                let shifted = (*word << bits) | carry;
+                carry = *word >> (32 - bits);
+                *word = shifted;
+            }
+            if carry != 0 {
+                self.0.push(carry);
+            }
+        }
+        self.0.splice(0..0, std::iter::repeat_n(0, words));
+        self.trim();
+    }
+
+    fn add(&self, other: &Big) -> Big {
+        let len = self.0.len().max(other.0.len());
+        let mut sum = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+        for i in 0..len {
+            let total =
+                *self.0.get(i).unwrap_or(&0) as u64 + *other.0.get(i).unwrap_or(&0) as u64 + carry;
+            sum.push(total as u32);
+            carry = total >> 32;
+        }
+        if carry != 0 {
+            sum.push(carry as u32);
+        }
+        Big(sum)
+    }
+
+    /// `self -= other`, `other` may not be larger
+    fn sub(&mut self, other: &Big) {
+        let mut borrow = 0i64;
+        for i in 0..self.0.len() {
+            let diff = self.0[i] as i64 - *other.0.get(i).unwrap_or(&0) as i64 - borrow;
+            self.0[i] = diff as u32;
+            borrow = (diff < 0) as i64;
+        }
+        self.trim();
+    }
+
+    /// Returns `self / s` and leaves `10 * (self % s)`, the quotient is
+    /// a single digit
+    fn quo_rem_iteration(&mut self, s: &Big) -> u8 {
+        let mut q = 0;
+        while *self >= *s {
+            self.sub(s);
+            q += 1;
+        }
+        self.mul_small(10);
+        q
+    }
+}
+
+impl Ord for Big {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for Big {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_values() {
+        assert_eq!(float_to_string(f32::NAN), "NaN");
+        assert_eq!(float_to_string(f32::NEG_INFINITY), "-Infinity");
+        assert_eq!(double_to_string(f64::INFINITY), "Infinity");
+        assert_eq!(double_to_string(-0.0), "-0.0");
+        assert_eq!(float_to_string(0.0), "0.0");
+    }
+
+    #[test]
+    fn plain_and_e_forms() {
+        assert_eq!(float_to_string(100.0), "100.0");
+        assert_eq!(float_to_string(9999999.0), "9999999.0");
+        assert_eq!(float_to_string(1.0E7), "1.0E7");
+        assert_eq!(float_to_string(0.001), "0.001");
+        assert_eq!(float_to_string(9.999999E-4), "9.999999E-4");
+        assert_eq!(double_to_string(0.1), "0.1");
+        assert_eq!(double_to_string(1234567.0), "1234567.0");
+        assert_eq!(double_to_string(123456789.0), "1.23456789E8");
+        assert_eq!(double_to_string(1.0 / 3.0), "0.3333333333333333");
+    }
+
+    #[test]
+    fn extremes() {
+        assert_eq!(float_to_string(f32::MAX), "3.4028235E38");
+        assert_eq!(float_to_string(f32::from_bits(1)), "1.4E-45");
+        assert_eq!(float_to_string(f32::MIN_POSITIVE), "1.17549435E-38");
+        assert_eq!(double_to_string(f64::MAX), "1.7976931348623157E308");
+        assert_eq!(double_to_string(f64::from_bits(1)), "4.9E-324");
+        assert_eq!(
+            double_to_string(f64::MIN_POSITIVE),
+            "2.2250738585072014E-308"
+        );
+    }
+
+    #[test]
+    fn not_always_shortest() {
+        // all of these are what Java prints, not the shortest round trip
+        assert_eq!(float_to_string(1073741824.0), "1.07374182E9");
+        assert_eq!(float_to_string(3.4028235E30), "3.4028236E30");
+        assert_eq!(double_to_string(1.0E23), "9.999999999999999E22");
+        assert_eq!(double_to_string(2.0E22), "2.0E22");
+    }
+}
@@ -0,0 +1,1423 @@
+//! Decoding of the bytecode held in a `Code` attribute
+//!
+//! Every opcode has its own variant in [`Instruction`] carrying its decoded
+//! operands. Branch offsets are kept exactly as they are encoded, relative
+//! to the pc of the branching instruction. The `wide` prefix wraps the
+//! instruction it modifies in [`Instruction::Wide`], the narrow and wide
+//! forms carry their index the same way.
+
+use super::{attribute::AttributeInfo, descriptor::BaseType, ClassBuilderError, ClassFileIter};
+
+pub mod jvm_opcodes {
+    //Constants
+    pub const NOP: u8 = 0x00;
+    pub const ACONST_NULL: u8 = 0x01;
+    pub const ICONST_M1: u8 = 0x02;
+    pub const ICONST_0: u8 = 0x03;
+    pub const ICONST_1: u8 = 0x04;
+    pub const ICONST_2: u8 = 0x05;
+    pub const ICONST_3: u8 = 0x06;
+    pub const ICONST_4: u8 = 0x07;
+    pub const ICONST_5: u8 = 0x08;
+    pub const LCONST_0: u8 = 0x09;
+    pub const LCONST_1: u8 = 0x0a;
+    pub const FCONST_0: u8 = 0x0b;
+    pub const FCONST_1: u8 = 0x0c;
+    pub const FCONST_2: u8 = 0x0d;
+    pub const DCONST_0: u8 = 0x0e;
+    pub const DCONST_1: u8 = 0x0f;
+    pub const BIPUSH: u8 = 0x10;
+    pub const SIPUSH: u8 = 0x11;
+    pub const LDC: u8 = 0x12;
+    pub const LDC_W: u8 = 0x13;
+    pub const LDC2_W: u8 = 0x14;
+
+    //Loads
+    pub const ILOAD: u8 = 0x15;
+    pub const LLOAD: u8 = 0x16;
+    pub const FLOAD: u8 = 0x17;
+    pub const DLOAD: u8 = 0x18;
+    pub const ALOAD: u8 = 0x19;
+    pub const ILOAD_0: u8 = 0x1a;
+    pub const ILOAD_1: u8 = 0x1b;
+    pub const ILOAD_2: u8 = 0x1c;
+    pub const ILOAD_3: u8 = 0x1d;
+    pub const LLOAD_0: u8 = 0x1e;
+    pub const LLOAD_1: u8 = 0x1f;
+    pub const LLOAD_2: u8 = 0x20;
+    pub const LLOAD_3: u8 = 0x21;
+    pub const FLOAD_0: u8 = 0x22;
+    pub const FLOAD_1: u8 = 0x23;
+    pub const FLOAD_2: u8 = 0x24;
+    pub const FLOAD_3: u8 = 0x25;
+    pub const DLOAD_0: u8 = 0x26;
+    pub const DLOAD_1: u8 = 0x27;
+    pub const DLOAD_2: u8 = 0x28;
+    pub const DLOAD_3: u8 = 0x29;
+    pub const ALOAD_0: u8 = 0x2a;
+    pub const ALOAD_1: u8 = 0x2b;
+    pub const ALOAD_2: u8 = 0x2c;
+    pub const ALOAD_3: u8 = 0x2d;
+    pub const IALOAD: u8 = 0x2e;
+    pub const LALOAD: u8 = 0x2f;
+    pub const FALOAD: u8 = 0x30;
+    pub const DALOAD: u8 = 0x31;
+    pub const AALOAD: u8 = 0x32;
+    pub const BALOAD: u8 = 0x33;
+    pub const CALOAD: u8 = 0x34;
+    pub const SALOAD: u8 = 0x35;
+
+    //Stores
+    pub const ISTORE: u8 = 0x36;
+    pub const LSTORE: u8 = 0x37;
+    pub const FSTORE: u8 = 0x38;
+    pub const DSTORE: u8 = 0x39;
+    pub const ASTORE: u8 = 0x3a;
+    pub const ISTORE_0: u8 = 0x3b;
+    pub const ISTORE_1: u8 = 0x3c;
+    pub const ISTORE_2: u8 = 0x3d;
+    pub const ISTORE_3: u8 = 0x3e;
+    pub const LSTORE_0: u8 = 0x3f;
+    pub const LSTORE_1: u8 = 0x40;
+    pub const LSTORE_2: u8 = 0x41;
+    pub const LSTORE_3: u8 = 0x42;
+    pub const FSTORE_0: u8 = 0x43;
+    pub const FSTORE_1: u8 = 0x44;
+    pub const FSTORE_2: u8 = 0x45;
+    pub const FSTORE_3: u8 = 0x46;
+    pub const DSTORE_0: u8 = 0x47;
+    pub const DSTORE_1: u8 = 0x48;
+    pub const DSTORE_2: u8 = 0x49;
+    pub const DSTORE_3: u8 = 0x4a;
+    pub const ASTORE_0: u8 = 0x4b;
+    pub const ASTORE_1: u8 = 0x4c;
+    pub const ASTORE_2: u8 = 0x4d;
+    pub const ASTORE_3: u8 = 0x4e;
+    pub const IASTORE: u8 = 0x4f;
+    pub const LASTORE: u8 = 0x50;
+    pub const FASTORE: u8 = 0x51;
+    pub const DASTORE: u8 = 0x52;
+    pub const AASTORE: u8 = 0x53;
+    pub const BASTORE: u8 = 0x54;
+    pub const CASTORE: u8 = 0x55;
+    pub const SASTORE: u8 = 0x56;
+
+    //Stack
+    pub const POP: u8 = 0x57;
+    pub const POP2: u8 = 0x58;
+    pub const DUP: u8 = 0x59;
+    pub const DUP_X1: u8 = 0x5a;
+    pub const DUP_X2: u8 = 0x5b;
+    pub const DUP2: u8 = 0x5c;
+    pub const DUP2_X1: u8 = 0x5d;
+    pub const DUP2_X2: u8 = 0x5e;
+    pub const SWAP: u8 = 0x5f;
+
+    //Math
+    pub const IADD: u8 = 0x60;
+    pub const LADD: u8 = 0x61;
+    pub const FADD: u8 = 0x62;
+    pub const DADD: u8 = 0x63;
+    pub const ISUB: u8 = 0x64;
+    pub const LSUB: u8 = 0x65;
+    pub const FSUB: u8 = 0x66;
+    pub const DSUB: u8 = 0x67;
+    pub const IMUL: u8 = 0x68;
+    pub const LMUL: u8 = 0x69;
+    pub const FMUL: u8 = 0x6a;
+    pub const DMUL: u8 = 0x6b;
+    pub const IDIV: u8 = 0x6c;
+    pub const LDIV: u8 = 0x6d;
+    pub const FDIV: u8 = 0x6e;
+    pub const DDIV: u8 = 0x6f;
+    pub const IREM: u8 = 0x70;
+    pub const LREM: u8 = 0x71;
+    pub const FREM: u8 = 0x72;
+    pub const DREM: u8 = 0x73;
+    pub const INEG: u8 = 0x74;
+    pub const LNEG: u8 = 0x75;
+    pub const FNEG: u8 = 0x76;
+    pub const DNEG: u8 = 0x77;
+    pub const ISHL: u8 = 0x78;
+    pub const LSHL: u8 = 0x79;
+    pub const ISHR: u8 = 0x7a;
+    pub const LSHR: u8 = 0x7b;
+    pub const IUSHR: u8 = 0x7c;
+    pub const LUSHR: u8 = 0x7d;
+    pub const IAND: u8 = 0x7e;
+    pub const LAND: u8 = 0x7f;
+    pub const IOR: u8 = 0x80;
+    pub const LOR: u8 = 0x81;
+    pub const IXOR: u8 = 0x82;
+    pub const LXOR: u8 = 0x83;
+    pub const IINC: u8 = 0x84;
+
+    //Conversions
+    pub const I2L: u8 = 0x85;
+    pub const I2F: u8 = 0x86;
+    pub const I2D: u8 = 0x87;
+    pub const L2I: u8 = 0x88;
+    pub const L2F: u8 = 0x89;
+    pub const L2D: u8 = 0x8a;
+    pub const F2I: u8 = 0x8b;
+    pub const F2L: u8 = 0x8c;
+    pub const F2D: u8 = 0x8d;
+    pub const D2I: u8 = 0x8e;
+    pub const D2L: u8 = 0x8f;
+    pub const D2F: u8 = 0x90;
+    pub const I2B: u8 = 0x91;
+    pub const I2C: u8 = 0x92;
+    pub const I2S: u8 = 0x93;
+
+    //Comparisons
+    pub const LCMP: u8 = 0x94;
+    pub const FCMPL: u8 = 0x95;
+    pub const FCMPG: u8 = 0x96;
+    pub const DCMPL: u8 = 0x97;
+    pub const DCMPG: u8 = 0x98;
+    pub const IFEQ: u8 = 0x99;
+    pub const IFNE: u8 = 0x9a;
+    pub const IFLT: u8 = 0x9b;
+    pub const IFGE: u8 = 0x9c;
+    pub const IFGT: u8 = 0x9d;
+    pub const IFLE: u8 = 0x9e;
+    pub const IF_ICMPEQ: u8 = 0x9f;
+    pub const IF_ICMPNE: u8 = 0xa0;
+    pub const IF_ICMPLT: u8 = 0xa1;
+    pub const IF_ICMPGE: u8 = 0xa2;
+    pub const IF_ICMPGT: u8 = 0xa3;
+    pub const IF_ICMPLE: u8 = 0xa4;
+    pub const IF_ACMPEQ: u8 = 0xa5;
+    pub const IF_ACMPNE: u8 = 0xa6;
+
+    //Control
+    pub const GOTO: u8 = 0xa7;
+    pub const JSR: u8 = 0xa8;
+    pub const RET: u8 = 0xa9;
+    pub const TABLESWITCH: u8 = 0xaa;
+    pub const LOOKUPSWITCH: u8 = 0xab;
+    pub const IRETURN: u8 = 0xac;
+    pub const LRETURN: u8 = 0xad;
+    pub const FRETURN: u8 = 0xae;
+    pub const DRETURN: u8 = 0xaf;
+    pub const ARETURN: u8 = 0xb0;
+    pub const RETURN: u8 = 0xb1;
+
+    //References
+    pub const GETSTATIC: u8 = 0xb2;
+    pub const PUTSTATIC: u8 = 0xb3;
+    pub const GETFIELD: u8 = 0xb4;
+    pub const PUTFIELD: u8 = 0xb5;
+    pub const INVOKEVIRTUAL: u8 = 0xb6;
+    pub const INVOKESPECIAL: u8 = 0xb7;
+    pub const INVOKESTATIC: u8 = 0xb8;
+    pub const INVOKEINTERFACE: u8 = 0xb9;
+    pub const INVOKEDYNAMIC: u8 = 0xba;
+    pub const NEW: u8 = 0xbb;
+    pub const NEWARRAY: u8 = 0xbc;
+    pub const ANEWARRAY: u8 = 0xbd;
+    pub const ARRAYLENGTH: u8 = 0xbe;
+    pub const ATHROW: u8 = 0xbf;
+    pub const CHECKCAST: u8 = 0xc0;
+    pub const INSTANCEOF: u8 = 0xc1;
+    pub const MONITORENTER: u8 = 0xc2;
+    pub const MONITOREXIT: u8 = 0xc3;
+
+    //Extended
+    pub const WIDE: u8 = 0xc4;
+    pub const MULTIANEWARRAY: u8 = 0xc5;
+    pub const IFNULL: u8 = 0xc6;
+    pub const IFNONNULL: u8 = 0xc7;
+    pub const GOTO_W: u8 = 0xc8;
+    pub const JSR_W: u8 = 0xc9;
+
+    //Reserved
+    pub const BREAKPOINT: u8 = 0xca;
+    pub const IMPDEP1: u8 = 0xfe;
+    pub const IMPDEP2: u8 = 0xff;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayType {
+    Boolean,
+    Char,
+    Float,
+    Double,
+    Byte,
+    Short,
+    Int,
+    Long,
+}
+
+impl ArrayType {
+    pub fn from_u8(val: u8) -> Option<Self> {
+        Some(match val {
+            4 => Self::Boolean,
+            5 => Self::Char,
+            6 => Self::Float,
+            7 => Self::Double,
+            8 => Self::Byte,
+            9 => Self::Short,
+            10 => Self::Int,
+            11 => Self::Long,
+            _ => return None,
+        })
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Boolean => 4,
+            Self::Char => 5,
+            Self::Float => 6,
+            Self::Double => 7,
+            Self::Byte => 8,
+            Self::Short => 9,
+            Self::Int => 10,
+            Self::Long => 11,
+        }
+    }
+
+    pub fn base_type(self) -> BaseType {
+        match self {
+            Self::Boolean => BaseType::Boolean,
+            Self::Char => BaseType::Char,
+            Self::Float => BaseType::Float,
+            Self::Double => BaseType::Double,
+            Self::Byte => BaseType::Byte,
+            Self::Short => BaseType::Short,
+            Self::Int => BaseType::Int,
+            Self::Long => BaseType::Long,
+        }
+    }
+
+    /// The descriptor of the element type, `I` for `T_INT` and so on
+    pub fn descriptor(self) -> char {
+        match self {
+            Self::Boolean => 'Z',
+            Self::Char => 'C',
+            Self::Float => 'F',
+            Self::Double => 'D',
+            Self::Byte => 'B',
+            Self::Short => 'S',
+            Self::Int => 'I',
+            Self::Long => 'J',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Char => "char",
+            Self::Float => "float",
+            Self::Double => "double",
+            Self::Byte => "byte",
+            Self::Short => "short",
+            Self::Int => "int",
+            Self::Long => "long",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    //Constants
+    Nop,
+    AconstNull,
+    IconstM1,
+    Iconst0,
+    Iconst1,
+    Iconst2,
+    Iconst3,
+    Iconst4,
+    Iconst5,
+    Lconst0,
+    Lconst1,
+    Fconst0,
+    Fconst1,
+    Fconst2,
+    Dconst0,
+    Dconst1,
+    Bipush(i8),
+    Sipush(i16),
+    Ldc(u16),
+    LdcW(u16),
+    Ldc2W(u16),
+
+    //Loads
+    Iload(u16),
+    Lload(u16),
+    Fload(u16),
+    Dload(u16),
+    Aload(u16),
+    Iload0,
+    Iload1,
+    Iload2,
+    Iload3,
+    Lload0,
+    Lload1,
+    Lload2,
+    Lload3,
+    Fload0,
+    Fload1,
+    Fload2,
+    Fload3,
+    Dload0,
+    Dload1,
+    Dload2,
+    Dload3,
+    Aload0,
+    Aload1,
+    Aload2,
+    Aload3,
+    Iaload,
+    Laload,
+    Faload,
+    Daload,
+    Aaload,
+    Baload,
+    Caload,
+    Saload,
+
+    //Stores
+    Istore(u16),
+    Lstore(u16),
+    Fstore(u16),
+    Dstore(u16),
+    Astore(u16),
+    Istore0,
+    Istore1,
+    Istore2,
+    Istore3,
+    Lstore0,
+    Lstore1,
+    Lstore2,
+    Lstore3,
+    Fstore0,
+    Fstore1,
+    Fstore2,
+    Fstore3,
+    Dstore0,
+    Dstore1,
+    Dstore2,
+    Dstore3,
+    Astore0,
+    Astore1,
+    Astore2,
+    Astore3,
+    Iastore,
+    Lastore,
+    Fastore,
+    Dastore,
+    Aastore,
+    Bastore,
+    Castore,
+    Sastore,
+
+    //Stack
+    Pop,
+    Pop2,
+    Dup,
+    DupX1,
+    DupX2,
+    Dup2,
+    Dup2X1,
+    Dup2X2,
+    Swap,
+
+    //Math
+    Iadd,
+    Ladd,
+    Fadd,
+    Dadd,
+    Isub,
+    Lsub,
+    Fsub,
+    Dsub,
+    Imul,
+    Lmul,
+    Fmul,
+    Dmul,
+    Idiv,
+    Ldiv,
+    Fdiv,
+    Ddiv,
+    Irem,
+    Lrem,
+    Frem,
+    Drem,
+    Ineg,
+    Lneg,
+    Fneg,
+    Dneg,
+    Ishl,
+    Lshl,
+    Ishr,
+    Lshr,
+    Iushr,
+    Lushr,
+    Iand,
+    Land,
+    Ior,
+    Lor,
+    Ixor,
+    Lxor,
+    Iinc {
+        index: u16,
+        value: i16,
+    },
+
+    //Conversions
+    I2l,
+    I2f,
+    I2d,
+    L2i,
+    L2f,
+    L2d,
+    F2i,
+    F2l,
+    F2d,
+    D2i,
+    D2l,
+    D2f,
+    I2b,
+    I2c,
+    I2s,
+
+    //Comparisons
+    Lcmp,
+    Fcmpl,
+    Fcmpg,
+    Dcmpl,
+    Dcmpg,
+    Ifeq(i16),
+    Ifne(i16),
+    Iflt(i16),
+    Ifge(i16),
+    Ifgt(i16),
+    Ifle(i16),
+    IfIcmpeq(i16),
+    IfIcmpne(i16),
+    IfIcmplt(i16),
+    IfIcmpge(i16),
+    IfIcmpgt(i16),
+    IfIcmple(i16),
+    IfAcmpeq(i16),
+    IfAcmpne(i16),
+
+    //Control
+    Goto(i16),
+    Jsr(i16),
+    Ret(u16),
+    Tableswitch {
+        default: i32,
+        low: i32,
+        high: i32,
+        //len high - low + 1
+        offsets: Vec<i32>,
+    },
+    Lookupswitch {
+        default: i32,
+        //len u32, sorted by match
+        pairs: Vec<(i32, i32)>,
+    },
+    Ireturn,
+    Lreturn,
+    Freturn,
+    Dreturn,
+    Areturn,
+    Return,
+
+    //References
+    Getstatic(u16),
+    Putstatic(u16),
+    Getfield(u16),
+    Putfield(u16),
+    Invokevirtual(u16),
+    Invokespecial(u16),
+    Invokestatic(u16),
+    Invokeinterface {
+        index: u16,
+        count: u8,
+    },
+    Invokedynamic(u16),
+    New(u16),
+    Newarray(ArrayType),
+    Anewarray(u16),
+    Arraylength,
+    Athrow,
+    Checkcast(u16),
+    Instanceof(u16),
+    Monitorenter,
+    Monitorexit,
+
+    //Extended
+    /// An index load or store, `ret` or `iinc` behind the `wide` prefix,
+    /// which widens its index and increment to 16 bits
+    Wide(Box<Instruction>),
+    Multianewarray {
+        index: u16,
+        dimensions: u8,
+    },
+    Ifnull(i16),
+    Ifnonnull(i16),
+    GotoW(i32),
+    JsrW(i32),
+
+    //Reserved
+    Breakpoint,
+    Impdep1,
+    Impdep2,
+}
+
+impl Instruction {
+    /// Decodes the instruction starting at `pc`, returning it with its
+    /// length in bytes. `code` has to be the whole code array since the
+    /// switch instructions are padded relative to its start
+    pub fn decode(code: &[u8], pc: usize) -> Result<(Self, usize), ClassBuilderError> {
+        use jvm_opcodes::*;
+        let mut iter = ClassFileIter {
+            slice: code,
+            index: pc,
+        };
+        let op = iter.next_u8()?;
+        let ins = match op {
+            NOP => Self::Nop,
+            ACONST_NULL => Self::AconstNull,
+            ICONST_M1 => Self::IconstM1,
+            ICONST_0 => Self::Iconst0,
+            ICONST_1 => Self::Iconst1,
+            ICONST_2 => Self::Iconst2,
+            ICONST_3 => Self::Iconst3,
+            ICONST_4 => Self::Iconst4,
+            ICONST_5 => Self::Iconst5,
+            LCONST_0 => Self::Lconst0,
+            LCONST_1 => Self::Lconst1,
+            FCONST_0 => Self::Fconst0,
+            FCONST_1 => Self::Fconst1,
+            FCONST_2 => Self::Fconst2,
+            DCONST_0 => Self::Dconst0,
+            DCONST_1 => Self::Dconst1,
+            BIPUSH => Self::Bipush(iter.next_u8()? as i8),
+            SIPUSH => Self::Sipush(iter.next_u16()? as i16),
+            LDC => Self::Ldc(iter.next_u8()? as u16),
+            LDC_W => Self::LdcW(iter.next_u16()?),
+            LDC2_W => Self::Ldc2W(iter.next_u16()?),
+            ILOAD => Self::Iload(iter.next_u8()? as u16),
+            LLOAD => Self::Lload(iter.next_u8()? as u16),
+            FLOAD => Self::Fload(iter.next_u8()? as u16),
+            DLOAD => Self::Dload(iter.next_u8()? as u16),
+            ALOAD => Self::Aload(iter.next_u8()? as u16),
+            ILOAD_0 => Self::Iload0,
+            ILOAD_1 => Self::Iload1,
+            ILOAD_2 => Self::Iload2,
+            ILOAD_3 => Self::Iload3,
+            LLOAD_0 => Self::Lload0,
+            LLOAD_1 => Self::Lload1,
+            LLOAD_2 => Self::Lload2,
+            LLOAD_3 => Self::Lload3,
+            FLOAD_0 => Self::Fload0,
+            FLOAD_1 => Self::Fload1,
+            FLOAD_2 => Self::Fload2,
+            FLOAD_3 => Self::Fload3,
+            DLOAD_0 => Self::Dload0,
+            DLOAD_1 => Self::Dload1,
+            DLOAD_2 => Self::Dload2,
+            DLOAD_3 => Self::Dload3,
+            ALOAD_0 => Self::Aload0,
+            ALOAD_1 => Self::Aload1,
+            ALOAD_2 => Self::Aload2,
+            ALOAD_3 => Self::Aload3,
+            IALOAD => Self::Iaload,
+            LALOAD => Self::Laload,
+            FALOAD => Self::Faload,
+            DALOAD => Self::Daload,
+            AALOAD => Self::Aaload,
+            BALOAD => Self::Baload,
+            CALOAD => Self::Caload,
+            SALOAD => Self::Saload,
+            ISTORE => Self::Istore(iter.next_u8()? as u16),
+            LSTORE => Self::Lstore(iter.next_u8()? as u16),
+            FSTORE => Self::Fstore(iter.next_u8()? as u16),
+            DSTORE => Self::Dstore(iter.next_u8()? as u16),
+            ASTORE => Self::Astore(iter.next_u8()? as u16),
+            ISTORE_0 => Self::Istore0,
+            ISTORE_1 => Self::Istore1,
+            ISTORE_2 => Self::Istore2,
+            ISTORE_3 => Self::Istore3,
+            LSTORE_0 => Self::Lstore0,
+            LSTORE_1 => Self::Lstore1,
+            LSTORE_2 => Self::Lstore2,
+            LSTORE_3 => Self::Lstore3,
+            FSTORE_0 => Self::Fstore0,
+            FSTORE_1 => Self::Fstore1,
+            FSTORE_2 => Self::Fstore2,
+            FSTORE_3 => Self::Fstore3,
+            DSTORE_0 => Self::Dstore0,
+            DSTORE_1 => Self::Dstore1,
+            DSTORE_2 => Self::Dstore2,
+            DSTORE_3 => Self::Dstore3,
+            ASTORE_0 => Self::Astore0,
+            ASTORE_1 => Self::Astore1,
+            ASTORE_2 => Self::Astore2,
+            ASTORE_3 => Self::Astore3,
+            IASTORE => Self::Iastore,
+            LASTORE => Self::Lastore,
+            FASTORE => Self::Fastore,
+            DASTORE => Self::Dastore,
+            AASTORE => Self::Aastore,
+            BASTORE => Self::Bastore,
+            CASTORE => Self::Castore,
+            SASTORE => Self::Sastore,
+            POP => Self::Pop,
+            POP2 => Self::Pop2,
+            DUP => Self::Dup,
+            DUP_X1 => Self::DupX1,
+            DUP_X2 => Self::DupX2,
+            DUP2 => Self::Dup2,
+            DUP2_X1 => Self::Dup2X1,
+            DUP2_X2 => Self::Dup2X2,
+            SWAP => Self::Swap,
+            IADD => Self::Iadd,
+            LADD => Self::Ladd,
+            FADD => Self::Fadd,
+            DADD => Self::Dadd,
+            ISUB => Self::Isub,
+            LSUB => Self::Lsub,
+            FSUB => Self::Fsub,
+            DSUB => Self::Dsub,
+            IMUL => Self::Imul,
+            LMUL => Self::Lmul,
+            FMUL => Self::Fmul,
+            DMUL => Self::Dmul,
+            IDIV => Self::Idiv,
+            LDIV => Self::Ldiv,
+            FDIV => Self::Fdiv,
+            DDIV => Self::Ddiv,
+            IREM => Self::Irem,
+            LREM => Self::Lrem,
+            FREM => Self::Frem,
+            DREM => Self::Drem,
+            INEG => Self::Ineg,
+            LNEG => Self::Lneg,
+            FNEG => Self::Fneg,
+            DNEG => Self::Dneg,
+            ISHL => Self::Ishl,
+            LSHL => Self::Lshl,
+            ISHR => Self::Ishr,
+            LSHR => Self::Lshr,
+            IUSHR => Self::Iushr,
+            LUSHR => Self::Lushr,
+            IAND => Self::Iand,
+            LAND => Self::Land,
+            IOR => Self::Ior,
+            LOR => Self::Lor,
+            IXOR => Self::Ixor,
+            LXOR => Self::Lxor,
+            IINC => Self::Iinc {
+                index: iter.next_u8()? as u16,
+                value: iter.next_u8()? as i8 as i16,
+            },
+            I2L => Self::I2l,
+            I2F => Self::I2f,
+            I2D => Self::I2d,
+            L2I => Self::L2i,
+            L2F => Self::L2f,
+            L2D => Self::L2d,
+            F2I => Self::F2i,
+            F2L => Self::F2l,
+            F2D => Self::F2d,
+            D2I => Self::D2i,
+            D2L => Self::D2l,
+            D2F => Self::D2f,
+            I2B => Self::I2b,
+            I2C => Self::I2c,
+            I2S => Self::I2s,
+            LCMP => Self::Lcmp,
+            FCMPL => Self::Fcmpl,
+            FCMPG => Self::Fcmpg,
+            DCMPL => Self::Dcmpl,
+            DCMPG => Self::Dcmpg,
+            IFEQ => Self::Ifeq(iter.next_u16()? as i16),
+            IFNE => Self::Ifne(iter.next_u16()? as i16),
+            IFLT => Self::Iflt(iter.next_u16()? as i16),
+            IFGE => Self::Ifge(iter.next_u16()? as i16),
+            IFGT => Self::Ifgt(iter.next_u16()? as i16),
+            IFLE => Self::Ifle(iter.next_u16()? as i16),
+            IF_ICMPEQ => Self::IfIcmpeq(iter.next_u16()? as i16),
+            IF_ICMPNE => Self::IfIcmpne(iter.next_u16()? as i16),
+            IF_ICMPLT => Self::IfIcmplt(iter.next_u16()? as i16),
+            IF_ICMPGE => Self::IfIcmpge(iter.next_u16()? as i16),
+            IF_ICMPGT => Self::IfIcmpgt(iter.next_u16()? as i16),
+            IF_ICMPLE => Self::IfIcmple(iter.next_u16()? as i16),
+            IF_ACMPEQ => Self::IfAcmpeq(iter.next_u16()? as i16),
+            IF_ACMPNE => Self::IfAcmpne(iter.next_u16()? as i16),
+            GOTO => Self::Goto(iter.next_u16()? as i16),
+            JSR => Self::Jsr(iter.next_u16()? as i16),
+            RET => Self::Ret(iter.next_u8()? as u16),
+            TABLESWITCH => {
+                iter.index += padding(pc);
+                let default = iter.next_u32()? as i32;
+                let low = iter.next_u32()? as i32;
+                let high = iter.next_u32()? as i32;
+                if high < low {
+                    return Err(ClassBuilderError::InvalidSwitch);
+                }
+                // the range comes from the class file, only trust it as far
+                // as there are bytes left to hold the offsets
+                let count = (high as i64 - low as i64 + 1) as usize;
+                let mut offsets = Vec::with_capacity(count.min(iter.remaining() / 4));
+                for _ in low..=high {
+                    offsets.push(iter.next_u32()? as i32);
+                }
+                Self::Tableswitch {
+                    default,
+                    low,
+                    high,
+                    offsets,
+                }
+            }
+            LOOKUPSWITCH => {
+                iter.index += padding(pc);
+                let default = iter.next_u32()? as i32;
+                let npairs = iter.next_u32()? as i32;
+                if npairs < 0 {
+                    return Err(ClassBuilderError::InvalidSwitch);
+                }
+                let mut pairs = Vec::with_capacity((npairs as usize).min(iter.remaining() / 8));
+                for _ in 0..npairs {
+                    pairs.push((iter.next_u32()? as i32, iter.next_u32()? as i32));
+                }
+                Self::Lookupswitch { default, pairs }
+            }
+            IRETURN => Self::Ireturn,
+            LRETURN => Self::Lreturn,
+            FRETURN => Self::Freturn,
+            DRETURN => Self::Dreturn,
+            ARETURN => Self::Areturn,
+            RETURN => Self::Return,
+            GETSTATIC => Self::Getstatic(iter.next_u16()?),
+            PUTSTATIC => Self::Putstatic(iter.next_u16()?),
+            GETFIELD => Self::Getfield(iter.next_u16()?),
+            PUTFIELD => Self::Putfield(iter.next_u16()?),
+            INVOKEVIRTUAL => Self::Invokevirtual(iter.next_u16()?),
+            INVOKESPECIAL => Self::Invokespecial(iter.next_u16()?),
+            INVOKESTATIC => Self::Invokestatic(iter.next_u16()?),
+            INVOKEINTERFACE => {
+                let ins = Self::Invokeinterface {
+                    index: iter.next_u16()?,
+                    count: iter.next_u8()?,
+                };
+                // always zero
+                iter.next_u8()?;
+                ins
+            }
+            INVOKEDYNAMIC => {
+                let ins = Self::Invokedynamic(iter.next_u16()?);
+                // always zero
+                iter.next_u16()?;
+                ins
+            }
+            NEW => Self::New(iter.next_u16()?),
+            NEWARRAY => {
+                let atype = iter.next_u8()?;
+                Self::Newarray(
+                    ArrayType::from_u8(atype).ok_or(ClassBuilderError::InvalidArrayType(atype))?,
+                )
+            }
+            ANEWARRAY => Self::Anewarray(iter.next_u16()?),
+            ARRAYLENGTH => Self::Arraylength,
+            ATHROW => Self::Athrow,
+            CHECKCAST => Self::Checkcast(iter.next_u16()?),
+            INSTANCEOF => Self::Instanceof(iter.next_u16()?),
+            MONITORENTER => Self::Monitorenter,
+            MONITOREXIT => Self::Monitorexit,
+            WIDE => Self::Wide(Box::new(match iter.next_u8()? {
+                ILOAD => Self::Iload(iter.next_u16()?),
+                LLOAD => Self::Lload(iter.next_u16()?),
+                FLOAD => Self::Fload(iter.next_u16()?),
+                DLOAD => Self::Dload(iter.next_u16()?),
+                ALOAD => Self::Aload(iter.next_u16()?),
+                ISTORE => Self::Istore(iter.next_u16()?),
+                LSTORE => Self::Lstore(iter.next_u16()?),
+                FSTORE => Self::Fstore(iter.next_u16()?),
+                DSTORE => Self::Dstore(iter.next_u16()?),
+                ASTORE => Self::Astore(iter.next_u16()?),
+                RET => Self::Ret(iter.next_u16()?),
+                IINC => Self::Iinc {
+                    index: iter.next_u16()?,
+                    value: iter.next_u16()? as i16,
+                },
+                op => return Err(ClassBuilderError::InvalidWideOpcode(op)),
+            })),
+            MULTIANEWARRAY => Self::Multianewarray {
+                index: iter.next_u16()?,
+                dimensions: iter.next_u8()?,
+            },
+            IFNULL => Self::Ifnull(iter.next_u16()? as i16),
+            IFNONNULL => Self::Ifnonnull(iter.next_u16()? as i16),
+            GOTO_W => Self::GotoW(iter.next_u32()? as i32),
+            JSR_W => Self::JsrW(iter.next_u32()? as i32),
+            BREAKPOINT => Self::Breakpoint,
+            IMPDEP1 => Self::Impdep1,
+            IMPDEP2 => Self::Impdep2,
+            op => return Err(ClassBuilderError::InvalidOpcode(op)),
+        };
+        Ok((ins, iter.index - pc))
+    }
+
+    pub fn opcode(&self) -> u8 {
+        use jvm_opcodes::*;
+        match self {
+            Self::Nop { .. } => NOP,
+            Self::AconstNull { .. } => ACONST_NULL,
+            Self::IconstM1 { .. } => ICONST_M1,
+            Self::Iconst0 { .. } => ICONST_0,
+            Self::Iconst1 { .. } => ICONST_1,
+            Self::Iconst2 { .. } => ICONST_2,
+            Self::Iconst3 { .. } => ICONST_3,
+            Self::Iconst4 { .. } => ICONST_4,
+            Self::Iconst5 { .. } => ICONST_5,
+            Self::Lconst0 { .. } => LCONST_0,
+            Self::Lconst1 { .. } => LCONST_1,
+            Self::Fconst0 { .. } => FCONST_0,
+            Self::Fconst1 { .. } => FCONST_1,
+            Self::Fconst2 { .. } => FCONST_2,
+            Self::Dconst0 { .. } => DCONST_0,
+            Self::Dconst1 { .. } => DCONST_1,
+            Self::Bipush { .. } => BIPUSH,
+            Self::Sipush { .. } => SIPUSH,
+            Self::Ldc { .. } => LDC,
+            Self::LdcW { .. } => LDC_W,
+            Self::Ldc2W { .. } => LDC2_W,
+            Self::Iload { .. } => ILOAD,
+            Self::Lload { .. } => LLOAD,
+            Self::Fload { .. } => FLOAD,
+            Self::Dload { .. } => DLOAD,
+            Self::Aload { .. } => ALOAD,
+            Self::Iload0 { .. } => ILOAD_0,
+            Self::Iload1 { .. } => ILOAD_1,
+            Self::Iload2 { .. } => ILOAD_2,
+            Self::Iload3 { .. } => ILOAD_3,
+            Self::Lload0 { .. } => LLOAD_0,
+            Self::Lload1 { .. } => LLOAD_1,
+            Self::Lload2 { .. } => LLOAD_2,
+            Self::Lload3 { .. } => LLOAD_3,
+            Self::Fload0 { .. } => FLOAD_0,
+            Self::Fload1 { .. } => FLOAD_1,
+            Self::Fload2 { .. } => FLOAD_2,
+            Self::Fload3 { .. } => FLOAD_3,
+            Self::Dload0 { .. } => DLOAD_0,
+            Self::Dload1 { .. } => DLOAD_1,
+            Self::Dload2 { .. } => DLOAD_2,
+            Self::Dload3 { .. } => DLOAD_3,
+            Self::Aload0 { .. } => ALOAD_0,
+            Self::Aload1 { .. } => ALOAD_1,
+            Self::Aload2 { .. } => ALOAD_2,
+            Self::Aload3 { .. } => ALOAD_3,
+            Self::Iaload { .. } => IALOAD,
+            Self::Laload { .. } => LALOAD,
+            Self::Faload { .. } => FALOAD,
+            Self::Daload { .. } => DALOAD,
+            Self::Aaload { .. } => AALOAD,
+            Self::Baload { .. } => BALOAD,
+            Self::Caload { .. } => CALOAD,
+            Self::Saload { .. } => SALOAD,
+            Self::Istore { .. } => ISTORE,
+            Self::Lstore { .. } => LSTORE,
+            Self::Fstore { .. } => FSTORE,
+            Self::Dstore { .. } => DSTORE,
+            Self::Astore { .. } => ASTORE,
+            Self::Istore0 { .. } => ISTORE_0,
+            Self::Istore1 { .. } => ISTORE_1,
+            Self::Istore2 { .. } => ISTORE_2,
+            Self::Istore3 { .. } => ISTORE_3,
+            Self::Lstore0 { .. } => LSTORE_0,
+            Self::Lstore1 { .. } => LSTORE_1,
+            Self::Lstore2 { .. } => LSTORE_2,
+            Self::Lstore3 { .. } => LSTORE_3,
+            Self::Fstore0 { .. } => FSTORE_0,
+            Self::Fstore1 { .. } => FSTORE_1,
+            Self::Fstore2 { .. } => FSTORE_2,
+            Self::Fstore3 { .. } => FSTORE_3,
+            Self::Dstore0 { .. } => DSTORE_0,
+            Self::Dstore1 { .. } => DSTORE_1,
+            Self::Dstore2 { .. } => DSTORE_2,
+            Self::Dstore3 { .. } => DSTORE_3,
+            Self::Astore0 { .. } => ASTORE_0,
+            Self::Astore1 { .. } => ASTORE_1,
+            Self::Astore2 { .. } => ASTORE_2,
+            Self::Astore3 { .. } => ASTORE_3,
+            Self::Iastore { .. } => IASTORE,
+            Self::Lastore { .. } => LASTORE,
+            Self::Fastore { .. } => FASTORE,
+            Self::Dastore { .. } => DASTORE,
+            Self::Aastore { .. } => AASTORE,
+            Self::Bastore { .. } => BASTORE,
+            Self::Castore { .. } => CASTORE,
+            Self::Sastore { .. } => SASTORE,
+            Self::Pop { .. } => POP,
+            Self::Pop2 { .. } => POP2,
+            Self::Dup { .. } => DUP,
+            Self::DupX1 { .. } => DUP_X1,
+            Self::DupX2 { .. } => DUP_X2,
+            Self::Dup2 { .. } => DUP2,
+            Self::Dup2X1 { .. } => DUP2_X1,
+            Self::Dup2X2 { .. } => DUP2_X2,
+            Self::Swap { .. } => SWAP,
+            Self::Iadd { .. } => IADD,
+            Self::Ladd { .. } => LADD,
+            Self::Fadd { .. } => FADD,
+            Self::Dadd { .. } => DADD,
+            Self::Isub { .. } => ISUB,
+            Self::Lsub { .. } => LSUB,
+            Self::Fsub { .. } => FSUB,
+            Self::Dsub { .. } => DSUB,
+            Self::Imul { .. } => IMUL,
+            Self::Lmul { .. } => LMUL,
+            Self::Fmul { .. } => FMUL,
+            Self::Dmul { .. } => DMUL,
+            Self::Idiv { .. } => IDIV,
+            Self::Ldiv { .. } => LDIV,
+            Self::Fdiv { .. } => FDIV,
+            Self::Ddiv { .. } => DDIV,
+            Self::Irem { .. } => IREM,
+            Self::Lrem { .. } => LREM,
+            Self::Frem { .. } => FREM,
+            Self::Drem { .. } => DREM,
+            Self::Ineg { .. } => INEG,
+            Self::Lneg { .. } => LNEG,
+            Self::Fneg { .. } => FNEG,
+            Self::Dneg { .. } => DNEG,
+            Self::Ishl { .. } => ISHL,
+            Self::Lshl { .. } => LSHL,
+            Self::Ishr { .. } => ISHR,
+            Self::Lshr { .. } => LSHR,
+            Self::Iushr { .. } => IUSHR,
+            Self::Lushr { .. } => LUSHR,
+            Self::Iand { .. } => IAND,
+            Self::Land { .. } => LAND,
+            Self::Ior { .. } => IOR,
+            Self::Lor { .. } => LOR,
+            Self::Ixor { .. } => IXOR,
+            Self::Lxor { .. } => LXOR,
+            Self::Iinc { .. } => IINC,
+            Self::I2l { .. } => I2L,
+            Self::I2f { .. } => I2F,
+            Self::I2d { .. } => I2D,
+            Self::L2i { .. } => L2I,
+            Self::L2f { .. } => L2F,
+            Self::L2d { .. } => L2D,
+            Self::F2i { .. } => F2I,
+            Self::F2l { .. } => F2L,
+            Self::F2d { .. } => F2D,
+            Self::D2i { .. } => D2I,
+            Self::D2l { .. } => D2L,
+            Self::D2f { .. } => D2F,
+            Self::I2b { .. } => I2B,
+            Self::I2c { .. } => I2C,
+            Self::I2s { .. } => I2S,
+            Self::Lcmp { .. } => LCMP,
+            Self::Fcmpl { .. } => FCMPL,
+            Self::Fcmpg { .. } => FCMPG,
+            Self::Dcmpl { .. } => DCMPL,
+            Self::Dcmpg { .. } => DCMPG,
+            Self::Ifeq { .. } => IFEQ,
+            Self::Ifne { .. } => IFNE,
+            Self::Iflt { .. } => IFLT,
+            Self::Ifge { .. } => IFGE,
+            Self::Ifgt { .. } => IFGT,
+            Self::Ifle { .. } => IFLE,
+            Self::IfIcmpeq { .. } => IF_ICMPEQ,
+            Self::IfIcmpne { .. } => IF_ICMPNE,
+            Self::IfIcmplt { .. } => IF_ICMPLT,
+            Self::IfIcmpge { .. } => IF_ICMPGE,
+            Self::IfIcmpgt { .. } => IF_ICMPGT,
+            Self::IfIcmple { .. } => IF_ICMPLE,
+            Self::IfAcmpeq { .. } => IF_ACMPEQ,
+            Self::IfAcmpne { .. } => IF_ACMPNE,
+            Self::Goto { .. } => GOTO,
+            Self::Jsr { .. } => JSR,
+            Self::Ret { .. } => RET,
+            Self::Tableswitch { .. } => TABLESWITCH,
+            Self::Lookupswitch { .. } => LOOKUPSWITCH,
+            Self::Ireturn { .. } => IRETURN,
+            Self::Lreturn { .. } => LRETURN,
+            Self::Freturn { .. } => FRETURN,
+            Self::Dreturn { .. } => DRETURN,
+            Self::Areturn { .. } => ARETURN,
+            Self::Return { .. } => RETURN,
+            Self::Getstatic { .. } => GETSTATIC,
+            Self::Putstatic { .. } => PUTSTATIC,
+            Self::Getfield { .. } => GETFIELD,
+            Self::Putfield { .. } => PUTFIELD,
+            Self::Invokevirtual { .. } => INVOKEVIRTUAL,
+            Self::Invokespecial { .. } => INVOKESPECIAL,
+            Self::Invokestatic { .. } => INVOKESTATIC,
+            Self::Invokeinterface { .. } => INVOKEINTERFACE,
+            Self::Invokedynamic { .. } => INVOKEDYNAMIC,
+            Self::New { .. } => NEW,
+            Self::Newarray { .. } => NEWARRAY,
+            Self::Anewarray { .. } => ANEWARRAY,
+            Self::Arraylength { .. } => ARRAYLENGTH,
+            Self::Athrow { .. } => ATHROW,
+            Self::Checkcast { .. } => CHECKCAST,
+            Self::Instanceof { .. } => INSTANCEOF,
+            Self::Monitorenter { .. } => MONITORENTER,
+            Self::Monitorexit { .. } => MONITOREXIT,
+            Self::Wide { .. } => WIDE,
+            Self::Multianewarray { .. } => MULTIANEWARRAY,
+            Self::Ifnull { .. } => IFNULL,
+            Self::Ifnonnull { .. } => IFNONNULL,
+            Self::GotoW { .. } => GOTO_W,
+            Self::JsrW { .. } => JSR_W,
+            Self::Breakpoint { .. } => BREAKPOINT,
+            Self::Impdep1 { .. } => IMPDEP1,
+            Self::Impdep2 { .. } => IMPDEP2,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Nop { .. } => "nop",
+            Self::AconstNull { .. } => "aconst_null",
+            Self::IconstM1 { .. } => "iconst_m1",
+            Self::Iconst0 { .. } => "iconst_0",
+            Self::Iconst1 { .. } => "iconst_1",
+            Self::Iconst2 { .. } => "iconst_2",
+            Self::Iconst3 { .. } => "iconst_3",
+            Self::Iconst4 { .. } => "iconst_4",
+            Self::Iconst5 { .. } => "iconst_5",
+            Self::Lconst0 { .. } => "lconst_0",
+            Self::Lconst1 { .. } => "lconst_1",
+            Self::Fconst0 { .. } => "fconst_0",
+            Self::Fconst1 { .. } => "fconst_1",
+            Self::Fconst2 { .. } => "fconst_2",
+            Self::Dconst0 { .. } => "dconst_0",
+            Self::Dconst1 { .. } => "dconst_1",
+            Self::Bipush { .. } => "bipush",
+            Self::Sipush { .. } => "sipush",
+            Self::Ldc { .. } => "ldc",
+            Self::LdcW { .. } => "ldc_w",
+            Self::Ldc2W { .. } => "ldc2_w",
+            Self::Iload { .. } => "iload",
+            Self::Lload { .. } => "lload",
+            Self::Fload { .. } => "fload",
+            Self::Dload { .. } => "dload",
+            Self::Aload { .. } => "aload",
+            Self::Iload0 { .. } => "iload_0",
+            Self::Iload1 { .. } => "iload_1",
+            Self::Iload2 { .. } => "iload_2",
+            Self::Iload3 { .. } => "iload_3",
+            Self::Lload0 { .. } => "lload_0",
+            Self::Lload1 { .. } => "lload_1",
+            Self::Lload2 { .. } => "lload_2",
+            Self::Lload3 { .. } => "lload_3",
+            Self::Fload0 { .. } => "fload_0",
+            Self::Fload1 { .. } => "fload_1",
+            Self::Fload2 { .. } => "fload_2",
+            Self::Fload3 { .. } => "fload_3",
+            Self::Dload0 { .. } => "dload_0",
+            Self::Dload1 { .. } => "dload_1",
+            Self::Dload2 { .. } => "dload_2",
+            Self::Dload3 { .. } => "dload_3",
+            Self::Aload0 { .. } => "aload_0",
+            Self::Aload1 { .. } => "aload_1",
+            Self::Aload2 { .. } => "aload_2",
+            Self::Aload3 { .. } => "aload_3",
+            Self::Iaload { .. } => "iaload",
+            Self::Laload { .. } => "laload",
+            Self::Faload { .. } => "faload",
+            Self::Daload { .. } => "daload",
+            Self::Aaload { .. } => "aaload",
+            Self::Baload { .. } => "baload",
+            Self::Caload { .. } => "caload",
+            Self::Saload { .. } => "saload",
+            Self::Istore { .. } => "istore",
+            Self::Lstore { .. } => "lstore",
+            Self::Fstore { .. } => "fstore",
+            Self::Dstore { .. } => "dstore",
+            Self::Astore { .. } => "astore",
+            Self::Istore0 { .. } => "istore_0",
+            Self::Istore1 { .. } => "istore_1",
+            Self::Istore2 { .. } => "istore_2",
+            Self::Istore3 { .. } => "istore_3",
+            Self::Lstore0 { .. } => "lstore_0",
+            Self::Lstore1 { .. } => "lstore_1",
+            Self::Lstore2 { .. } => "lstore_2",
+            Self::Lstore3 { .. } => "lstore_3",
+            Self::Fstore0 { .. } => "fstore_0",
+            Self::Fstore1 { .. } => "fstore_1",
+            Self::Fstore2 { .. } => "fstore_2",
+            Self::Fstore3 { .. } => "fstore_3",
+            Self::Dstore0 { .. } => "dstore_0",
+            Self::Dstore1 { .. } => "dstore_1",
+            Self::Dstore2 { .. } => "dstore_2",
+            Self::Dstore3 { .. } => "dstore_3",
+            Self::Astore0 { .. } => "astore_0",
+            Self::Astore1 { .. } => "astore_1",
+            Self::Astore2 { .. } => "astore_2",
+            Self::Astore3 { .. } => "astore_3",
+            Self::Iastore { .. } => "iastore",
+            Self::Lastore { .. } => "lastore",
+            Self::Fastore { .. } => "fastore",
+            Self::Dastore { .. } => "dastore",
+            Self::Aastore { .. } => "aastore",
+            Self::Bastore { .. } => "bastore",
+            Self::Castore { .. } => "castore",
+            Self::Sastore { .. } => "sastore",
+            Self::Pop { .. } => "pop",
+            Self::Pop2 { .. } => "pop2",
+            Self::Dup { .. } => "dup",
+            Self::DupX1 { .. } => "dup_x1",
+            Self::DupX2 { .. } => "dup_x2",
+            Self::Dup2 { .. } => "dup2",
+            Self::Dup2X1 { .. } => "dup2_x1",
+            Self::Dup2X2 { .. } => "dup2_x2",
+            Self::Swap { .. } => "swap",
+            Self::Iadd { .. } => "iadd",
+            Self::Ladd { .. } => "ladd",
+            Self::Fadd { .. } => "fadd",
+            Self::Dadd { .. } => "dadd",
+            Self::Isub { .. } => "isub",
+            Self::Lsub { .. } => "lsub",
+            Self::Fsub { .. } => "fsub",
+            Self::Dsub { .. } => "dsub",
+            Self::Imul { .. } => "imul",
+            Self::Lmul { .. } => "lmul",
+            Self::Fmul { .. } => "fmul",
+            Self::Dmul { .. } => "dmul",
+            Self::Idiv { .. } => "idiv",
+            Self::Ldiv { .. } => "ldiv",
+            Self::Fdiv { .. } => "fdiv",
+            Self::Ddiv { .. } => "ddiv",
+            Self::Irem { .. } => "irem",
+            Self::Lrem { .. } => "lrem",
+            Self::Frem { .. } => "frem",
+            Self::Drem { .. } => "drem",
+            Self::Ineg { .. } => "ineg",
+            Self::Lneg { .. } => "lneg",
+            Self::Fneg { .. } => "fneg",
+            Self::Dneg { .. } => "dneg",
+            Self::Ishl { .. } => "ishl",
+            Self::Lshl { .. } => "lshl",
+            Self::Ishr { .. } => "ishr",
+            Self::Lshr { .. } => "lshr",
+            Self::Iushr { .. } => "iushr",
+            Self::Lushr { .. } => "lushr",
+            Self::Iand { .. } => "iand",
+            Self::Land { .. } => "land",
+            Self::Ior { .. } => "ior",
+            Self::Lor { .. } => "lor",
+            Self::Ixor { .. } => "ixor",
+            Self::Lxor { .. } => "lxor",
+            Self::Iinc { .. } => "iinc",
+            Self::I2l { .. } => "i2l",
+            Self::I2f { .. } => "i2f",
+            Self::I2d { .. } => "i2d",
+            Self::L2i { .. } => "l2i",
+            Self::L2f { .. } => "l2f",
+            Self::L2d { .. } => "l2d",
+            Self::F2i { .. } => "f2i",
+            Self::F2l { .. } => "f2l",
+            Self::F2d { .. } => "f2d",
+            Self::D2i { .. } => "d2i",
+            Self::D2l { .. } => "d2l",
+            Self::D2f { .. } => "d2f",
+            Self::I2b { .. } => "i2b",
+            Self::I2c { .. } => "i2c",
+            Self::I2s { .. } => "i2s",
+            Self::Lcmp { .. } => "lcmp",
+            Self::Fcmpl { .. } => "fcmpl",
+            Self::Fcmpg { .. } => "fcmpg",
+            Self::Dcmpl { .. } => "dcmpl",
+            Self::Dcmpg { .. } => "dcmpg",
+            Self::Ifeq { .. } => "ifeq",
+            Self::Ifne { .. } => "ifne",
+            Self::Iflt { .. } => "iflt",
+            Self::Ifge { .. } => "ifge",
+            Self::Ifgt { .. } => "ifgt",
+            Self::Ifle { .. } => "ifle",
+            Self::IfIcmpeq { .. } => "if_icmpeq",
+            Self::IfIcmpne { .. } => "if_icmpne",
+            Self::IfIcmplt { .. } => "if_icmplt",
+            Self::IfIcmpge { .. } => "if_icmpge",
+            Self::IfIcmpgt { .. } => "if_icmpgt",
+            Self::IfIcmple { .. } => "if_icmple",
+            Self::IfAcmpeq { .. } => "if_acmpeq",
+            Self::IfAcmpne { .. } => "if_acmpne",
+            Self::Goto { .. } => "goto",
+            Self::Jsr { .. } => "jsr",
+            Self::Ret { .. } => "ret",
+            Self::Tableswitch { .. } => "tableswitch",
+            Self::Lookupswitch { .. } => "lookupswitch",
+            Self::Ireturn { .. } => "ireturn",
+            Self::Lreturn { .. } => "lreturn",
+            Self::Freturn { .. } => "freturn",
+            Self::Dreturn { .. } => "dreturn",
+            Self::Areturn { .. } => "areturn",
+            Self::Return { .. } => "return",
+            Self::Getstatic { .. } => "getstatic",
+            Self::Putstatic { .. } => "putstatic",
+            Self::Getfield { .. } => "getfield",
+            Self::Putfield { .. } => "putfield",
+            Self::Invokevirtual { .. } => "invokevirtual",
+            Self::Invokespecial { .. } => "invokespecial",
+            Self::Invokestatic { .. } => "invokestatic",
+            Self::Invokeinterface { .. } => "invokeinterface",
+            Self::Invokedynamic { .. } => "invokedynamic",
+            Self::New { .. } => "new",
+            Self::Newarray { .. } => "newarray",
+            Self::Anewarray { .. } => "anewarray",
+            Self::Arraylength { .. } => "arraylength",
+            Self::Athrow { .. } => "athrow",
+            Self::Checkcast { .. } => "checkcast",
+            Self::Instanceof { .. } => "instanceof",
+            Self::Monitorenter { .. } => "monitorenter",
+            Self::Monitorexit { .. } => "monitorexit",
+            Self::Wide { .. } => "wide",
+            Self::Multianewarray { .. } => "multianewarray",
+            Self::Ifnull { .. } => "ifnull",
+            Self::Ifnonnull { .. } => "ifnonnull",
+            Self::GotoW { .. } => "goto_w",
+            Self::JsrW { .. } => "jsr_w",
+            Self::Breakpoint { .. } => "breakpoint",
+            Self::Impdep1 { .. } => "impdep1",
+            Self::Impdep2 { .. } => "impdep2",
+        }
+    }
+}
+
+/// The switch instructions pad their operands to a multiple of four bytes
+/// from the start of the code array
+fn padding(pc: usize) -> usize {
+    (4 - (pc + 1) % 4) % 4
+}
+
+/// Iterates over the `(pc, Instruction)` pairs of a code array, stopping
+/// after the first instruction that fails to decode
+pub struct InstructionIter<'a> {
+    code: &'a [u8],
+    pc: usize,
+}
+
+impl<'a> InstructionIter<'a> {
+    pub fn new(code: &'a [u8]) -> Self {
+        Self { code, pc: 0 }
+    }
+}
+
+impl<'a> Iterator for InstructionIter<'a> {
+    type Item = Result<(usize, Instruction), ClassBuilderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pc >= self.code.len() {
+            return None;
+        }
+        let pc = self.pc;
+        match Instruction::decode(self.code, pc) {
+            Ok((ins, len)) => {
+                self.pc += len;
+                Some(Ok((pc, ins)))
+            }
+            Err(err) => {
+                self.pc = self.code.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl AttributeInfo {
+    /// The decoded instructions of a `Code` attribute, `None` for any other
+    /// attribute
+    pub fn instructions(&self) -> Option<InstructionIter<'_>> {
+        match self {
+            AttributeInfo::Code { code, .. } => Some(InstructionIter::new(code)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jvm_opcodes::*, *};
+
+    #[test]
+    fn huge_switches_fail_without_allocating() {
+        // tableswitch over the whole i32 range and lookupswitch claiming
+        // i32::MAX pairs, neither has the bytes to back it up
+        let table = [
+            TABLESWITCH,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0x80,
+            0,
+            0,
+            0,
+            0x7F,
+            0xFF,
+            0xFF,
+            0xFF,
+            0,
+            0,
+            0,
+            0,
+        ];
+        let lookup = [
+            LOOKUPSWITCH,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0x7F,
+            0xFF,
+            0xFF,
+            0xFF,
+            0,
+            0,
+            0,
+            0,
+        ];
+        for code in [&table[..], &lookup[..]] {
+            assert!(matches!(
+                Instruction::decode(code, 0),
+                Err(ClassBuilderError::ReachedEndOfFile)
+            ));
+        }
+    }
+
+    #[test]
+    fn wide_forms_are_wrapped() {
+        let code = [WIDE, IINC, 1, 0, 0xFF, 0xFF, WIDE, ILOAD, 0, 5, ILOAD, 5];
+        let decoded = InstructionIter::new(&code)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            decoded,
+            [
+                (
+                    0,
+                    Instruction::Wide(Box::new(Instruction::Iinc {
+                        index: 256,
+                        value: -1
+                    }))
+                ),
+                (6, Instruction::Wide(Box::new(Instruction::Iload(5)))),
+                (10, Instruction::Iload(5)),
+            ]
+        );
+        assert_eq!(decoded[0].1.opcode(), WIDE);
+        assert!(matches!(
+            Instruction::decode(&[WIDE, NOP], 0),
+            Err(ClassBuilderError::InvalidWideOpcode(NOP))
+        ));
+    }
+}
@@ -0,0 +1,1793 @@
+//! A disassembler producing output in the style of `javap`
+
+use std::fmt::{Display, Formatter, Result};
+
+use super::{
+    annotation::{Annotation, ElementValue, TargetInfo, TypeAnnotation},
+    attribute::{AttributeEntry, AttributeInfo, ModuleInfo},
+    constant::{ConstantPool, ConstantPoolEntry, ReferenceKind},
+    descriptor::{FieldType, MethodDescriptor},
+    field::FieldEntry,
+    floating_decimal::{double_to_string, float_to_string},
+    instruction::{Instruction, InstructionIter},
+    method::MethodEntry,
+    signature::{TypeParameter, TypeParameters},
+    stack_map::{StackMapFrame, VerificationTypeInfo},
+    Class,
+};
+
+/// Mirrors the command line flags of `javap`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JavapOptions {
+    /// `-c` disassemble the code of each method
+    pub code: bool,
+    /// `-v` print the constant pool, flags and every attribute
+    pub verbose: bool,
+    /// `-p` include private members
+    pub private: bool,
+    /// `-l` print line and local variable tables
+    pub lines: bool,
+}
+
+pub struct Javap<'a> {
+    class: &'a Class,
+    options: JavapOptions,
+}
+
+impl Class {
+    pub fn javap(&self, options: JavapOptions) -> Javap<'_> {
+        Javap {
+            class: self,
+            options,
+        }
+    }
+}
+
+const CLASS_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0020, "ACC_SUPER"),
+    (0x0200, "ACC_INTERFACE"),
+    (0x0400, "ACC_ABSTRACT"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x2000, "ACC_ANNOTATION"),
+    (0x4000, "ACC_ENUM"),
+    (0x8000, "ACC_MODULE"),
+];
+
+const FIELD_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0002, "ACC_PRIVATE"),
+    (0x0004, "ACC_PROTECTED"),
+    (0x0008, "ACC_STATIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0040, "ACC_VOLATILE"),
+    (0x0080, "ACC_TRANSIENT"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x4000, "ACC_ENUM"),
+];
+
+const METHOD_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0002, "ACC_PRIVATE"),
+    (0x0004, "ACC_PROTECTED"),
+    (0x0008, "ACC_STATIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0020, "ACC_SYNCHRONIZED"),
+    (0x0040, "ACC_BRIDGE"),
+    (0x0080, "ACC_VARARGS"),
+    (0x0100, "ACC_NATIVE"),
+    (0x0400, "ACC_ABSTRACT"),
+    (0x0800, "ACC_STRICT"),
+    (0x1000, "ACC_SYNTHETIC"),
+];
+
+const OBJECT: &str = "java.lang.Object";
+
+fn flag_names(flags: u16, table: &[(u16, &str)]) -> String {
+    let names: Vec<&str> = table
+        .iter()
+        .filter(|(mask, _)| flags & mask != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if names.is_empty() {
+        format!("(0x{:04x})", flags)
+    } else {
+        format!("(0x{:04x}) {}", flags, names.join(", "))
+    }
+}
+
+fn modifiers(flags: u16, table: &[(u16, &str)]) -> String {
+    let mut str = String::new();
+    for (mask, name) in table {
+        if flags & mask != 0 {
+            str.push_str(name);
+            str.push(' ');
+        }
+    }
+    str
+}
+
+const FIELD_MODIFIERS: &[(u16, &str)] = &[
+    (0x0001, "public"),
+    (0x0002, "private"),
+    (0x0004, "protected"),
+    (0x0008, "static"),
+    (0x0010, "final"),
+    (0x0040, "volatile"),
+    (0x0080, "transient"),
+];
+
+const INNER_CLASS_MODIFIERS: &[(u16, &str)] = &[
+    (0x0001, "public"),
+    (0x0002, "private"),
+    (0x0004, "protected"),
+    (0x0008, "static"),
+    (0x0010, "final"),
+    (0x0400, "abstract"),
+];
+
+const METHOD_MODIFIERS: &[(u16, &str)] = &[
+    (0x0001, "public"),
+    (0x0002, "private"),
+    (0x0004, "protected"),
+    (0x0008, "static"),
+    (0x0010, "final"),
+    (0x0020, "synchronized"),
+    (0x0100, "native"),
+    (0x0400, "abstract"),
+];
+
+const PARAMETER_MODIFIERS: &[(u16, &str)] = &[
+    (0x0010, "final"),
+    (0x8000, "mandated"),
+    (0x1000, "synthetic"),
+];
+
+fn reference_kind_name(kind: ReferenceKind) -> &'static str {
+    match kind {
+        ReferenceKind::GetField => "REF_getField",
+        ReferenceKind::GetStatic => "REF_getStatic",
+        ReferenceKind::PutField => "REF_putField",
+        ReferenceKind::PutStatic => "REF_putStatic",
+        ReferenceKind::InvokeVirtual => "REF_invokeVirtual",
+        ReferenceKind::InvokeStatic => "REF_invokeStatic",
+        ReferenceKind::InvokeSpecial => "REF_invokeSpecial",
+        ReferenceKind::NewInvokeSpecial => "REF_newInvokeSpecial",
+        ReferenceKind::InvokeInterface => "REF_invokeInterface",
+    }
+}
+
+/// Escapes quotes, backslashes and control characters the way javap does,
+/// anything else is printed as is
+fn escape(str: &str) -> String {
+    let mut out = String::with_capacity(str.len());
+    for c in str.chars() {
+        match c {
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '"' => out.push_str("\\\""),
+            '\'' => out.push_str("\\'"),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// What a type annotation applies to, `METHOD_FORMAL_PARAMETER, param_index=0`
+/// and so on, followed by its path into the type
+fn type_annotation_target(annotation: &TypeAnnotation) -> String {
+    let name = match annotation.target_type {
+        0x00 => "CLASS_TYPE_PARAMETER",
+        0x01 => "METHOD_TYPE_PARAMETER",
+        0x10 => "CLASS_EXTENDS",
+        0x11 => "CLASS_TYPE_PARAMETER_BOUND",
+        0x12 => "METHOD_TYPE_PARAMETER_BOUND",
+        0x13 => "FIELD",
+        0x14 => "METHOD_RETURN",
+        0x15 => "METHOD_RECEIVER",
+        0x16 => "METHOD_FORMAL_PARAMETER",
+        0x17 => "THROWS",
+        0x40 => "LOCAL_VARIABLE",
+        0x41 => "RESOURCE_VARIABLE",
+        0x42 => "EXCEPTION_PARAMETER",
+        0x43 => "INSTANCEOF",
+        0x44 => "NEW",
+        0x45 => "CONSTRUCTOR_REFERENCE",
+        0x46 => "METHOD_REFERENCE",
+        0x47 => "CAST",
+        0x48 => "CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT",
+        0x49 => "METHOD_INVOCATION_TYPE_ARGUMENT",
+        0x4A => "CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT",
+        0x4B => "METHOD_REFERENCE_TYPE_ARGUMENT",
+        _ => "UNKNOWN",
+    };
+    let mut str = name.to_string();
+    match &annotation.target_info {
+        TargetInfo::TypeParameter {
+            type_parameter_index,
+        } => str.push_str(&format!(", param_index={}", type_parameter_index)),
+        TargetInfo::Supertype { supertype_index } => {
+            str.push_str(&format!(", type_index={}", supertype_index))
+        }
+        TargetInfo::TypeParameterBound {
+            type_parameter_index,
+            bound_index,
+        } => str.push_str(&format!(
+            ", param_index={}, bound_index={}",
+            type_parameter_index, bound_index
+        )),
+        TargetInfo::Empty => {}
+        TargetInfo::FormalParameter {
+            formal_parameter_index,
+        } => str.push_str(&format!(", param_index={}", formal_parameter_index)),
+        TargetInfo::Throws { throws_type_index } => {
+            str.push_str(&format!(", type_index={}", throws_type_index))
+        }
+        TargetInfo::Localvar(entries) => {
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|e| {
+                    format!(
+                        "start_pc={}, length={}, index={}",
+                        e.start_pc, e.length, e.index
+                    )
+                })
+                .collect();
+            str.push_str(&format!(", {{{}}}", entries.join("; ")));
+        }
+        TargetInfo::Catch {
+            exception_table_index,
+        } => str.push_str(&format!(", exception_index={}", exception_table_index)),
+        TargetInfo::Offset { offset } => str.push_str(&format!(", offset={}", offset)),
+        TargetInfo::TypeArgument {
+            offset,
+            type_argument_index,
+        } => str.push_str(&format!(
+            ", offset={}, type_index={}",
+            offset, type_argument_index
+        )),
+    }
+    if !annotation.target_path.is_empty() {
+        let path: Vec<String> = annotation
+            .target_path
+            .iter()
+            .map(|entry| match entry.type_path_kind {
+                0 => "ARRAY".into(),
+                1 => "INNER_TYPE".into(),
+                2 => "WILDCARD".into(),
+                3 => format!("TYPE_ARGUMENT({})", entry.type_argument_index),
+                kind => format!("UNKNOWN({})", kind),
+            })
+            .collect();
+        str.push_str(&format!(", location=[{}]", path.join(", ")));
+    }
+    str
+}
+
+/// Quotes names that aren't java identifiers separated by `/`, such as
+/// special method names, array classes and module names, like javap does
+fn quote(name: &str) -> String {
+    let mut prev = '/';
+    for c in name.chars() {
+        let start = c.is_alphabetic() || c == '$' || c == '_';
+        let part = start || c.is_alphanumeric();
+        if (prev == '/' && !start) || (c != '/' && !part) {
+            let mut quoted = String::from("\"");
+            for c in name.chars() {
+                match c {
+                    '\\' => quoted.push_str("\\\\"),
+                    '"' => quoted.push_str("\\\""),
+                    '\n' => quoted.push_str("\\n"),
+                    '\t' => quoted.push_str("\\t"),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            return quoted;
+        }
+        prev = c;
+    }
+    if name.is_empty() {
+        "\"\"".into()
+    } else {
+        name.into()
+    }
+}
+
+/// The java form of a field descriptor, falling back to the raw descriptor
+fn render_field_type(desc: &str) -> String {
+    FieldType::parse(desc).map_or_else(|_| desc.into(), |ty| ty.to_string())
+}
+
+impl<'a> Javap<'a> {
+    fn cp(&self) -> &'a ConstantPool {
+        &self.class.constant_pool
+    }
+
+    fn utf8(&self, index: u16) -> &'a str {
+        self.cp().get_const_utd8_or_invalid(index)
+    }
+
+    fn this_name(&self) -> &'a str {
+        self.cp().get_class_name_invalid(self.class.this_class)
+    }
+
+    fn attribute_name(&self, attr: &AttributeEntry) -> &'a str {
+        self.utf8(attr.name_index)
+    }
+
+    fn name_and_type(&self, index: u16) -> (&'a str, &'a str) {
+        self.cp()
+            .get_name_and_type(index)
+            .unwrap_or(("##INVALID_NAME_AND_TYPE##", "##INVALID_NAME_AND_TYPE##"))
+    }
+
+    fn module_name(&self, index: u16) -> &'a str {
+        match self.cp().get_constant(index) {
+            Some(ConstantPoolEntry::Module { name_index }) => self.utf8(*name_index),
+            _ => "##INVALID_MODULE##",
+        }
+    }
+
+    fn package_name(&self, index: u16) -> String {
+        match self.cp().get_constant(index) {
+            Some(ConstantPoolEntry::Package { name_index }) => {
+                self.utf8(*name_index).replace('/', ".")
+            }
+            _ => "##INVALID_PACKAGE##".into(),
+        }
+    }
+
+    fn module(&self) -> Option<&'a ModuleInfo> {
+        self.class
+            .attribute_info
+            .iter()
+            .find_map(|a| match &a.info {
+                AttributeInfo::Module(module) => Some(&**module),
+                _ => None,
+            })
+    }
+
+    /// `class.name:descriptor` of a field or method reference, the class is
+    /// left out when it is this class and `omit_own` is set
+    fn member_ref(&self, index: u16, omit_own: bool) -> (&'static str, String) {
+        let (kind, class_index, nat) = match self.cp().get_constant(index) {
+            Some(ConstantPoolEntry::Fieldref {
+                class_index,
+                name_and_type_index,
+            }) => ("Field", *class_index, *name_and_type_index),
+            Some(ConstantPoolEntry::Methodref {
+                class_index,
+                name_and_type_index,
+            }) => ("Method", *class_index, *name_and_type_index),
+            Some(ConstantPoolEntry::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            }) => ("InterfaceMethod", *class_index, *name_and_type_index),
+            _ => return ("", "##INVALID_MEMBER_REFERENCE##".into()),
+        };
+        let class = self.cp().get_class_name_invalid(class_index);
+        let (name, desc) = self.name_and_type(nat);
+        if omit_own && class == self.this_name() {
+            (kind, format!("{}:{}", quote(name), desc))
+        } else {
+            (kind, format!("{}.{}:{}", quote(class), quote(name), desc))
+        }
+    }
+
+    /// The description of a loadable constant, `int 10`, `String abc` etc
+    fn constant_value(&self, index: u16) -> String {
+        match self.cp().get_constant(index) {
+            Some(ConstantPoolEntry::Integer(i)) => format!("int {}", i),
+            Some(ConstantPoolEntry::Float(v)) => format!("float {}f", float_to_string(*v)),
+            Some(ConstantPoolEntry::Long(l)) => format!("long {}l", l),
+            Some(ConstantPoolEntry::Double(v)) => format!("double {}d", double_to_string(*v)),
+            Some(ConstantPoolEntry::String { string_index }) => {
+                format!("String {}", self.string(*string_index))
+            }
+            Some(ConstantPoolEntry::Class { name_index }) => {
+                format!("class {}", quote(self.utf8(*name_index)))
+            }
+            Some(ConstantPoolEntry::MethodType { descriptor_index }) => {
+                format!("MethodType {}", self.utf8(*descriptor_index))
+            }
+            Some(ConstantPoolEntry::MethodHandle { .. }) => {
+                format!("MethodHandle {}", self.constant_comment(index))
+            }
+            Some(ConstantPoolEntry::Dynamic { .. }) => {
+                format!("Dynamic {}", self.constant_comment(index))
+            }
+            _ => "##NOT_LOADABLE##".into(),
+        }
+    }
+
+    fn string(&self, index: u16) -> String {
+        match self.cp().get_constant(index) {
+            Some(ConstantPoolEntry::Utf8(str)) => escape(str),
+            // javap can't encode unpaired surrogates and prints `?` instead
+            Some(ConstantPoolEntry::Utf16(units)) => escape(
+                &char::decode_utf16(units.iter().copied())
+                    .map(|c| c.unwrap_or('?'))
+                    .collect::<String>(),
+            ),
+            _ => "##CONSTANT_NOT_UTF8##".into(),
+        }
+    }
+
+    /// The text after `//` in the constant pool listing
+    fn constant_comment(&self, index: u16) -> String {
+        match self.cp().get_constant(index) {
+            Some(ConstantPoolEntry::Class { name_index }) => quote(self.utf8(*name_index)),
+            Some(
+                ConstantPoolEntry::Fieldref { .. }
+                | ConstantPoolEntry::Methodref { .. }
+                | ConstantPoolEntry::InterfaceMethodref { .. },
+            ) => self.member_ref(index, false).1,
+            Some(ConstantPoolEntry::String { string_index }) => self.string(*string_index),
+            Some(ConstantPoolEntry::Integer(v)) => v.to_string(),
+            Some(ConstantPoolEntry::Float(v)) => format!("{}f", float_to_string(*v)),
+            Some(ConstantPoolEntry::Long(v)) => format!("{}l", v),
+            Some(ConstantPoolEntry::Double(v)) => format!("{}d", double_to_string(*v)),
+            Some(ConstantPoolEntry::NameAndType { .. }) => {
+                let (name, desc) = self.name_and_type(index);
+                format!("{}:{}", quote(name), desc)
+            }
+            Some(ConstantPoolEntry::MethodHandle {
+                reference_kind,
+                reference_index,
+            }) => format!(
+                "{} {}",
+                reference_kind_name(*reference_kind),
+                self.member_ref(*reference_index, false).1
+            ),
+            Some(ConstantPoolEntry::MethodType { descriptor_index }) => {
+                format!(" {}", self.utf8(*descriptor_index))
+            }
+            Some(
+                ConstantPoolEntry::Dynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                }
+                | ConstantPoolEntry::InvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                },
+            ) => {
+                let (name, desc) = self.name_and_type(*name_and_type_index);
+                format!("#{}:{}:{}", bootstrap_method_attr_index, quote(name), desc)
+            }
+            Some(ConstantPoolEntry::Module { name_index })
+            | Some(ConstantPoolEntry::Package { name_index }) => quote(self.utf8(*name_index)),
+            _ => String::new(),
+        }
+    }
+
+    fn fmt_constant_pool(&self, f: &mut Formatter<'_>) -> Result {
+        writeln!(f, "Constant pool:")?;
+        let width = (self.cp().constant_pool.len() + 1).to_string().len() + 3;
+        for (i, constant) in self.cp().constant_pool.iter().enumerate() {
+            let index = i as u16 + 1;
+            let (kind, args) = match constant {
+                ConstantPoolEntry::Empty => continue,
+                ConstantPoolEntry::Class { name_index } => ("Class", format!("#{}", name_index)),
+                ConstantPoolEntry::Fieldref {
+                    class_index,
+                    name_and_type_index,
+                } => (
+                    "Fieldref",
+                    format!("#{}.#{}", class_index, name_and_type_index),
+                ),
+                ConstantPoolEntry::Methodref {
+                    class_index,
+                    name_and_type_index,
+                } => (
+                    "Methodref",
+                    format!("#{}.#{}", class_index, name_and_type_index),
+                ),
+                ConstantPoolEntry::InterfaceMethodref {
+                    class_index,
+                    name_and_type_index,
+                } => (
+                    "InterfaceMethodref",
+                    format!("#{}.#{}", class_index, name_and_type_index),
+                ),
+                ConstantPoolEntry::String { string_index } => {
+                    ("String", format!("#{}", string_index))
+                }
+                ConstantPoolEntry::Integer(v) => ("Integer", v.to_string()),
+                ConstantPoolEntry::Float(v) => ("Float", format!("{}f", float_to_string(*v))),
+                ConstantPoolEntry::Long(v) => ("Long", format!("{}l", v)),
+                ConstantPoolEntry::Double(v) => ("Double", format!("{}d", double_to_string(*v))),
+                ConstantPoolEntry::NameAndType {
+                    name_index,
+                    descriptor_index,
+                } => (
+                    "NameAndType",
+                    format!("#{}:#{}", name_index, descriptor_index),
+                ),
+                ConstantPoolEntry::Utf8(_) | ConstantPoolEntry::Utf16(_) => {
+                    ("Utf8", self.string(index))
+                }
+                ConstantPoolEntry::MethodHandle {
+                    reference_kind,
+                    reference_index,
+                } => (
+                    "MethodHandle",
+                    format!("{}:#{}", reference_kind.to_u8(), reference_index),
+                ),
+                ConstantPoolEntry::MethodType { descriptor_index } => {
+                    ("MethodType", format!("#{}", descriptor_index))
+                }
+                ConstantPoolEntry::Dynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } => (
+                    "Dynamic",
+                    format!("#{}:#{}", bootstrap_method_attr_index, name_and_type_index),
+                ),
+                ConstantPoolEntry::InvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } => (
+                    "InvokeDynamic",
+                    format!("#{}:#{}", bootstrap_method_attr_index, name_and_type_index),
+                ),
+                ConstantPoolEntry::Module { name_index } => ("Module", format!("#{}", name_index)),
+                ConstantPoolEntry::Package { name_index } => {
+                    ("Package", format!("#{}", name_index))
+                }
+            };
+            // the index column grows with the pool but comments stay aligned
+            let line = format!("{:>width$} = {:<18} {}", format!("#{}", index), kind, args);
+            if matches!(kind, "Utf8" | "Integer" | "Float" | "Long" | "Double") {
+                writeln!(f, "{}", line)?;
+            } else {
+                let comment = self.constant_comment(index);
+                writeln!(f, "{:<41} // {}", line, comment)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Without -v javap leaves out `java.lang.Object` bounds, `<T>` rather
+    /// than `<T extends java.lang.Object>`
+    fn type_parameters(&self, params: &[TypeParameter]) -> String {
+        if self.options.verbose || params.is_empty() {
+            return TypeParameters(params).to_string();
+        }
+        let params: Vec<String> = params
+            .iter()
+            .map(|param| {
+                let bounds: Vec<String> = param
+                    .class_bound
+                    .iter()
+                    .chain(&param.interface_bounds)
+                    .map(|bound| bound.to_string())
+                    .filter(|bound| bound != OBJECT)
+                    .collect();
+                if bounds.is_empty() {
+                    param.name.clone()
+                } else {
+                    format!("{} extends {}", param.name, bounds.join(" & "))
+                }
+            })
+            .collect();
+        format!("<{}>", params.join(", "))
+    }
+
+    fn fmt_header(&self, f: &mut Formatter<'_>) -> Result {
+        let class = self.class;
+        let flags = class.access_flags.bits();
+        for attr in &class.attribute_info {
+            if let AttributeInfo::SourceFile { sourcefile_index } = attr.info {
+                let indent = if self.options.verbose { "  " } else { "" };
+                writeln!(
+                    f,
+                    "{}Compiled from \"{}\"",
+                    indent,
+                    self.utf8(sourcefile_index)
+                )?;
+            }
+        }
+
+        let mut decl = String::new();
+        if flags & 0x0001 != 0 {
+            decl.push_str("public ");
+        }
+        match self.module() {
+            Some(module) if flags & 0x8000 != 0 => {
+                if module.module_flags & 0x0020 != 0 {
+                    decl.push_str("open ");
+                }
+                decl.push_str("module ");
+                decl.push_str(self.module_name(module.module_name_index));
+                if module.module_version_index != 0 {
+                    decl.push('@');
+                    decl.push_str(self.utf8(module.module_version_index));
+                }
+            }
+            _ => {
+                if flags & 0x0200 != 0 {
+                    decl.push_str("interface ");
+                } else {
+                    if flags & 0x0400 != 0 {
+                        decl.push_str("abstract ");
+                    }
+                    if flags & 0x0010 != 0 {
+                        decl.push_str("final ");
+                    }
+                    decl.push_str("class ");
+                }
+                decl.push_str(&self.this_name().replace('/', "."));
+            }
+        }
+        // javap separates generic interfaces with ", " and plain ones with ","
+        let interfaces = match class.signature() {
+            Some(Ok(sig)) => {
+                decl.push_str(&self.type_parameters(&sig.type_parameters));
+                let super_class = sig.super_class.to_string();
+                if flags & 0x0200 == 0 && (self.options.verbose || super_class != OBJECT) {
+                    decl.push_str(&format!(" extends {}", super_class));
+                }
+                let interfaces: Vec<_> = sig.interfaces.iter().map(|i| i.to_string()).collect();
+                interfaces.join(", ")
+            }
+            _ => {
+                if let Some(super_name) = class.super_class_name() {
+                    if super_name != "java/lang/Object" {
+                        decl.push_str(" extends ");
+                        decl.push_str(&super_name.replace('/', "."));
+                    }
+                }
+                let interfaces: Vec<_> = class
+                    .interface_names()
+                    .map(|i| i.replace('/', "."))
+                    .collect();
+                interfaces.join(",")
+            }
+        };
+        if !interfaces.is_empty() {
+            decl.push_str(if flags & 0x0200 != 0 {
+                " extends "
+            } else {
+                " implements "
+            });
+            decl.push_str(&interfaces);
+        }
+        if !self.options.verbose {
+            return writeln!(f, "{} {{", decl);
+        }
+        writeln!(f, "{}", decl)?;
+
+        {
+            writeln!(f, "  minor version: {}", class.minor_version)?;
+            writeln!(f, "  major version: {}", class.major_version)?;
+            writeln!(f, "  flags: {}", flag_names(flags, CLASS_FLAGS))?;
+            writeln!(
+                f,
+                "  {:<40}// {}",
+                format!("this_class: #{}", class.this_class),
+                quote(self.this_name())
+            )?;
+            match class.super_class_name() {
+                Some(name) => writeln!(
+                    f,
+                    "  {:<40}// {}",
+                    format!("super_class: #{}", class.super_class),
+                    quote(name)
+                )?,
+                None => writeln!(f, "  super_class: #{}", class.super_class)?,
+            }
+            writeln!(
+                f,
+                "  interfaces: {}, fields: {}, methods: {}, attributes: {}",
+                class.interfaces.len(),
+                class.field_info.len(),
+                class.method_info.len(),
+                class.attribute_info.len()
+            )?;
+            self.fmt_constant_pool(f)?;
+        }
+        writeln!(f, "{{")
+    }
+
+    /// The body of a `module-info` class without -v
+    fn fmt_module_directives(&self, f: &mut Formatter<'_>) -> Result {
+        let Some(module) = self.module() else {
+            return Ok(());
+        };
+        let class_name = |index: u16| self.cp().get_class_name_invalid(index).replace('/', ".");
+        // the targets of a qualified directive go one per line
+        let fmt_targets = |f: &mut Formatter<'_>, keyword: &str, targets: Vec<String>| {
+            if targets.is_empty() {
+                writeln!(f, ";")
+            } else {
+                writeln!(f, " {}", keyword)?;
+                writeln!(f, "    {};", targets.join(",\n    "))
+            }
+        };
+        for requires in &module.requires {
+            write!(f, "  requires ")?;
+            if requires.requires_flags & 0x0040 != 0 {
+                write!(f, "static ")?;
+            }
+            if requires.requires_flags & 0x0020 != 0 {
+                write!(f, "transitive ")?;
+            }
+            writeln!(f, "{};", self.module_name(requires.requires_index))?;
+        }
+        for (keyword, index, to) in module
+            .exports
+            .iter()
+            .map(|e| ("exports", e.exports_index, &e.exports_to_index))
+            .chain(
+                module
+                    .opens
+                    .iter()
+                    .map(|o| ("opens", o.opens_index, &o.opens_to_index)),
+            )
+        {
+            write!(f, "  {} {}", keyword, self.package_name(index))?;
+            let targets = to.iter().map(|m| self.module_name(*m).into()).collect();
+            fmt_targets(f, "to", targets)?;
+        }
+        for uses in &module.uses_index {
+            writeln!(f, "  uses {};", class_name(*uses))?;
+        }
+        for provides in &module.provides {
+            write!(f, "  provides  {}", class_name(provides.provides_index))?;
+            let targets = provides
+                .provides_with_index
+                .iter()
+                .map(|c| class_name(*c))
+                .collect();
+            fmt_targets(f, "with", targets)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_field(&self, f: &mut Formatter<'_>, field: &FieldEntry) -> Result {
+        let flags = field.access_flags.bits();
+        let desc = self.utf8(field.descriptor_index);
+        let ty = match field.signature(self.cp()) {
+            Some(Ok(sig)) => sig.to_string(),
+            _ => render_field_type(desc),
+        };
+        writeln!(
+            f,
+            "  {}{} {};",
+            modifiers(flags, FIELD_MODIFIERS),
+            ty,
+            self.utf8(field.name_index)
+        )?;
+        if self.options.verbose {
+            writeln!(f, "    descriptor: {}", desc)?;
+            writeln!(f, "    flags: {}", flag_names(flags, FIELD_FLAGS))?;
+            for attr in &field.attributes {
+                self.fmt_attribute(f, attr, "    ")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_method(&self, f: &mut Formatter<'_>, method: &MethodEntry) -> Result {
+        let flags = method.access_flags.bits();
+        let name = self.utf8(method.name_index);
+        let desc = self.utf8(method.descriptor_index);
+        let descriptor = MethodDescriptor::parse(desc).ok();
+        let signature = match method.signature(self.cp()) {
+            Some(Ok(sig)) => Some(sig),
+            _ => None,
+        };
+
+        let (type_params, mut params, ret, mut throws) = match (&signature, &descriptor) {
+            (Some(sig), _) => (
+                self.type_parameters(&sig.type_parameters),
+                sig.parameters.iter().map(|p| p.to_string()).collect(),
+                sig.return_type
+                    .as_ref()
+                    .map_or_else(|| "void".into(), |r| r.to_string()),
+                sig.throws.iter().map(|t| t.to_string()).collect(),
+            ),
+            (None, Some(descriptor)) => (
+                String::new(),
+                descriptor
+                    .parameters
+                    .iter()
+                    .map(|p| p.to_string())
+                    .collect(),
+                descriptor
+                    .return_type
+                    .as_ref()
+                    .map_or_else(|| "void".into(), |r| r.to_string()),
+                Vec::new(),
+            ),
+            (None, None) => (String::new(), Vec::new(), desc.to_string(), Vec::new()),
+        };
+        if flags & 0x0080 != 0 {
+            if let Some(last) = params.last_mut() {
+                if let Some(stripped) = last.strip_suffix("[]") {
+                    *last = format!("{}...", stripped);
+                }
+            }
+        }
+
+        if throws.is_empty() {
+            for attr in &method.attributes {
+                if let AttributeInfo::Exceptions {
+                    exception_index_table,
+                } = &attr.info
+                {
+                    for e in exception_index_table {
+                        throws.push(self.cp().get_class_name_invalid(*e).replace('/', "."));
+                    }
+                }
+            }
+        }
+        let mut modifiers = modifiers(flags, METHOD_MODIFIERS);
+        // interface methods with a body that aren't static are default methods
+        if self.class.access_flags.bits() & 0x0200 != 0 && flags & (0x0400 | 0x0008 | 0x0002) == 0 {
+            modifiers.push_str("default ");
+        }
+        let modifiers = if type_params.is_empty() {
+            modifiers
+        } else {
+            format!("{}{} ", modifiers, type_params)
+        };
+
+        let decl = match name {
+            "<clinit>" => "static {}".to_string(),
+            "<init>" => format!(
+                "{}{}({})",
+                modifiers,
+                self.this_name().replace('/', "."),
+                params.join(", ")
+            ),
+            _ => format!("{}{} {}({})", modifiers, ret, name, params.join(", ")),
+        };
+        if throws.is_empty() {
+            writeln!(f, "  {};", decl)?;
+        } else {
+            writeln!(f, "  {} throws {};", decl, throws.join(", "))?;
+        }
+
+        if self.options.verbose {
+            writeln!(f, "    descriptor: {}", desc)?;
+            writeln!(f, "    flags: {}", flag_names(flags, METHOD_FLAGS))?;
+        }
+        for attr in &method.attributes {
+            if let AttributeInfo::Code { .. } = attr.info {
+                let args_size = descriptor.as_ref().map_or(0, |d| d.parameters.len())
+                    + if flags & 0x0008 != 0 { 0 } else { 1 };
+                self.fmt_code(f, attr, args_size)?;
+            } else if self.options.verbose {
+                self.fmt_attribute(f, attr, "    ")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_code(&self, f: &mut Formatter<'_>, attr: &AttributeEntry, args_size: usize) -> Result {
+        let options = self.options;
+        if !(options.code || options.verbose || options.lines) {
+            return Ok(());
+        }
+        if options.code || options.verbose {
+            writeln!(f, "    Code:")?;
+        }
+        if options.verbose {
+            return self.fmt_code_body(f, attr, args_size);
+        }
+        // without -v javap indents what is under the method two columns less
+        for line in CodeBody(self, attr, args_size).to_string().lines() {
+            writeln!(f, "{}", line.strip_prefix("  ").unwrap_or(line))?;
+        }
+        Ok(())
+    }
+
+    fn fmt_code_body(
+        &self,
+        f: &mut Formatter<'_>,
+        attr: &AttributeEntry,
+        args_size: usize,
+    ) -> Result {
+        let AttributeInfo::Code {
+            max_stack,
+            max_locals,
+            code,
+            exception_table,
+            attributes,
+        } = &attr.info
+        else {
+            return Ok(());
+        };
+        let options = self.options;
+        if options.verbose {
+            writeln!(
+                f,
+                "      stack={}, locals={}, args_size={}",
+                max_stack, max_locals, args_size
+            )?;
+        }
+        if options.code || options.verbose {
+            for res in InstructionIter::new(code) {
+                match res {
+                    Ok((pc, ins)) => self.fmt_instruction(f, pc, &ins)?,
+                    Err(err) => {
+                        writeln!(f, "      ##INVALID_BYTECODE## {:?}", err)?;
+                        break;
+                    }
+                }
+            }
+            if !exception_table.is_empty() {
+                writeln!(f, "      Exception table:")?;
+                writeln!(f, "         from    to  target type")?;
+                for entry in exception_table {
+                    let catch_type = match entry.catch_type_name(self.cp()) {
+                        Some(name) => format!("Class {}", name),
+                        None => "any".into(),
+                    };
+                    writeln!(
+                        f,
+                        "{:>14}{:>6}{:>6}   {}",
+                        entry.start_pc, entry.end_pc, entry.handler_pc, catch_type
+                    )?;
+                }
+            }
+        }
+        for attr in attributes {
+            match &attr.info {
+                AttributeInfo::LineNumberTable { .. }
+                | AttributeInfo::LocalVariableTable { .. }
+                    if options.lines || options.verbose =>
+                {
+                    self.fmt_attribute(f, attr, "      ")?
+                }
+                _ if options.verbose => self.fmt_attribute(f, attr, "      ")?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// javap prints the instructions behind the `wide` prefix as `iload_w`,
+    /// `iinc_w` and so on
+    fn fmt_instruction(&self, f: &mut Formatter<'_>, pc: usize, ins: &Instruction) -> Result {
+        use Instruction::*;
+        let target = |offset: i32| (pc as i64 + offset as i64).to_string();
+        let class =
+            |index: u16| format!("class {}", quote(self.cp().get_class_name_invalid(index)));
+        let (ins, mnemonic) = match ins {
+            Wide(ins) => (&**ins, format!("{}_w", ins.mnemonic())),
+            ins => (ins, ins.mnemonic().to_string()),
+        };
+
+        let (operand, comment): (String, Option<String>) = match ins {
+            Tableswitch {
+                default,
+                low,
+                high,
+                offsets,
+            } => {
+                writeln!(
+                    f,
+                    "      {:>4}: {:<13} {{ // {} to {}",
+                    pc, mnemonic, low, high
+                )?;
+                for (i, offset) in offsets.iter().enumerate() {
+                    writeln!(f, "{:>24}: {}", *low as i64 + i as i64, target(*offset))?;
+                }
+                writeln!(f, "{:>24}: {}", "default", target(*default))?;
+                return writeln!(f, "            }}");
+            }
+            Lookupswitch { default, pairs } => {
+                writeln!(f, "      {:>4}: {:<13} {{ // {}", pc, mnemonic, pairs.len())?;
+                for (key, offset) in pairs {
+                    writeln!(f, "{:>24}: {}", key, target(*offset))?;
+                }
+                writeln!(f, "{:>24}: {}", "default", target(*default))?;
+                return writeln!(f, "            }}");
+            }
+            Bipush(v) => (v.to_string(), None),
+            Sipush(v) => (v.to_string(), None),
+            Ldc(i) | LdcW(i) | Ldc2W(i) => (format!("#{}", i), Some(self.constant_value(*i))),
+            Iload(i) | Lload(i) | Fload(i) | Dload(i) | Aload(i) | Istore(i) | Lstore(i)
+            | Fstore(i) | Dstore(i) | Astore(i) | Ret(i) => (i.to_string(), None),
+            Iinc { index, value } => (format!("{}, {}", index, value), None),
+            Ifeq(o) | Ifne(o) | Iflt(o) | Ifge(o) | Ifgt(o) | Ifle(o) | IfIcmpeq(o)
+            | IfIcmpne(o) | IfIcmplt(o) | IfIcmpge(o) | IfIcmpgt(o) | IfIcmple(o) | IfAcmpeq(o)
+            | IfAcmpne(o) | Goto(o) | Jsr(o) | Ifnull(o) | Ifnonnull(o) => {
+                (target(*o as i32), None)
+            }
+            GotoW(o) | JsrW(o) => (target(*o), None),
+            Getstatic(i) | Putstatic(i) | Getfield(i) | Putfield(i) | Invokevirtual(i)
+            | Invokespecial(i) | Invokestatic(i) => {
+                let (kind, member) = self.member_ref(*i, true);
+                (format!("#{}", i), Some(format!("{} {}", kind, member)))
+            }
+            Invokeinterface { index, count } => {
+                let (kind, member) = self.member_ref(*index, true);
+                (
+                    format!("#{},  {}", index, count),
+                    Some(format!("{} {}", kind, member)),
+                )
+            }
+            Invokedynamic(i) => (
+                format!("#{},  0", i),
+                Some(format!("InvokeDynamic {}", self.constant_comment(*i))),
+            ),
+            New(i) | Anewarray(i) | Checkcast(i) | Instanceof(i) => {
+                (format!("#{}", i), Some(class(*i)))
+            }
+            Multianewarray { index, dimensions } => {
+                (format!("#{},  {}", index, dimensions), Some(class(*index)))
+            }
+            Newarray(ty) => return writeln!(f, "      {:>4}: {:<14} {}", pc, mnemonic, ty.name()),
+            _ => return writeln!(f, "      {:>4}: {}", pc, mnemonic),
+        };
+        // the comment column doesn't move for pcs past 9999
+        let line = format!("      {:>4}: {:<13} {}", pc, mnemonic, operand);
+        match comment {
+            Some(comment) => writeln!(f, "{:<45} // {}", line, comment),
+            None => writeln!(f, "{}", line),
+        }
+    }
+
+    fn verification_type(&self, ty: &VerificationTypeInfo) -> String {
+        match ty {
+            VerificationTypeInfo::Top => "top".into(),
+            VerificationTypeInfo::Integer => "int".into(),
+            VerificationTypeInfo::Float => "float".into(),
+            VerificationTypeInfo::Double => "double".into(),
+            VerificationTypeInfo::Long => "long".into(),
+            VerificationTypeInfo::Null => "null".into(),
+            VerificationTypeInfo::UninitializedThis => "this".into(),
+            VerificationTypeInfo::Object { cpool_index } => {
+                format!(
+                    "class {}",
+                    quote(self.cp().get_class_name_invalid(*cpool_index))
+                )
+            }
+            VerificationTypeInfo::Uninitialized { offset } => format!("uninitialized {}", offset),
+        }
+    }
+
+    fn verification_types(&self, types: &[VerificationTypeInfo]) -> String {
+        let types: Vec<String> = types.iter().map(|t| self.verification_type(t)).collect();
+        if types.is_empty() {
+            "[]".into()
+        } else {
+            format!("[ {} ]", types.join(", "))
+        }
+    }
+
+    /// The index form of an element value, `s#12`, `e#4.#5` etc
+    fn element_value_raw(&self, value: &ElementValue) -> String {
+        match value {
+            ElementValue::Const {
+                tag,
+                const_value_index,
+            } => format!("{}#{}", *tag as char, const_value_index),
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!("e#{}.#{}", type_name_index, const_name_index),
+            ElementValue::Class { class_info_index } => format!("c#{}", class_info_index),
+            ElementValue::Annotation(annotation) => {
+                format!("@{}", self.annotation_raw(annotation))
+            }
+            ElementValue::Array(values) => {
+                let values: Vec<String> =
+                    values.iter().map(|v| self.element_value_raw(v)).collect();
+                format!("[{}]", values.join(","))
+            }
+        }
+    }
+
+    fn annotation_raw(&self, annotation: &Annotation) -> String {
+        let pairs: Vec<String> = annotation
+            .element_value_pairs
+            .iter()
+            .map(|pair| {
+                format!(
+                    "#{}={}",
+                    pair.element_name_index,
+                    self.element_value_raw(&pair.value)
+                )
+            })
+            .collect();
+        format!("#{}({})", annotation.type_index, pairs.join(","))
+    }
+
+    /// The resolved form of an element value
+    fn element_value(&self, value: &ElementValue) -> String {
+        match value {
+            ElementValue::Const {
+                tag,
+                const_value_index,
+            } => match (tag, self.cp().get_constant(*const_value_index)) {
+                (b's', _) => format!("\"{}\"", self.string(*const_value_index)),
+                (b'Z', Some(ConstantPoolEntry::Integer(v))) => (*v != 0).to_string(),
+                (b'C', Some(ConstantPoolEntry::Integer(v))) => {
+                    format!(
+                        "'{}'",
+                        escape(&char::from_u32(*v as u32).unwrap_or('?').to_string())
+                    )
+                }
+                (_, Some(ConstantPoolEntry::Integer(v))) => v.to_string(),
+                (_, Some(ConstantPoolEntry::Float(v))) => format!("{}f", float_to_string(*v)),
+                (_, Some(ConstantPoolEntry::Long(v))) => format!("{}l", v),
+                (_, Some(ConstantPoolEntry::Double(v))) => format!("{}d", double_to_string(*v)),
+                _ => "##INVALID_CONSTANT##".into(),
+            },
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!(
+                "{}.{}",
+                self.utf8(*type_name_index),
+                self.utf8(*const_name_index)
+            ),
+            ElementValue::Class { class_info_index } => {
+                format!("class {}", self.utf8(*class_info_index))
+            }
+            ElementValue::Annotation(annotation) => {
+                format!("@{}", self.annotation(annotation).replace('\n', ""))
+            }
+            ElementValue::Array(values) => {
+                let values: Vec<String> = values.iter().map(|v| self.element_value(v)).collect();
+                format!("[{}]", values.join(","))
+            }
+        }
+    }
+
+    /// The resolved form of an annotation, one line per element value
+    fn annotation(&self, annotation: &Annotation) -> String {
+        let desc = self.utf8(annotation.type_index);
+        let ty = render_field_type(desc);
+        if annotation.element_value_pairs.is_empty() {
+            return ty;
+        }
+        let mut str = format!("{}(\n", ty);
+        for pair in &annotation.element_value_pairs {
+            str.push_str(&format!(
+                "  {}={}\n",
+                self.utf8(pair.element_name_index),
+                self.element_value(&pair.value)
+            ));
+        }
+        str.push(')');
+        str
+    }
+
+    fn fmt_annotations(
+        &self,
+        f: &mut Formatter<'_>,
+        annotations: &[Annotation],
+        indent: &str,
+    ) -> Result {
+        for (i, annotation) in annotations.iter().enumerate() {
+            writeln!(f, "{}{}: {}", indent, i, self.annotation_raw(annotation))?;
+            for line in self.annotation(annotation).lines() {
+                writeln!(f, "{}  {}", indent, line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn fmt_attribute(&self, f: &mut Formatter<'_>, attr: &AttributeEntry, indent: &str) -> Result {
+        let name = self.attribute_name(attr);
+        match &attr.info {
+            AttributeInfo::ConstantValue { constantvalue_indx } => {
+                writeln!(
+                    f,
+                    "{}ConstantValue: {}",
+                    indent,
+                    self.constant_value(*constantvalue_indx)
+                )
+            }
+            AttributeInfo::Signature { signature_index } => writeln!(
+                f,
+                "{}{:<40}// {}",
+                indent,
+                format!("Signature: #{}", signature_index),
+                self.utf8(*signature_index)
+            ),
+            AttributeInfo::SourceFile { sourcefile_index } => {
+                writeln!(
+                    f,
+                    "{}SourceFile: \"{}\"",
+                    indent,
+                    self.utf8(*sourcefile_index)
+                )
+            }
+            AttributeInfo::Exceptions {
+                exception_index_table,
+            } => {
+                writeln!(f, "{}Exceptions:", indent)?;
+                let names: Vec<String> = exception_index_table
+                    .iter()
+                    .map(|e| self.cp().get_class_name_invalid(*e).replace('/', "."))
+                    .collect();
+                writeln!(f, "{}  throws {}", indent, names.join(", "))
+            }
+            AttributeInfo::LineNumberTable { line_number_table } => {
+                writeln!(f, "{}LineNumberTable:", indent)?;
+                for line in line_number_table {
+                    writeln!(
+                        f,
+                        "{}  line {}: {}",
+                        indent, line.line_number, line.start_pc
+                    )?;
+                }
+                Ok(())
+            }
+            AttributeInfo::LocalVariableTable {
+                local_variable_table,
+            } => {
+                writeln!(f, "{}LocalVariableTable:", indent)?;
+                writeln!(f, "{}  Start  Length  Slot  Name   Signature", indent)?;
+                for var in local_variable_table {
+                    writeln!(
+                        f,
+                        "{}{:>7}{:>8}{:>6} {:>5}   {}",
+                        indent,
+                        var.start_pc,
+                        var.length,
+                        var.index,
+                        self.utf8(var.name_index),
+                        self.utf8(var.descriptor_index)
+                    )?;
+                }
+                Ok(())
+            }
+            AttributeInfo::LocalVariableTypeTable {
+                local_variable_type_table,
+            } => {
+                writeln!(f, "{}LocalVariableTypeTable:", indent)?;
+                writeln!(f, "{}  Start  Length  Slot  Name   Signature", indent)?;
+                for var in local_variable_type_table {
+                    writeln!(
+                        f,
+                        "{}{:>7}{:>8}{:>6} {:>5}   {}",
+                        indent,
+                        var.start_pc,
+                        var.length,
+                        var.index,
+                        self.utf8(var.name_index),
+                        self.utf8(var.signature_index)
+                    )?;
+                }
+                Ok(())
+            }
+            AttributeInfo::StackMapTable { entries } => {
+                writeln!(
+                    f,
+                    "{}StackMapTable: number_of_entries = {}",
+                    indent,
+                    entries.len()
+                )?;
+                for frame in entries {
+                    let kind = match frame {
+                        StackMapFrame::SameFrame { .. } => "same",
+                        StackMapFrame::SameLocals1StackItemFrame { .. } => {
+                            "same_locals_1_stack_item"
+                        }
+                        StackMapFrame::SameLocals1StackItemFrameExtended { .. } => {
+                            "same_locals_1_stack_item_frame_extended"
+                        }
+                        StackMapFrame::ChopFrame { .. } => "chop",
+                        StackMapFrame::SameFrameExtended { .. } => "same_frame_extended",
+                        StackMapFrame::AppendFrame { .. } => "append",
+                        StackMapFrame::FullFrame { .. } => "full_frame",
+                    };
+                    writeln!(
+                        f,
+                        "{}  frame_type = {} /* {} */",
+                        indent,
+                        frame.frame_type(),
+                        kind
+                    )?;
+                    match frame {
+                        StackMapFrame::SameFrame { .. } => {}
+                        StackMapFrame::SameLocals1StackItemFrame { stack, .. } => writeln!(
+                            f,
+                            "{}    stack = {}",
+                            indent,
+                            self.verification_types(std::slice::from_ref(stack))
+                        )?,
+                        StackMapFrame::SameLocals1StackItemFrameExtended {
+                            offset_delta,
+                            stack,
+                        } => {
+                            writeln!(f, "{}    offset_delta = {}", indent, offset_delta)?;
+                            writeln!(
+                                f,
+                                "{}    stack = {}",
+                                indent,
+                                self.verification_types(std::slice::from_ref(stack))
+                            )?
+                        }
+                        StackMapFrame::ChopFrame { offset_delta, .. }
+                        | StackMapFrame::SameFrameExtended { offset_delta } => {
+                            writeln!(f, "{}    offset_delta = {}", indent, offset_delta)?
+                        }
+                        StackMapFrame::AppendFrame {
+                            offset_delta,
+                            locals,
+                            ..
+                        } => {
+                            writeln!(f, "{}    offset_delta = {}", indent, offset_delta)?;
+                            writeln!(
+                                f,
+                                "{}    locals = {}",
+                                indent,
+                                self.verification_types(locals)
+                            )?
+                        }
+                        StackMapFrame::FullFrame {
+                            offset_delta,
+                            locals,
+                            stack,
+                        } => {
+                            writeln!(f, "{}    offset_delta = {}", indent, offset_delta)?;
+                            writeln!(
+                                f,
+                                "{}    locals = {}",
+                                indent,
+                                self.verification_types(locals)
+                            )?;
+                            writeln!(
+                                f,
+                                "{}    stack = {}",
+                                indent,
+                                self.verification_types(stack)
+                            )?
+                        }
+                    }
+                }
+                Ok(())
+            }
+            AttributeInfo::BootstrapMethods { bootstrap_methods } => {
+                writeln!(f, "{}BootstrapMethods:", indent)?;
+                for (i, method) in bootstrap_methods.iter().enumerate() {
+                    writeln!(
+                        f,
+                        "{}  {}: #{} {}",
+                        indent,
+                        i,
+                        method.bootstrap_method_ref,
+                        self.constant_comment(method.bootstrap_method_ref)
+                    )?;
+                    writeln!(f, "{}    Method arguments:", indent)?;
+                    for arg in &method.bootstrap_arguments {
+                        writeln!(
+                            f,
+                            "{}      #{} {}",
+                            indent,
+                            arg,
+                            self.constant_comment(*arg).trim_start()
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+            AttributeInfo::InnerClasses { classes } => {
+                writeln!(f, "{}InnerClasses:", indent)?;
+                for inner in classes {
+                    let mut flags = inner.inner_class_access_flags;
+                    // interfaces are implicitly abstract
+                    if flags & 0x0200 != 0 {
+                        flags &= !0x0400;
+                    }
+                    let mut line = modifiers(flags, INNER_CLASS_MODIFIERS);
+                    if inner.inner_name_index != 0 {
+                        line.push_str(&format!("#{}= ", inner.inner_name_index));
+                    }
+                    line.push_str(&format!("#{}", inner.inner_class_info_index));
+                    if inner.outer_class_info_index != 0 {
+                        line.push_str(&format!(" of #{}", inner.outer_class_info_index));
+                    }
+                    let mut comment = String::new();
+                    if inner.inner_name_index != 0 {
+                        comment.push_str(self.utf8(inner.inner_name_index));
+                        comment.push('=');
+                    }
+                    comment.push_str(&format!(
+                        "class {}",
+                        self.cp()
+                            .get_class_name_invalid(inner.inner_class_info_index)
+                    ));
+                    if inner.outer_class_info_index != 0 {
+                        comment.push_str(&format!(
+                            " of class {}",
+                            self.cp()
+                                .get_class_name_invalid(inner.outer_class_info_index)
+                        ));
+                    }
+                    let line = format!("{}  {};", indent, line);
+                    writeln!(f, "{:<41} // {}", line, comment)?;
+                }
+                Ok(())
+            }
+            AttributeInfo::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                let method = if *method_index == 0 {
+                    String::new()
+                } else {
+                    let (name, _) = self.name_and_type(*method_index);
+                    format!(".{}", name)
+                };
+                writeln!(
+                    f,
+                    "{}{:<40}// {}{}",
+                    indent,
+                    format!("EnclosingMethod: #{}.#{}", class_index, method_index),
+                    self.cp()
+                        .get_class_name_invalid(*class_index)
+                        .replace('/', "."),
+                    method
+                )
+            }
+            AttributeInfo::NestHost { host_class_index } => writeln!(
+                f,
+                "{}NestHost: class {}",
+                indent,
+                self.cp().get_class_name_invalid(*host_class_index)
+            ),
+            AttributeInfo::NestMembers { classes }
+            | AttributeInfo::PermittedSubclasses { classes } => {
+                writeln!(f, "{}{}:", indent, name)?;
+                for class in classes {
+                    writeln!(
+                        f,
+                        "{}  {}",
+                        indent,
+                        self.cp().get_class_name_invalid(*class)
+                    )?;
+                }
+                Ok(())
+            }
+            AttributeInfo::Record { components } => {
+                writeln!(f, "{}Record:", indent)?;
+                for component in components {
+                    let desc = self.utf8(component.descriptor_index);
+                    let ty = render_field_type(desc);
+                    writeln!(f, "{}  {} {};", indent, ty, self.utf8(component.name_index))?;
+                    writeln!(f, "{}    descriptor: {}", indent, desc)?;
+                    for attr in &component.attributes {
+                        self.fmt_attribute(f, attr, &format!("{}    ", indent))?;
+                    }
+                }
+                Ok(())
+            }
+            AttributeInfo::ModuleMainClass { main_class_index } => writeln!(
+                f,
+                "{}{:<40}// {}",
+                indent,
+                format!("ModuleMainClass: #{}", main_class_index),
+                self.cp()
+                    .get_class_name_invalid(*main_class_index)
+                    .replace('/', ".")
+            ),
+            AttributeInfo::ModulePackages { package_index } => {
+                writeln!(f, "{}ModulePackages:", indent)?;
+                for package in package_index {
+                    writeln!(
+                        f,
+                        "{}  {:<40}// {}",
+                        indent,
+                        format!("#{}", package),
+                        self.package_name(*package)
+                    )?;
+                }
+                Ok(())
+            }
+            AttributeInfo::RuntimeVisibleAnnotations { annotations }
+            | AttributeInfo::RuntimeInvisibleAnnotations { annotations } => {
+                writeln!(f, "{}{}:", indent, name)?;
+                self.fmt_annotations(f, annotations, &format!("{}  ", indent))
+            }
+            AttributeInfo::RuntimeVisibleParameterAnnotations {
+                parameter_annotations,
+            }
+            | AttributeInfo::RuntimeInvisibleParameterAnnotations {
+                parameter_annotations,
+            } => {
+                writeln!(f, "{}{}:", indent, name)?;
+                for (i, annotations) in parameter_annotations.iter().enumerate() {
+                    writeln!(f, "{}  parameter {}:", indent, i)?;
+                    self.fmt_annotations(f, annotations, &format!("{}    ", indent))?;
+                }
+                Ok(())
+            }
+            AttributeInfo::AnnotationDefault { default_value } => {
+                writeln!(f, "{}AnnotationDefault:", indent)?;
+                writeln!(
+                    f,
+                    "{}  default_value: {}",
+                    indent,
+                    self.element_value_raw(default_value)
+                )?;
+                writeln!(f, "{}    {}", indent, self.element_value(default_value))
+            }
+            AttributeInfo::RuntimeVisibleTypeAnnotations { annotations }
+            | AttributeInfo::RuntimeInvisibleTypeAnnotations { annotations } => {
+                writeln!(f, "{}{}:", indent, name)?;
+                for (i, annotation) in annotations.iter().enumerate() {
+                    writeln!(
+                        f,
+                        "{}  {}: {}: {}",
+                        indent,
+                        i,
+                        self.annotation_raw(&annotation.annotation),
+                        type_annotation_target(annotation)
+                    )?;
+                    for line in self.annotation(&annotation.annotation).lines() {
+                        writeln!(f, "{}    {}", indent, line)?;
+                    }
+                }
+                Ok(())
+            }
+            AttributeInfo::MethodParameters { parameters } => {
+                writeln!(f, "{}MethodParameters:", indent)?;
+                writeln!(f, "{}  {:<31}Flags", indent, "Name")?;
+                for param in parameters {
+                    let name = if param.name_index == 0 {
+                        "<no name>"
+                    } else {
+                        self.utf8(param.name_index)
+                    };
+                    writeln!(
+                        f,
+                        "{}  {:<31}{}",
+                        indent,
+                        name,
+                        modifiers(param.access_flags, PARAMETER_MODIFIERS)
+                    )?;
+                }
+                Ok(())
+            }
+            AttributeInfo::Module(module) => self.fmt_module(f, module, indent),
+            AttributeInfo::Raw(data) => {
+                writeln!(
+                    f,
+                    "{}  {}: length = 0x{:X} (unknown attribute)",
+                    indent,
+                    name,
+                    data.len()
+                )?;
+                // sixteen bytes a line, a full last line is followed by an
+                // empty one
+                write!(f, "{}   ", indent)?;
+                for (i, byte) in data.iter().enumerate() {
+                    if i % 16 == 15 {
+                        write!(f, "{:02X}\n{}   ", byte, indent)?;
+                    } else {
+                        write!(f, "{:02X} ", byte)?;
+                    }
+                }
+                writeln!(f)
+            }
+            AttributeInfo::Synthetic | AttributeInfo::Deprecated => {
+                writeln!(f, "{}{}: true", indent, name)
+            }
+            AttributeInfo::SourceDebugExtension { debug_extension } => {
+                writeln!(f, "{}SourceDebugExtension:", indent)?;
+                let debug_extension = String::from_utf8_lossy(debug_extension);
+                for line in debug_extension
+                    .split(['\r', '\n'])
+                    .filter(|l| !l.is_empty())
+                {
+                    writeln!(f, "{}  {}", indent, line)?;
+                }
+                Ok(())
+            }
+            AttributeInfo::Code { .. } => writeln!(f, "{}{}:", indent, name),
+        }
+    }
+
+    /// The `Module` attribute with -v, each entry followed by its flags
+    fn fmt_module(&self, f: &mut Formatter<'_>, module: &ModuleInfo, indent: &str) -> Result {
+        // `#index` lines with the resolved constant as a comment
+        let line = |f: &mut Formatter<'_>, depth: usize, entry: String, comment: String| {
+            let indent = format!("{}{}", indent, "  ".repeat(depth));
+            if comment.is_empty() {
+                writeln!(f, "{}{}", indent, entry)
+            } else {
+                writeln!(f, "{}{:<40}// {}", indent, entry, comment)
+            }
+        };
+        let version = |index: u16| {
+            if index == 0 {
+                String::new()
+            } else {
+                self.utf8(index).into()
+            }
+        };
+        let with_flags = |index: u16, flags: u16, names: &[(u16, &str)]| {
+            let mut comment = self.constant_comment(index);
+            for (mask, name) in names {
+                if flags & mask != 0 {
+                    comment.push(' ');
+                    comment.push_str(name);
+                }
+            }
+            comment
+        };
+
+        writeln!(f, "{}Module:", indent)?;
+        line(
+            f,
+            1,
+            format!("#{},{:x}", module.module_name_index, module.module_flags),
+            with_flags(
+                module.module_name_index,
+                module.module_flags,
+                &[
+                    (0x0020, "ACC_OPEN"),
+                    (0x8000, "ACC_MANDATED"),
+                    (0x1000, "ACC_SYNTHETIC"),
+                ],
+            ),
+        )?;
+        line(
+            f,
+            1,
+            format!("#{}", module.module_version_index),
+            version(module.module_version_index),
+        )?;
+
+        line(f, 1, module.requires.len().to_string(), "requires".into())?;
+        for requires in &module.requires {
+            line(
+                f,
+                2,
+                format!("#{},{:x}", requires.requires_index, requires.requires_flags),
+                with_flags(
+                    requires.requires_index,
+                    requires.requires_flags,
+                    &[
+                        (0x0020, "ACC_TRANSITIVE"),
+                        (0x0040, "ACC_STATIC_PHASE"),
+                        (0x1000, "ACC_SYNTHETIC"),
+                        (0x8000, "ACC_MANDATED"),
+                    ],
+                ),
+            )?;
+            line(
+                f,
+                2,
+                format!("#{}", requires.requires_version_index),
+                version(requires.requires_version_index),
+            )?;
+        }
+
+        let exports = module
+            .exports
+            .iter()
+            .map(|e| (e.exports_index, e.exports_flags, &e.exports_to_index));
+        let opens = module
+            .opens
+            .iter()
+            .map(|o| (o.opens_index, o.opens_flags, &o.opens_to_index));
+        for (keyword, entries) in [
+            ("exports", exports.collect::<Vec<_>>()),
+            ("opens", opens.collect()),
+        ] {
+            line(f, 1, entries.len().to_string(), keyword.into())?;
+            for (index, flags, to) in entries {
+                let mut comment = with_flags(
+                    index,
+                    flags,
+                    &[(0x8000, "ACC_MANDATED"), (0x1000, "ACC_SYNTHETIC")],
+                );
+                if !to.is_empty() {
+                    comment.push_str(&format!(" to ... {}", to.len()));
+                }
+                line(f, 2, format!("#{},{:x}", index, flags), comment)?;
+                for target in to {
+                    let comment = format!("... to {}", self.constant_comment(*target));
+                    line(f, 3, format!("#{}", target), comment)?;
+                }
+            }
+        }
+
+        line(f, 1, module.uses_index.len().to_string(), "uses".into())?;
+        for uses in &module.uses_index {
+            line(f, 2, format!("#{}", uses), self.constant_comment(*uses))?;
+        }
+
+        line(f, 1, module.provides.len().to_string(), "provides".into())?;
+        for provides in &module.provides {
+            let with = &provides.provides_with_index;
+            let comment = format!(
+                "{} with ... {}",
+                self.constant_comment(provides.provides_index),
+                with.len()
+            );
+            line(f, 2, format!("#{}", provides.provides_index), comment)?;
+            for class in with {
+                let comment = format!("... with {}", self.constant_comment(*class));
+                line(f, 3, format!("#{}", class), comment)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn shown(&self, flags: u16) -> bool {
+        self.options.private || flags & 0x0002 == 0
+    }
+}
+
+/// The contents of a `Code` attribute, rendered on their own so they can
+/// be indented differently
+struct CodeBody<'a, 'b>(&'b Javap<'a>, &'b AttributeEntry, usize);
+
+impl<'a, 'b> Display for CodeBody<'a, 'b> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.0.fmt_code_body(f, self.1, self.2)
+    }
+}
+
+/// javap strips trailing whitespace from every line, so the output is
+/// rendered through this first and trimmed afterwards
+struct Untrimmed<'a, 'b>(&'b Javap<'a>);
+
+impl<'a> Display for Javap<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        for line in Untrimmed(self).to_string().lines() {
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b> Display for Untrimmed<'a, 'b> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let javap = self.0;
+        javap.fmt_header(f)?;
+        if !javap.options.verbose {
+            javap.fmt_module_directives(f)?;
+        }
+        // members are only set apart when there is more than one line each
+        let options = javap.options;
+        let spaced = options.verbose || options.code || options.lines;
+        let mut first = true;
+        for field in &javap.class.field_info {
+            if !javap.shown(field.access_flags.bits()) {
+                continue;
+            }
+            if !first && spaced {
+                writeln!(f)?;
+            }
+            first = false;
+            javap.fmt_field(f, field)?;
+        }
+        for method in &javap.class.method_info {
+            if !javap.shown(method.access_flags.bits()) {
+                continue;
+            }
+            if !first && spaced {
+                writeln!(f)?;
+            }
+            first = false;
+            javap.fmt_method(f, method)?;
+        }
+        writeln!(f, "}}")?;
+        if javap.options.verbose {
+            for attr in &javap.class.attribute_info {
+                javap.fmt_attribute(f, attr, "")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ mycelium_bitfield::bitfield! {
         pub const FINAL: bool;
         pub const SYNCRONIZED: bool;
         pub const BRIDGE: bool;
+        pub const VARARGS: bool;
         pub const NATIVE: bool;
         const _RESERVED = 1;
         pub const ABSTRACT: bool;
@@ -2,12 +2,21 @@ use std::fmt::Debug;
 
 use self::{attribute::*, constant::*, field::*, interface::*, method::*};
 
+pub mod annotation;
 pub mod attribute;
 pub mod constant;
 pub mod debug;
+pub mod descriptor;
 pub mod field;
+pub mod floating_decimal;
+pub mod instruction;
 pub mod interface;
+pub mod javap;
 pub mod method;
+pub mod mutf8;
+pub mod signature;
+pub mod stack_map;
+pub mod writer;
 
 mycelium_bitfield::bitfield! {
     /// Bitfield types can have doc comments.
@@ -31,6 +40,7 @@ mycelium_bitfield::bitfield! {
         pub const SYNTHETIC: bool;
         pub const ANNOTATION: bool;
         pub const ENUM: bool;
+        pub const MODULE: bool;
     }
 }
 
@@ -63,9 +73,20 @@ pub struct Class {
 pub enum ClassBuilderError {
     InvalidMagic,
     ReachedEndOfFile,
-    StringError,
+    InvalidModifiedUtf8(mutf8::Mutf8Error),
     InvalidConstantType(u8),
     InvalidReferenceKind,
+    InvalidElementValueTag(u8),
+    InvalidTargetType(u8),
+    InvalidStackMapFrameType(u8),
+    InvalidVerificationType(u8),
+    InvalidOpcode(u8),
+    InvalidWideOpcode(u8),
+    InvalidArrayType(u8),
+    InvalidSwitch,
+    InvalidDescriptor,
+    InvalidSignature,
+    InvalidAttributeLength,
 }
 
 impl Class {
@@ -90,46 +111,207 @@ impl Class {
             attribute_info: FromClassFileIter::from_arr(&mut iter)?,
         };
 
-        class.parse_attributes();
+        class.parse_attributes()?;
 
         Ok(class)
     }
 
-    fn parse_attributes(&mut self) {
-        let mut constants = self.constant_pool.take();
+    fn parse_attributes(&mut self) -> Result<(), ClassBuilderError> {
+        let constants = self.constant_pool.take();
 
-        for attr in &mut self.attribute_info {
-            attr.parse(&mut constants);
+        let result = self
+            .attribute_info
+            .iter_mut()
+            .chain(self.method_info.iter_mut().flat_map(|m| &mut m.attributes))
+            .chain(self.field_info.iter_mut().flat_map(|f| &mut f.attributes))
+            .try_for_each(|attr| attr.parse(&constants));
+
+        self.constant_pool = constants;
+        result
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.constant_pool.get_class_name(self.this_class)
+    }
+
+    pub fn super_class_name(&self) -> Option<&str> {
+        if self.super_class == 0 {
+            None
+        } else {
+            self.constant_pool.get_class_name(self.super_class)
         }
-        for blah in &mut self.method_info {
-            for attr in &mut blah.attributes {
-                attr.parse(&mut constants);
-            }
+    }
+
+    pub fn interface_names(&self) -> impl Iterator<Item = &str> {
+        self.interfaces
+            .iter()
+            .filter_map(|i| self.constant_pool.get_class_name(i.name_index))
+    }
+
+    /// The class that hosts the nest this class belongs to, every class is
+    /// in its own nest unless it has a `NestHost` attribute
+    pub fn nest_host_name(&self) -> Option<&str> {
+        self.attribute_info
+            .iter()
+            .find_map(|a| match a.info {
+                AttributeInfo::NestHost { host_class_index } => {
+                    Some(self.constant_pool.get_class_name(host_class_index))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| self.name())
+    }
+
+    pub fn nest_member_names(&self) -> Vec<&str> {
+        self.attribute_info
+            .iter()
+            .filter_map(|a| match &a.info {
+                AttributeInfo::NestMembers { classes } => Some(classes),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|c| self.constant_pool.get_class_name(*c))
+            .collect()
+    }
+
+    /// `None` if the class is not sealed
+    pub fn permitted_subclass_names(&self) -> Option<Vec<&str>> {
+        self.attribute_info.iter().find_map(|a| match &a.info {
+            AttributeInfo::PermittedSubclasses { classes } => Some(
+                classes
+                    .iter()
+                    .filter_map(|c| self.constant_pool.get_class_name(*c))
+                    .collect(),
+            ),
+            _ => None,
+        })
+    }
+
+    pub fn is_sealed(&self) -> bool {
+        self.permitted_subclass_names().is_some()
+    }
+
+    /// Whether `subclass` may directly extend or implement this class
+    pub fn permits_subclass(&self, subclass: &str) -> bool {
+        match self.permitted_subclass_names() {
+            Some(permitted) => permitted.contains(&subclass),
+            None => true,
         }
-        for blah in &mut self.field_info {
-            for attr in &mut blah.attributes {
-                attr.parse(&mut constants);
+    }
+
+    pub fn record_components(&self) -> Option<&[RecordComponent]> {
+        self.attribute_info.iter().find_map(|a| match &a.info {
+            AttributeInfo::Record { components } => Some(components.as_slice()),
+            _ => None,
+        })
+    }
+
+    pub fn is_record(&self) -> bool {
+        self.record_components().is_some()
+    }
+
+    /// The raw bytecode of the method at `method_index`, `None` for
+    /// abstract and native methods
+    pub fn method_code(&self, method_index: usize) -> Option<&[u8]> {
+        self.method_info
+            .get(method_index)?
+            .attributes
+            .iter()
+            .find_map(|a| match &a.info {
+                AttributeInfo::Code { code, .. } => Some(code.as_slice()),
+                _ => None,
+            })
+    }
+
+    /// The exception handlers of the method at `method_index` in the
+    /// order they are searched, empty without code
+    pub fn method_exception_table(&self, method_index: usize) -> &[ExceptionTableEntry] {
+        self.method_info
+            .get(method_index)
+            .and_then(|m| {
+                m.attributes.iter().find_map(|a| match &a.info {
+                    AttributeInfo::Code {
+                        exception_table, ..
+                    } => Some(exception_table.as_slice()),
+                    _ => None,
+                })
+            })
+            .unwrap_or(&[])
+    }
+
+    /// The source line of the instruction at `pc`, from the method's
+    /// `LineNumberTable`
+    pub fn line_number(&self, method_index: usize, pc: usize) -> Option<u16> {
+        let code = self
+            .method_info
+            .get(method_index)?
+            .attributes
+            .iter()
+            .find_map(|a| match &a.info {
+                AttributeInfo::Code { attributes, .. } => Some(attributes),
+                _ => None,
+            })?;
+        code.iter()
+            .filter_map(|a| match &a.info {
+                AttributeInfo::LineNumberTable { line_number_table } => Some(line_number_table),
+                _ => None,
+            })
+            .flatten()
+            .filter(|l| l.start_pc as usize <= pc)
+            .max_by_key(|l| l.start_pc)
+            .map(|l| l.line_number)
+    }
+
+    /// Entry `index` of the `BootstrapMethods` attribute, which
+    /// `CONSTANT_Dynamic` and `CONSTANT_InvokeDynamic` entries point into
+    pub fn bootstrap_method(&self, index: u16) -> Option<&BootstrapMethod> {
+        self.attribute_info.iter().find_map(|a| match &a.info {
+            AttributeInfo::BootstrapMethods { bootstrap_methods } => {
+                bootstrap_methods.get(index as usize)
             }
-        }
+            _ => None,
+        })
+    }
 
-        self.constant_pool = constants;
+    pub fn source_file(&self) -> Option<&str> {
+        self.attribute_info.iter().find_map(|a| match a.info {
+            AttributeInfo::SourceFile { sourcefile_index } => {
+                self.constant_pool.get_const_utd8(sourcefile_index)
+            }
+            _ => None,
+        })
     }
 
-    pub fn get_method_from_name(&self, method_name: &str) -> Option<&MethodEntry> {
-        if let Some(index) = self.method_entry_index_from_name(method_name) {
+    pub fn get_method_from_name(
+        &self,
+        method_name: &str,
+        descriptor: &str,
+    ) -> Option<&MethodEntry> {
+        if let Some(index) = self.method_entry_index_from_name(method_name, descriptor) {
             self.method_info.get(index)
         } else {
             None
         }
     }
 
-    fn method_entry_index_from_name(&self, method_name: &str) -> Option<usize> {
+    /// Methods are identified by name and descriptor together, overloads
+    /// share a name
+    pub fn method_entry_index_from_name(
+        &self,
+        method_name: &str,
+        descriptor: &str,
+    ) -> Option<usize> {
         self.method_info.iter().position(|m| {
-            if let Some(name) = self.constant_pool.get_const_utd8(m.name_index) {
-                name.eq(method_name)
-            } else {
-                false
-            }
+            self.constant_pool.get_const_utd8(m.name_index) == Some(method_name)
+                && self.constant_pool.get_const_utd8(m.descriptor_index) == Some(descriptor)
+        })
+    }
+
+    /// Fields are identified by name and descriptor together
+    pub fn field_entry_index_from_name(&self, field_name: &str, descriptor: &str) -> Option<usize> {
+        self.field_info.iter().position(|f| {
+            self.constant_pool.get_const_utd8(f.name_index) == Some(field_name)
+                && self.constant_pool.get_const_utd8(f.descriptor_index) == Some(descriptor)
         })
     }
 }
@@ -181,7 +363,7 @@ impl<'a> ClassFileIter<'a> {
     }
 
     pub fn next_u64(&mut self) -> Result<u64, ClassBuilderError> {
-        Ok(((self.next_u8()? as u64) << 54)
+        Ok(((self.next_u8()? as u64) << 56)
             | ((self.next_u8()? as u64) << 48)
             | ((self.next_u8()? as u64) << 40)
             | ((self.next_u8()? as u64) << 32)
@@ -191,6 +373,14 @@ impl<'a> ClassFileIter<'a> {
             | self.next_u8()? as u64)
     }
 
+    pub fn remaining(&self) -> usize {
+        self.slice.len().saturating_sub(self.index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
     pub fn next_n_u8(&mut self, len: usize) -> Result<Vec<u8>, ClassBuilderError> {
         let mut vec = Vec::new();
         for _ in 0..len {
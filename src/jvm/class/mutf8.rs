@@ -0,0 +1,196 @@
+//! Modified UTF-8 as used by `CONSTANT_Utf8` (JVMS 4.4.7)
+//!
+//! It differs from standard UTF-8 in two ways: NUL is written as the two
+//! bytes `0xC0 0x80` so no encoded string ever contains a zero byte, and
+//! characters outside the BMP are written as a surrogate pair with each
+//! half encoded on its own in three bytes. Because of the second rule a
+//! valid string may hold unpaired surrogates, so decoding produces UTF-16
+//! code units rather than a Rust `String`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mutf8Error {
+    /// byte offset into the encoded string where the problem starts
+    pub offset: usize,
+    pub kind: Mutf8ErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutf8ErrorKind {
+    /// a raw `0x00` byte, NUL must be encoded as `0xC0 0x80`
+    NulByte,
+    /// a byte that cannot start a character (`0x80..=0xBF` or `0xF0..`)
+    InvalidLeadByte(u8),
+    /// a byte after the lead byte that is not `10xxxxxx`
+    InvalidContinuation(u8),
+    /// the string ends in the middle of a character
+    Truncated,
+    /// a character encoded with more bytes than necessary
+    Overlong,
+}
+
+/// Decodes Modified UTF-8 into UTF-16 code units
+pub fn decode(bytes: &[u8]) -> Result<Vec<u16>, Mutf8Error> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    let err = |offset, kind| Err(Mutf8Error { offset, kind });
+    let continuation = |at: usize, start: usize| -> Result<u16, Mutf8Error> {
+        match bytes.get(at) {
+            Some(b) if b & 0xC0 == 0x80 => Ok((b & 0x3F) as u16),
+            Some(b) => Err(Mutf8Error {
+                offset: start,
+                kind: Mutf8ErrorKind::InvalidContinuation(*b),
+            }),
+            None => Err(Mutf8Error {
+                offset: start,
+                kind: Mutf8ErrorKind::Truncated,
+            }),
+        }
+    };
+
+    while i < bytes.len() {
+        let x = bytes[i];
+        match x {
+            0x00 => return err(i, Mutf8ErrorKind::NulByte),
+            0x01..=0x7F => {
+                units.push(x as u16);
+                i += 1;
+            }
+            0xC0..=0xDF => {
+                let c = ((x as u16 & 0x1F) << 6) | continuation(i + 1, i)?;
+                // 0xC0 0x80 is the only legal two byte form below 0x80
+                if c != 0 && c < 0x80 {
+                    return err(i, Mutf8ErrorKind::Overlong);
+                }
+                units.push(c);
+                i += 2;
+            }
+            0xE0..=0xEF => {
+                let c = ((x as u16 & 0x0F) << 12)
+                    | (continuation(i + 1, i)? << 6)
+                    | continuation(i + 2, i)?;
+                if c < 0x800 {
+                    return err(i, Mutf8ErrorKind::Overlong);
+                }
+                units.push(c);
+                i += 3;
+            }
+            _ => return err(i, Mutf8ErrorKind::InvalidLeadByte(x)),
+        }
+    }
+    Ok(units)
+}
+
+/// Encodes UTF-16 code units, paired or not, into Modified UTF-8
+pub fn encode(units: &[u16]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(units.len());
+    for &c in units {
+        match c {
+            0x0001..=0x007F => bytes.push(c as u8),
+            0x0000 | 0x0080..=0x07FF => {
+                bytes.push(0xC0 | (c >> 6) as u8);
+                bytes.push(0x80 | (c & 0x3F) as u8);
+            }
+            _ => {
+                bytes.push(0xE0 | (c >> 12) as u8);
+                bytes.push(0x80 | ((c >> 6) & 0x3F) as u8);
+                bytes.push(0x80 | (c & 0x3F) as u8);
+            }
+        }
+    }
+    bytes
+}
+
+pub fn encode_str(str: &str) -> Vec<u8> {
+    encode(&str.encode_utf16().collect::<Vec<_>>())
+}
+
+/// Decodes into a `String` when possible, strings holding unpaired
+/// surrogates are handed back as their code units
+pub fn decode_string(bytes: &[u8]) -> Result<Result<String, Vec<u16>>, Mutf8Error> {
+    let units = decode(bytes)?;
+    Ok(String::from_utf16(&units).map_err(|_| units))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(offset: usize, kind: Mutf8ErrorKind) -> Result<Vec<u16>, Mutf8Error> {
+        Err(Mutf8Error { offset, kind })
+    }
+
+    #[test]
+    fn nul_is_two_bytes() {
+        assert_eq!(encode(&[0x41, 0x00, 0x42]), [0x41, 0xC0, 0x80, 0x42]);
+        assert_eq!(
+            decode(&[0x41, 0xC0, 0x80, 0x42]),
+            Ok(vec![0x41, 0x00, 0x42])
+        );
+        assert_eq!(decode(&[0x41, 0x00]), error(1, Mutf8ErrorKind::NulByte));
+    }
+
+    #[test]
+    fn round_trips() {
+        for s in ["", "hello", "é", "ü→€", "\u{7FF}\u{800}\u{FFFF}", "a\0b"] {
+            let bytes = encode_str(s);
+            assert_eq!(decode_string(&bytes), Ok(Ok(s.to_owned())), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn rejects_overlong() {
+        // 'A' in two bytes and NUL in three
+        assert_eq!(decode(&[0xC1, 0x81]), error(0, Mutf8ErrorKind::Overlong));
+        assert_eq!(
+            decode(&[0x41, 0xE0, 0x80, 0x80]),
+            error(1, Mutf8ErrorKind::Overlong)
+        );
+        // U+07FF in three bytes
+        assert_eq!(
+            decode(&[0xE0, 0x9F, 0xBF]),
+            error(0, Mutf8ErrorKind::Overlong)
+        );
+    }
+
+    #[test]
+    fn rejects_truncated() {
+        assert_eq!(decode(&[0x41, 0xC3]), error(1, Mutf8ErrorKind::Truncated));
+        assert_eq!(decode(&[0xE2, 0x82]), error(0, Mutf8ErrorKind::Truncated));
+        assert_eq!(
+            decode(&[0x41, 0x42, 0xE2, 0x41, 0xAC]),
+            error(2, Mutf8ErrorKind::InvalidContinuation(0x41))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lead_bytes() {
+        assert_eq!(
+            decode(&[0x80]),
+            error(0, Mutf8ErrorKind::InvalidLeadByte(0x80))
+        );
+        // four byte UTF-8 is not Modified UTF-8
+        assert_eq!(
+            decode(&[0x41, 0xF0, 0x9F, 0x98, 0x80]),
+            error(1, Mutf8ErrorKind::InvalidLeadByte(0xF0))
+        );
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        // U+1F600 is the pair D83D DE00, each half in three bytes
+        let bytes = [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80];
+        assert_eq!(encode_str("\u{1F600}"), bytes);
+        assert_eq!(decode(&bytes), Ok(vec![0xD83D, 0xDE00]));
+        assert_eq!(decode_string(&bytes), Ok(Ok("\u{1F600}".to_owned())));
+    }
+
+    #[test]
+    fn lone_surrogates() {
+        let units = [0x41, 0xDE00, 0xD83D];
+        let bytes = encode(&units);
+        assert_eq!(bytes, [0x41, 0xED, 0xB8, 0x80, 0xED, 0xA0, 0xBD]);
+        assert_eq!(decode(&bytes), Ok(units.to_vec()));
+        assert_eq!(decode_string(&bytes), Ok(Err(units.to_vec())));
+    }
+}
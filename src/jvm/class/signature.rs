@@ -0,0 +1,470 @@
+//! Generic signatures from the `Signature` attribute (JVMS 4.7.9.1)
+
+use std::fmt::{Display, Formatter};
+
+use super::{
+    attribute::AttributeInfo,
+    descriptor::{BaseType, Reader},
+    field::FieldEntry,
+    method::MethodEntry,
+    Class, ClassBuilderError,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeSignature {
+    Base(BaseType),
+    Class(ClassTypeSignature),
+    TypeVariable(String),
+    Array(Box<TypeSignature>),
+}
+
+/// `pkg/Outer<A>.Inner<B>` is stored as the segments `pkg/Outer<A>` and
+/// `Inner<B>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassTypeSignature {
+    pub segments: Vec<SimpleClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleClassTypeSignature {
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeArgument {
+    /// `*`
+    Any,
+    Exact(TypeSignature),
+    /// `+`
+    Extends(TypeSignature),
+    /// `-`
+    Super(TypeSignature),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParameter {
+    pub name: String,
+    pub class_bound: Option<TypeSignature>,
+    pub interface_bounds: Vec<TypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub super_class: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub parameters: Vec<TypeSignature>,
+    /// `None` for void
+    pub return_type: Option<TypeSignature>,
+    pub throws: Vec<TypeSignature>,
+}
+
+impl TypeSignature {
+    /// Parses a field signature, which is any reference type
+    pub fn parse(sig: &str) -> Result<Self, ClassBuilderError> {
+        let mut reader = Reader::new(sig);
+        match Self::read(&mut reader) {
+            Some(ty @ (Self::Class(_) | Self::TypeVariable(_) | Self::Array(_)))
+                if reader.is_empty() =>
+            {
+                Ok(ty)
+            }
+            _ => Err(ClassBuilderError::InvalidSignature),
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        match reader.peek()? {
+            b'L' => Some(Self::Class(ClassTypeSignature::read(reader)?)),
+            b'T' => {
+                reader.next();
+                Some(Self::TypeVariable(reader.take_until(b';')?.into()))
+            }
+            b'[' => {
+                reader.next();
+                Some(Self::Array(Box::new(Self::read(reader)?)))
+            }
+            c => {
+                reader.next();
+                Some(Self::Base(BaseType::from_char(c)?))
+            }
+        }
+    }
+}
+
+impl ClassTypeSignature {
+    fn read(reader: &mut Reader) -> Option<Self> {
+        reader.expect(b'L')?;
+        let mut segments = Vec::new();
+        loop {
+            let name = reader.take_ident(b"<.;")?.into();
+            let mut type_arguments = Vec::new();
+            if reader.peek()? == b'<' {
+                reader.next();
+                while reader.peek()? != b'>' {
+                    type_arguments.push(TypeArgument::read(reader)?);
+                }
+                reader.next();
+            }
+            segments.push(SimpleClassTypeSignature {
+                name,
+                type_arguments,
+            });
+            match reader.next()? {
+                b'.' => {}
+                b';' => return Some(Self { segments }),
+                _ => return None,
+            }
+        }
+    }
+
+    /// The binary name of the class with `/` separators and `$` before
+    /// nested classes, type arguments are dropped
+    pub fn erasure(&self) -> String {
+        let names: Vec<&str> = self.segments.iter().map(|s| s.name.as_str()).collect();
+        names.join("$")
+    }
+}
+
+impl TypeArgument {
+    fn read(reader: &mut Reader) -> Option<Self> {
+        Some(match reader.peek()? {
+            b'*' => {
+                reader.next();
+                Self::Any
+            }
+            b'+' => {
+                reader.next();
+                Self::Extends(TypeSignature::read(reader)?)
+            }
+            b'-' => {
+                reader.next();
+                Self::Super(TypeSignature::read(reader)?)
+            }
+            _ => Self::Exact(TypeSignature::read(reader)?),
+        })
+    }
+}
+
+impl TypeParameter {
+    fn read_all(reader: &mut Reader) -> Option<Vec<Self>> {
+        let mut params = Vec::new();
+        if reader.peek()? != b'<' {
+            return Some(params);
+        }
+        reader.next();
+        while reader.peek()? != b'>' {
+            let name = reader.take_ident(b":")?.into();
+            reader.expect(b':')?;
+            // the class bound may be left out, an interface bound follows
+            let class_bound = match reader.peek()? {
+                b':' => None,
+                _ => Some(TypeSignature::read(reader)?),
+            };
+            let mut interface_bounds = Vec::new();
+            while reader.peek()? == b':' {
+                reader.next();
+                interface_bounds.push(TypeSignature::read(reader)?);
+            }
+            params.push(Self {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+        }
+        reader.next();
+        Some(params)
+    }
+}
+
+impl ClassSignature {
+    pub fn parse(sig: &str) -> Result<Self, ClassBuilderError> {
+        Self::read(&mut Reader::new(sig)).ok_or(ClassBuilderError::InvalidSignature)
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        let type_parameters = TypeParameter::read_all(reader)?;
+        let super_class = ClassTypeSignature::read(reader)?;
+        let mut interfaces = Vec::new();
+        while !reader.is_empty() {
+            interfaces.push(ClassTypeSignature::read(reader)?);
+        }
+        Some(Self {
+            type_parameters,
+            super_class,
+            interfaces,
+        })
+    }
+}
+
+impl MethodSignature {
+    pub fn parse(sig: &str) -> Result<Self, ClassBuilderError> {
+        Self::read(&mut Reader::new(sig)).ok_or(ClassBuilderError::InvalidSignature)
+    }
+
+    fn read(reader: &mut Reader) -> Option<Self> {
+        let type_parameters = TypeParameter::read_all(reader)?;
+        reader.expect(b'(')?;
+        let mut parameters = Vec::new();
+        while reader.peek()? != b')' {
+            parameters.push(TypeSignature::read(reader)?);
+        }
+        reader.next();
+        let return_type = if reader.peek()? == b'V' {
+            reader.next();
+            None
+        } else {
+            Some(TypeSignature::read(reader)?)
+        };
+        let mut throws = Vec::new();
+        while !reader.is_empty() {
+            reader.expect(b'^')?;
+            throws.push(TypeSignature::read(reader)?);
+        }
+        Some(Self {
+            type_parameters,
+            parameters,
+            return_type,
+            throws,
+        })
+    }
+}
+
+fn signature_index(attributes: &[super::attribute::AttributeEntry]) -> Option<u16> {
+    attributes.iter().find_map(|a| match a.info {
+        AttributeInfo::Signature { signature_index } => Some(signature_index),
+        _ => None,
+    })
+}
+
+impl Class {
+    /// The parsed `Signature` attribute, `None` when the class has none
+    pub fn signature(&self) -> Option<Result<ClassSignature, ClassBuilderError>> {
+        let index = signature_index(&self.attribute_info)?;
+        Some(
+            self.constant_pool
+                .get_const_utd8(index)
+                .ok_or(ClassBuilderError::InvalidSignature)
+                .and_then(ClassSignature::parse),
+        )
+    }
+}
+
+impl FieldEntry {
+    pub fn signature(
+        &self,
+        constants: &super::constant::ConstantPool,
+    ) -> Option<Result<TypeSignature, ClassBuilderError>> {
+        let index = signature_index(&self.attributes)?;
+        Some(
+            constants
+                .get_const_utd8(index)
+                .ok_or(ClassBuilderError::InvalidSignature)
+                .and_then(TypeSignature::parse),
+        )
+    }
+}
+
+impl MethodEntry {
+    pub fn signature(
+        &self,
+        constants: &super::constant::ConstantPool,
+    ) -> Option<Result<MethodSignature, ClassBuilderError>> {
+        let index = signature_index(&self.attributes)?;
+        Some(
+            constants
+                .get_const_utd8(index)
+                .ok_or(ClassBuilderError::InvalidSignature)
+                .and_then(MethodSignature::parse),
+        )
+    }
+}
+
+fn write_list<T: Display>(f: &mut Formatter<'_>, items: &[T], sep: &str) -> std::fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", sep)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// The java source form, `java.util.Map<K, ? extends V>[]`
+impl Display for TypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Base(base) => write!(f, "{}", base.name()),
+            Self::Class(class) => write!(f, "{}", class),
+            Self::TypeVariable(name) => write!(f, "{}", name),
+            Self::Array(component) => write!(f, "{}[]", component),
+        }
+    }
+}
+
+impl Display for ClassTypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_list(f, &self.segments, ".")
+    }
+}
+
+impl Display for SimpleClassTypeSignature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name.replace('/', "."))?;
+        if !self.type_arguments.is_empty() {
+            write!(f, "<")?;
+            write_list(f, &self.type_arguments, ", ")?;
+            write!(f, ">")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for TypeArgument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Any => write!(f, "?"),
+            Self::Exact(ty) => write!(f, "{}", ty),
+            Self::Extends(ty) => write!(f, "? extends {}", ty),
+            Self::Super(ty) => write!(f, "? super {}", ty),
+        }
+    }
+}
+
+/// `T extends Bound & Interface`, like javap the bound is always written
+impl Display for TypeParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} extends ", self.name)?;
+        let bounds: Vec<&TypeSignature> = self
+            .class_bound
+            .iter()
+            .chain(self.interface_bounds.iter())
+            .collect();
+        write_list(f, &bounds, " & ")
+    }
+}
+
+/// Renders a list of type parameters as `<A, B extends C>`, nothing when
+/// the list is empty
+pub struct TypeParameters<'a>(pub &'a [TypeParameter]);
+
+impl<'a> Display for TypeParameters<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        write!(f, "<")?;
+        write_list(f, self.0, ", ")?;
+        write!(f, ">")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(segments: &[(&str, Vec<TypeArgument>)]) -> ClassTypeSignature {
+        ClassTypeSignature {
+            segments: segments
+                .iter()
+                .map(|(name, type_arguments)| SimpleClassTypeSignature {
+                    name: name.to_string(),
+                    type_arguments: type_arguments.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn variable(name: &str) -> TypeSignature {
+        TypeSignature::TypeVariable(name.into())
+    }
+
+    #[test]
+    fn nested_type_arguments() {
+        let sig = TypeSignature::parse("Lpkg/Outer<TA;>.Inner<TB;>;").unwrap();
+        assert_eq!(
+            sig,
+            TypeSignature::Class(class(&[
+                ("pkg/Outer", vec![TypeArgument::Exact(variable("A"))]),
+                ("Inner", vec![TypeArgument::Exact(variable("B"))]),
+            ]))
+        );
+        assert_eq!(sig.to_string(), "pkg.Outer<A>.Inner<B>");
+        if let TypeSignature::Class(class) = sig {
+            assert_eq!(class.erasure(), "pkg/Outer$Inner");
+        }
+    }
+
+    #[test]
+    fn wildcards() {
+        let sig = TypeSignature::parse("Ljava/util/Map<*+Ljava/lang/Number;-TT;>;").unwrap();
+        let object = |name: &str| TypeSignature::Class(class(&[(name, vec![])]));
+        assert_eq!(
+            sig,
+            TypeSignature::Class(class(&[(
+                "java/util/Map",
+                vec![
+                    TypeArgument::Any,
+                    TypeArgument::Extends(object("java/lang/Number")),
+                    TypeArgument::Super(variable("T")),
+                ]
+            )]))
+        );
+        assert_eq!(
+            sig.to_string(),
+            "java.util.Map<?, ? extends java.lang.Number, ? super T>"
+        );
+    }
+
+    #[test]
+    fn omitted_class_bound() {
+        let sig = ClassSignature::parse(
+            "<T::Ljava/lang/Comparable<TT;>;U:Ljava/lang/Object;>Ljava/lang/Object;",
+        )
+        .unwrap();
+        assert_eq!(sig.type_parameters.len(), 2);
+        assert_eq!(sig.type_parameters[0].class_bound, None);
+        assert_eq!(sig.type_parameters[0].interface_bounds.len(), 1);
+        assert!(sig.type_parameters[1].class_bound.is_some());
+        assert_eq!(
+            TypeParameters(&sig.type_parameters).to_string(),
+            "<T extends java.lang.Comparable<T>, U extends java.lang.Object>"
+        );
+    }
+
+    #[test]
+    fn throws_clauses() {
+        let sig =
+            MethodSignature::parse("<E:Ljava/lang/Exception;>(TE;)V^TE;^Ljava/io/IOException;")
+                .unwrap();
+        assert_eq!(sig.parameters, [variable("E")]);
+        assert_eq!(sig.return_type, None);
+        assert_eq!(
+            sig.throws,
+            [
+                variable("E"),
+                TypeSignature::Class(class(&[("java/io/IOException", vec![])])),
+            ]
+        );
+        assert!(MethodSignature::parse("()VTE;").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_signatures() {
+        for sig in [
+            "I",
+            "Ljava/util/List<TT;>",
+            "TT",
+            "L.Inner;",
+            "Ljava/lang/Object;;",
+        ] {
+            assert!(TypeSignature::parse(sig).is_err(), "{}", sig);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use super::{ClassBuilderError, ClassFileIter, FromClassFileIter};
+
+#[derive(Debug)]
+pub enum StackMapFrame {
+    /// frame_type 0..=63, the offset_delta is the frame_type
+    SameFrame { frame_type: u8 },
+    /// frame_type 64..=127, the offset_delta is frame_type - 64
+    SameLocals1StackItemFrame {
+        frame_type: u8,
+        stack: VerificationTypeInfo,
+    },
+    /// frame_type 247
+    SameLocals1StackItemFrameExtended {
+        offset_delta: u16,
+        stack: VerificationTypeInfo,
+    },
+    /// frame_type 248..=250, 251 - frame_type locals are removed
+    ChopFrame { frame_type: u8, offset_delta: u16 },
+    /// frame_type 251
+    SameFrameExtended { offset_delta: u16 },
+    /// frame_type 252..=254, frame_type - 251 locals are added
+    AppendFrame {
+        frame_type: u8,
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo>,
+    },
+    /// frame_type 255
+    FullFrame {
+        offset_delta: u16,
+        //len u16
+        locals: Vec<VerificationTypeInfo>,
+        //len u16
+        stack: Vec<VerificationTypeInfo>,
+    },
+}
+
+impl StackMapFrame {
+    pub fn frame_type(&self) -> u8 {
+        match self {
+            Self::SameFrame { frame_type }
+            | Self::SameLocals1StackItemFrame { frame_type, .. }
+            | Self::ChopFrame { frame_type, .. }
+            | Self::AppendFrame { frame_type, .. } => *frame_type,
+            Self::SameLocals1StackItemFrameExtended { .. } => 247,
+            Self::SameFrameExtended { .. } => 251,
+            Self::FullFrame { .. } => 255,
+        }
+    }
+
+    pub fn offset_delta(&self) -> u16 {
+        match self {
+            Self::SameFrame { frame_type } => *frame_type as u16,
+            Self::SameLocals1StackItemFrame { frame_type, .. } => *frame_type as u16 - 64,
+            Self::SameLocals1StackItemFrameExtended { offset_delta, .. }
+            | Self::ChopFrame { offset_delta, .. }
+            | Self::SameFrameExtended { offset_delta }
+            | Self::AppendFrame { offset_delta, .. }
+            | Self::FullFrame { offset_delta, .. } => *offset_delta,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationTypeInfo {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    Object { cpool_index: u16 },
+    Uninitialized { offset: u16 },
+}
+
+impl VerificationTypeInfo {
+    pub fn tag(&self) -> u8 {
+        match self {
+            Self::Top => 0,
+            Self::Integer => 1,
+            Self::Float => 2,
+            Self::Double => 3,
+            Self::Long => 4,
+            Self::Null => 5,
+            Self::UninitializedThis => 6,
+            Self::Object { .. } => 7,
+            Self::Uninitialized { .. } => 8,
+        }
+    }
+}
+
+impl FromClassFileIter for StackMapFrame {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        let frame_type = iter.next_u8()?;
+        Ok(match frame_type {
+            0..=63 => StackMapFrame::SameFrame { frame_type },
+            64..=127 => StackMapFrame::SameLocals1StackItemFrame {
+                frame_type,
+                stack: VerificationTypeInfo::from_iter(iter)?,
+            },
+            247 => StackMapFrame::SameLocals1StackItemFrameExtended {
+                offset_delta: iter.next_u16()?,
+                stack: VerificationTypeInfo::from_iter(iter)?,
+            },
+            248..=250 => StackMapFrame::ChopFrame {
+                frame_type,
+                offset_delta: iter.next_u16()?,
+            },
+            251 => StackMapFrame::SameFrameExtended {
+                offset_delta: iter.next_u16()?,
+            },
+            252..=254 => {
+                let offset_delta = iter.next_u16()?;
+                let mut locals = Vec::new();
+                for _ in 0..frame_type - 251 {
+                    locals.push(VerificationTypeInfo::from_iter(iter)?);
+                }
+                StackMapFrame::AppendFrame {
+                    frame_type,
+                    offset_delta,
+                    locals,
+                }
+            }
+            255 => StackMapFrame::FullFrame {
+                offset_delta: iter.next_u16()?,
+                locals: FromClassFileIter::from_arr(iter)?,
+                stack: FromClassFileIter::from_arr(iter)?,
+            },
+            c => return Err(ClassBuilderError::InvalidStackMapFrameType(c)),
+        })
+    }
+}
+
+impl FromClassFileIter for VerificationTypeInfo {
+    fn from_iter(iter: &mut ClassFileIter) -> Result<Self, ClassBuilderError> {
+        Ok(match iter.next_u8()? {
+            0 => Self::Top,
+            1 => Self::Integer,
+            2 => Self::Float,
+            3 => Self::Double,
+            4 => Self::Long,
+            5 => Self::Null,
+            6 => Self::UninitializedThis,
+            7 => Self::Object {
+                cpool_index: iter.next_u16()?,
+            },
+            8 => Self::Uninitialized {
+                offset: iter.next_u16()?,
+            },
+            c => return Err(ClassBuilderError::InvalidVerificationType(c)),
+        })
+    }
+}
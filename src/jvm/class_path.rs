@@ -0,0 +1,271 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    class::{Class, ClassBuilderError},
+    jar::{JarClassSource, JarError},
+};
+
+#[derive(Debug)]
+pub enum ClassPathError {
+    Io(std::io::Error),
+    Jar(JarError),
+    Class(ClassBuilderError),
+}
+
+impl From<std::io::Error> for ClassPathError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<JarError> for ClassPathError {
+    fn from(err: JarError) -> Self {
+        match err {
+            JarError::Class(err) => Self::Class(err),
+            err => Self::Jar(err),
+        }
+    }
+}
+
+impl From<ClassBuilderError> for ClassPathError {
+    fn from(err: ClassBuilderError) -> Self {
+        Self::Class(err)
+    }
+}
+
+#[derive(Debug)]
+pub enum ClassSource {
+    /// a directory laid out by package, `java/lang/Object.class`
+    Directory(PathBuf),
+    Jar(JarClassSource),
+    /// class file bytes keyed by binary name
+    Memory(HashMap<String, Vec<u8>>),
+}
+
+impl ClassSource {
+    fn read_class_bytes(&mut self, name: &str) -> Result<Option<Vec<u8>>, ClassPathError> {
+        match self {
+            Self::Directory(dir) => {
+                let path = dir.join(format!("{}.class", name));
+                match std::fs::read(path) {
+                    Ok(data) => Ok(Some(data)),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(err) => Err(err.into()),
+                }
+            }
+            Self::Jar(jar) => Ok(jar.read_class_bytes(name)?),
+            Self::Memory(map) => Ok(map.get(name).cloned()),
+        }
+    }
+}
+
+/// Ordered class sources, the first one holding a class wins
+#[derive(Debug, Default)]
+pub struct ClassPath {
+    sources: Vec<ClassSource>,
+}
+
+impl ClassPath {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn sources(&self) -> &[ClassSource] {
+        &self.sources
+    }
+
+    pub fn push_source(&mut self, source: ClassSource) {
+        self.sources.push(source);
+    }
+
+    pub fn push_dir(&mut self, dir: impl AsRef<Path>) {
+        self.push_source(ClassSource::Directory(dir.as_ref().to_path_buf()));
+    }
+
+    /// Adds a jar followed by the jars named in its manifest `Class-Path`,
+    /// entries that don't exist are skipped like the reference
+    /// implementation does
+    pub fn push_jar(&mut self, path: impl AsRef<Path>) -> Result<(), ClassPathError> {
+        let jar = JarClassSource::open(path)?;
+        let class_path = jar.class_path();
+        self.push_source(ClassSource::Jar(jar));
+        for path in class_path {
+            if self.contains_jar(&path) {
+                continue;
+            }
+            if path.is_dir() {
+                self.push_dir(path);
+            } else if path.is_file() {
+                self.push_jar(path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares canonical paths so `lib/../a.jar` can't start a cycle
+    fn contains_jar(&self, path: &Path) -> bool {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.sources.iter().any(|s| match s {
+            ClassSource::Jar(jar) => jar.path().canonicalize().is_ok_and(|p| p == path),
+            _ => false,
+        })
+    }
+
+    /// Adds raw class file bytes to the last in-memory source, creating
+    /// one at the end of the path when there is none
+    pub fn insert_bytes(&mut self, name: impl Into<String>, data: Vec<u8>) {
+        if !matches!(self.sources.last(), Some(ClassSource::Memory(_))) {
+            self.push_source(ClassSource::Memory(HashMap::new()));
+        }
+        if let Some(ClassSource::Memory(map)) = self.sources.last_mut() {
+            map.insert(name.into(), data);
+        }
+    }
+
+    /// `None` for anything that isn't a binary name, `../secret` would
+    /// otherwise read outside of a directory source
+    pub fn find_class_bytes(&mut self, name: &str) -> Result<Option<Vec<u8>>, ClassPathError> {
+        if !is_binary_name(name) {
+            return Ok(None);
+        }
+        for source in &mut self.sources {
+            if let Some(data) = source.read_class_bytes(name)? {
+                return Ok(Some(data));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Finds and parses `name`, a binary name like `java/lang/Object`
+    pub fn load_class(&mut self, name: &str) -> Result<Option<Class>, ClassPathError> {
+        match self.find_class_bytes(name)? {
+            Some(data) => Ok(Some(Class::new(&data)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Binary names have no empty segments and no `.`, `;`, `[` or `\`
+fn is_binary_name(name: &str) -> bool {
+    name.split('/')
+        .all(|s| !s.is_empty() && !s.contains(['.', ';', '[', '\\']))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use zip::{write::FileOptions, ZipWriter};
+
+    use super::*;
+
+    /// A fresh directory under the system temp dir
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rusty_jvm_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_jar(path: &Path, manifest: &str, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(std::fs::File::create(path).unwrap());
+        zip.start_file("META-INF/MANIFEST.MF", FileOptions::default())
+            .unwrap();
+        zip.write_all(manifest.as_bytes()).unwrap();
+        for (name, data) in entries {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn find(class_path: &mut ClassPath, name: &str) -> Option<Vec<u8>> {
+        class_path.find_class_bytes(name).unwrap()
+    }
+
+    #[test]
+    fn first_source_wins() {
+        let dir = temp_dir("precedence");
+        std::fs::create_dir_all(dir.join("classes/a")).unwrap();
+        std::fs::write(dir.join("classes/a/Both.class"), b"dir").unwrap();
+        std::fs::write(dir.join("classes/a/Dir.class"), b"dir").unwrap();
+        write_jar(
+            &dir.join("a.jar"),
+            "Manifest-Version: 1.0\n",
+            &[("a/Both.class", b"jar"), ("a/Jar.class", b"jar")],
+        );
+
+        let mut class_path = ClassPath::new();
+        class_path.push_dir(dir.join("classes"));
+        class_path.push_jar(dir.join("a.jar")).unwrap();
+        class_path.insert_bytes("a/Both", b"memory".to_vec());
+        class_path.insert_bytes("a/Memory", b"memory".to_vec());
+        assert_eq!(
+            find(&mut class_path, "a/Both").as_deref(),
+            Some(&b"dir"[..])
+        );
+        assert_eq!(find(&mut class_path, "a/Jar").as_deref(), Some(&b"jar"[..]));
+        assert_eq!(
+            find(&mut class_path, "a/Memory").as_deref(),
+            Some(&b"memory"[..])
+        );
+        assert_eq!(find(&mut class_path, "a/Missing"), None);
+
+        let mut class_path = ClassPath::new();
+        class_path.insert_bytes("a/Both", b"memory".to_vec());
+        class_path.push_jar(dir.join("a.jar")).unwrap();
+        class_path.push_dir(dir.join("classes"));
+        assert_eq!(
+            find(&mut class_path, "a/Both").as_deref(),
+            Some(&b"memory"[..])
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn class_path_cycles_are_followed_once() {
+        let dir = temp_dir("cycle");
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+        write_jar(
+            &dir.join("a.jar"),
+            "Class-Path: lib/b.jar\n",
+            &[("A.class", b"a")],
+        );
+        write_jar(
+            &dir.join("lib/b.jar"),
+            "Class-Path: ../a.jar b.jar missing.jar\n",
+            &[("B.class", b"b")],
+        );
+
+        let mut class_path = ClassPath::new();
+        class_path.push_jar(dir.join("a.jar")).unwrap();
+        assert_eq!(class_path.sources().len(), 2);
+        assert_eq!(find(&mut class_path, "B").as_deref(), Some(&b"b"[..]));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_names_outside_the_source() {
+        let dir = temp_dir("escape");
+        std::fs::create_dir_all(dir.join("classes")).unwrap();
+        std::fs::write(dir.join("Secret.class"), b"secret").unwrap();
+
+        let mut class_path = ClassPath::new();
+        class_path.push_dir(dir.join("classes"));
+        for name in [
+            "../Secret",
+            "a/../../Secret",
+            "/Secret",
+            "a//b",
+            "java.lang.Object",
+        ] {
+            assert_eq!(find(&mut class_path, name), None, "{}", name);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,263 +1,3969 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
-use super::class::{
-    attribute::{self, AttributeInfo},
-    method::MethodEntry,
-    Class,
+use super::{
+    class::{
+        attribute::AttributeInfo,
+        constant::{ConstantPoolEntry, ReferenceKind},
+        descriptor::{BaseType, FieldType, MethodDescriptor},
+        field::AccessFlags as FieldAccessFlags,
+        instruction::Instruction,
+        method::AccessFlags as MethodAccessFlags,
+        AccessFlags as ClassAccessFlags, Class,
+    },
+    class_path::{ClassPath, ClassPathError},
+    native::{NativeArgs, NativeRegistry},
 };
 
-#[derive(Debug, Clone)]
+pub use super::class::instruction::jvm_opcodes;
+
+/// A java value. Locals, the operand stack and fields only ever hold the
+/// computational types (JVMS 2.11.1), boolean, byte, char and short travel
+/// as `Int` and only take their own variant where the interpreter hands
+/// values to or takes them from the host
+#[derive(Debug, Clone, PartialEq)]
 pub enum JRTVar {
+    /// an unset local, the second slot of a long or double, or the result
+    /// of a void method
     Void,
-    // Char(char),
-    // Byte(i8),
-    // Short(i16),
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
     Int(i32),
     Long(i64),
     Float(f32),
     Double(f64),
-    // String(String),
     Object(JRTObject),
+    Null,
+    /// pushed by `jsr`, only `astore` and `ret` accept it
+    ReturnAddress(usize),
 }
 
-#[derive(Debug, Clone)]
+impl JRTVar {
+    /// Local variable and operand stack slots the value takes
+    pub fn slots(&self) -> usize {
+        match self {
+            Self::Long(_) | Self::Double(_) => 2,
+            Self::Void => 0,
+            _ => 1,
+        }
+    }
+
+    /// Long and double are category 2, everything else category 1 (JVMS
+    /// 2.11.1), this decides how `pop2`, `dup2` and friends treat a value
+    pub fn category(&self) -> usize {
+        match self {
+            Self::Long(_) | Self::Double(_) => 2,
+            _ => 1,
+        }
+    }
+
+    /// Widens boolean, byte, char and short to the `Int` the operand stack
+    /// holds them as
+    pub fn to_computational(self) -> Self {
+        match self {
+            Self::Boolean(v) => Self::Int(v as i32),
+            Self::Byte(v) => Self::Int(v as i32),
+            Self::Char(v) => Self::Int(v as i32),
+            Self::Short(v) => Self::Int(v as i32),
+            v => v,
+        }
+    }
+
+    /// An int narrowed to `ty` the way storing it into a field or array of
+    /// that type would, booleans keep the low bit
+    pub fn from_int(ty: BaseType, v: i32) -> Self {
+        match ty {
+            BaseType::Boolean => Self::Boolean(v & 1 != 0),
+            BaseType::Byte => Self::Byte(v as i8),
+            BaseType::Char => Self::Char(v as u16),
+            BaseType::Short => Self::Short(v as i16),
+            _ => Self::Int(v),
+        }
+    }
+
+    /// Converts to a value of type `ty` in its computational form, ints
+    /// are narrowed to the type's range. Fails when the value can't be of
+    /// that type, references aren't checked against their class
+    pub fn convert_to(self, ty: &FieldType) -> Result<Self, JRTError> {
+        match (ty, self) {
+            (FieldType::Base(BaseType::Long), v @ Self::Long(_))
+            | (FieldType::Base(BaseType::Float), v @ Self::Float(_))
+            | (FieldType::Base(BaseType::Double), v @ Self::Double(_))
+            | (FieldType::Object(_) | FieldType::Array(_), v @ (Self::Object(_) | Self::Null)) => {
+                Ok(v)
+            }
+            (FieldType::Base(BaseType::Long | BaseType::Float | BaseType::Double), _) => {
+                Err(JRTError::InvalidOperand)
+            }
+            (FieldType::Base(base), v) => match v.to_computational() {
+                Self::Int(v) => Ok(Self::from_int(*base, v).to_computational()),
+                _ => Err(JRTError::InvalidOperand),
+            },
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    /// The value a field or array element of type `ty` starts out with
+    pub fn default_for(ty: &FieldType) -> Self {
+        match ty {
+            FieldType::Base(BaseType::Long) => Self::Long(0),
+            FieldType::Base(BaseType::Float) => Self::Float(0.0),
+            FieldType::Base(BaseType::Double) => Self::Double(0.0),
+            FieldType::Base(_) => Self::Int(0),
+            FieldType::Object(_) | FieldType::Array(_) => Self::Null,
+        }
+    }
+
+    /// `None` for null, fails if the value isn't a reference
+    pub fn as_reference(&self) -> Result<Option<JRTObject>, JRTError> {
+        match self {
+            Self::Object(object) => Ok(Some(*object)),
+            Self::Null => Ok(None),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl From<bool> for JRTVar {
+    fn from(v: bool) -> Self {
+        Self::Boolean(v)
+    }
+}
+
+impl From<i8> for JRTVar {
+    fn from(v: i8) -> Self {
+        Self::Byte(v)
+    }
+}
+
+impl From<u16> for JRTVar {
+    fn from(v: u16) -> Self {
+        Self::Char(v)
+    }
+}
+
+impl From<i16> for JRTVar {
+    fn from(v: i16) -> Self {
+        Self::Short(v)
+    }
+}
+
+impl From<i32> for JRTVar {
+    fn from(v: i32) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<i64> for JRTVar {
+    fn from(v: i64) -> Self {
+        Self::Long(v)
+    }
+}
+
+impl From<f32> for JRTVar {
+    fn from(v: f32) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<f64> for JRTVar {
+    fn from(v: f64) -> Self {
+        Self::Double(v)
+    }
+}
+
+impl From<JRTObject> for JRTVar {
+    fn from(v: JRTObject) -> Self {
+        Self::Object(v)
+    }
+}
+
+/// `None` is null
+impl From<Option<JRTObject>> for JRTVar {
+    fn from(v: Option<JRTObject>) -> Self {
+        v.map_or(Self::Null, Self::Object)
+    }
+}
+
+/// The other way around, for host code taking values out of the
+/// interpreter. Ints also come out of the narrower int types
+impl TryFrom<JRTVar> for i32 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v.to_computational() {
+            JRTVar::Int(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for bool {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Boolean(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for i8 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Byte(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for u16 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Char(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for i16 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Short(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for i64 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Long(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for f32 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Float(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for f64 {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        match v {
+            JRTVar::Double(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+}
+
+impl TryFrom<JRTVar> for Option<JRTObject> {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        v.as_reference()
+    }
+}
+
+/// Null is a `NullPointerException`
+impl TryFrom<JRTVar> for JRTObject {
+    type Error = JRTError;
+
+    fn try_from(v: JRTVar) -> Result<Self, JRTError> {
+        v.as_reference()?.ok_or(JRTError::NullPointerException)
+    }
+}
+
+/// A reference to something living on the `Heap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct JRTObject {
-    //ya uhhhhhhhhh this ones a doozy :)
+    index: usize,
+}
+
+impl JRTObject {
+    /// What `System.identityHashCode` gives, stable for the object's
+    /// lifetime since the heap never moves objects
+    pub fn identity_hash(&self) -> i32 {
+        self.index as i32
+    }
 }
 
 #[derive(Debug)]
 pub enum JRTError {
-    FuckyWucky,
+    /// something that needs a frame ran with none on the stack
+    NoFrame,
     MethodNotFound,
+    FieldNotFound,
     MethodNotStatic,
     ClassNotFound,
+    /// a class whose initialization failed earlier
+    NoClassDefFound,
+    /// `<clinit>` completed with an exception
+    ExceptionInInitializerError,
+    ClassFormatError,
+    IncompatibleClassChange,
+    IllegalAccess,
+    /// `new` on an abstract class or interface
+    Instantiation,
+    /// the selected method has no code to run
+    AbstractMethod,
+    /// a native method with no implementation
+    UnsatisfiedLink,
+    /// a bootstrap method that can't be called or completed with an
+    /// exception
+    BootstrapMethodError,
+    InvalidInstruction,
+    /// an operand of the wrong type for the instruction
+    InvalidOperand,
+    /// integer division or remainder by zero
+    ArithmeticException,
+    /// the message says which index was out of bounds
+    ArrayIndexOutOfBoundsException(String),
+    NegativeArraySizeException,
+    /// storing into a reference array an object of the wrong type
+    ArrayStoreException,
+    NullPointerException,
+    /// the message names both types
+    ClassCastException(String),
+    StackOverflow,
+    OperandStackOverflow,
+    OperandStackUnderflow,
+    InvalidLocal,
+    /// a java exception no handler caught before reaching the frame the
+    /// interpreter was entered from
+    UncaughtException(Box<JavaException>),
+}
+
+impl JRTError {
+    /// The java class thrown in place of this error, `None` for failures
+    /// of the interpreter itself
+    pub fn java_class(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::MethodNotFound => "java/lang/NoSuchMethodError",
+            Self::FieldNotFound => "java/lang/NoSuchFieldError",
+            Self::ClassNotFound | Self::NoClassDefFound => "java/lang/NoClassDefFoundError",
+            Self::ExceptionInInitializerError => "java/lang/ExceptionInInitializerError",
+            Self::ClassFormatError => "java/lang/ClassFormatError",
+            Self::IncompatibleClassChange => "java/lang/IncompatibleClassChangeError",
+            Self::IllegalAccess => "java/lang/IllegalAccessError",
+            Self::Instantiation => "java/lang/InstantiationError",
+            Self::AbstractMethod => "java/lang/AbstractMethodError",
+            Self::UnsatisfiedLink => "java/lang/UnsatisfiedLinkError",
+            Self::BootstrapMethodError => "java/lang/BootstrapMethodError",
+            Self::ArithmeticException => "java/lang/ArithmeticException",
+            Self::ArrayIndexOutOfBoundsException(_) => "java/lang/ArrayIndexOutOfBoundsException",
+            Self::NegativeArraySizeException => "java/lang/NegativeArraySizeException",
+            Self::ArrayStoreException => "java/lang/ArrayStoreException",
+            Self::NullPointerException => "java/lang/NullPointerException",
+            Self::ClassCastException(_) => "java/lang/ClassCastException",
+            Self::StackOverflow => "java/lang/StackOverflowError",
+            _ => return None,
+        })
+    }
+
+    /// The detail message of the java exception, the same as HotSpot's
+    pub fn message(&self) -> Option<String> {
+        match self {
+            Self::ArithmeticException => Some("/ by zero".into()),
+            Self::ArrayIndexOutOfBoundsException(message) | Self::ClassCastException(message) => {
+                Some(message.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A thrown java exception and where it was thrown from
+#[derive(Debug, Clone)]
+pub struct JavaException {
+    pub object: JRTObject,
+    /// binary name of the exception's class
+    pub class_name: String,
+    /// innermost frame first
+    pub stack_trace: Vec<StackTraceElement>,
+}
+
+/// Formats like the `java` launcher reports an uncaught exception
+impl std::fmt::Display for JavaException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.class_name.replace('/', "."))?;
+        for element in &self.stack_trace {
+            write!(f, "\n\tat {}", element)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackTraceElement {
+    pub class_name: String,
+    pub method_name: String,
+    pub file_name: Option<String>,
+    pub line_number: Option<u16>,
+}
+
+/// `pkg.Class.method(File.java:12)` as `Throwable.printStackTrace` writes it
+impl std::fmt::Display for StackTraceElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}(",
+            self.class_name.replace('/', "."),
+            self.method_name
+        )?;
+        match (&self.file_name, self.line_number) {
+            (Some(file), Some(line)) => write!(f, "{}:{}", file, line)?,
+            (Some(file), None) => write!(f, "{}", file)?,
+            (None, _) => write!(f, "Unknown Source")?,
+        }
+        write!(f, ")")
+    }
 }
 
+/// Frames deeper than this throw StackOverflowError
+pub const MAX_FRAMES: usize = 1024;
+
+/// Frames past `MAX_FRAMES` kept for the interpreter to initialize the
+/// classes of the errors it throws, a StackOverflowError is created right
+/// at the limit
+const ERROR_HEADROOM: usize = 64;
+
+/// `Thread.NORM_PRIORITY`
+const THREAD_NORM_PRIORITY: i32 = 5;
+
+/// `threadStatus` of a started thread that isn't blocked or waiting, the
+/// JVMTI alive and runnable bits
+const THREAD_RUNNABLE: i32 = 0x0001 | 0x0004;
+
 #[derive(Debug, Default)]
 pub struct Interpreter {
+    class_path: ClassPath,
     class_list: Vec<Class>,
     class_map: HashMap<String, usize>,
     stack: Stack,
     heap: Heap,
-    code: Code,
+    /// the instance field slots of each class, see `field_layout`
+    field_layouts: HashMap<usize, Vec<FieldSlot>>,
+    /// values of static fields, keyed by their declaring class and field
+    statics: HashMap<FieldSlot, JRTVar>,
+    /// classes missing from here have not started initialization
+    init_states: HashMap<usize, InitState>,
+    /// where each throwable was first thrown
+    stack_traces: HashMap<JRTObject, Vec<StackTraceElement>>,
+    /// interned `java.lang.String`s keyed by their UTF-16 contents
+    strings: HashMap<Vec<u16>, JRTObject>,
+    /// `java.lang.Class` mirrors keyed by descriptor, `V` for void
+    mirrors: HashMap<String, JRTObject>,
+    /// `MethodType`, `MethodHandle` and dynamic constants are resolved
+    /// once per constant pool entry, keyed by class and index
+    resolved_constants: HashMap<(usize, u16), JRTVar>,
+    /// each method's code decoded on its first run, keyed by class and
+    /// method
+    decoded_code: HashMap<(usize, usize), Rc<DecodedCode>>,
+    natives: NativeRegistry,
+    /// see `current_thread`
+    main_thread: Option<JRTObject>,
+}
+
+/// A method's instructions with their lengths, indexed by the pc they start
+/// at. Decoding stops at the first invalid instruction
+type DecodedCode = [Option<(Instruction, usize)>];
+
+/// Where a class is in its initialization (JVMS 5.5), there is only one
+/// thread so a class in progress is being initialized further up the stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitState {
+    InProgress,
+    Initialized,
+    Erroneous,
+}
+
+/// A field, the index of its declaring class in `class_list` and of the
+/// field in that class's `field_info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FieldSlot {
+    pub class: usize,
+    pub field: usize,
 }
 
+/// The frame stack, the last frame is the one executing
 #[derive(Debug, Default)]
 pub struct Stack {
-    stack: Vec<JRTVar>,
+    frames: Vec<Frame>,
+    /// whether frames may go into `ERROR_HEADROOM`
+    headroom: bool,
 }
 
-#[derive(Debug, Default)]
-pub struct Code {
-    pc: usize,
-    method_class: usize,
-    raw: Vec<u8>,
+impl Stack {
+    pub fn push(&mut self, frame: Frame) -> Result<(), JRTError> {
+        let limit = if self.headroom {
+            MAX_FRAMES + ERROR_HEADROOM
+        } else {
+            MAX_FRAMES
+        };
+        if self.frames.len() >= limit {
+            return Err(JRTError::StackOverflow);
+        }
+        self.frames.push(frame);
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<Frame> {
+        self.frames.pop()
+    }
+
+    pub fn current(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut Frame> {
+        self.frames.last_mut()
+    }
+
+    pub fn truncate(&mut self, depth: usize) {
+        self.frames.truncate(depth);
+    }
+
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
 }
 
-#[derive(Debug, Default)]
-pub struct Heap {}
+/// The activation of one method (JVMS 2.6)
+#[derive(Debug)]
+pub struct Frame {
+    /// index into the interpreter's `class_list`
+    pub class: usize,
+    /// index into the class's `method_info`
+    pub method: usize,
+    pub pc: usize,
+    /// where the caller resumes once this frame returns
+    pub return_pc: usize,
+    locals: Vec<JRTVar>,
+    operands: Vec<JRTVar>,
+    /// slots currently used on the operand stack, long and double take two
+    operand_slots: usize,
+    max_stack: usize,
+}
 
-impl Interpreter {
-    pub fn new() -> Self {
+impl Frame {
+    pub fn new(class: usize, method: usize, max_locals: usize, max_stack: usize) -> Self {
         Self {
-            ..Default::default()
+            class,
+            method,
+            pc: 0,
+            return_pc: 0,
+            locals: vec![JRTVar::Void; max_locals],
+            operands: Vec::with_capacity(max_stack),
+            operand_slots: 0,
+            max_stack,
         }
     }
 
-    pub fn insert_class(&mut self, class: Class) {
-        self.class_list.push(class);
-        let index = self.class_list.len() - 1;
-        let class = self.class_list.get_mut(index).unwrap();
-        self.class_map.insert(
-            class
-                .constant_pool
-                .get_class_name(class.this_class)
-                .unwrap()
-                .into(),
-            index,
-        );
+    pub fn push(&mut self, var: JRTVar) -> Result<(), JRTError> {
+        let slots = var.slots();
+        if self.operand_slots + slots > self.max_stack {
+            return Err(JRTError::OperandStackOverflow);
+        }
+        self.operand_slots += slots;
+        self.operands.push(var);
+        Ok(())
     }
 
-    pub fn run_static_method(
-        &mut self,
-        class: &str,
-        method_name: &str,
-        arguments: &[JRTVar],
-    ) -> Result<JRTVar, JRTError> {
-        let class_iid = self.class_map.get(class).ok_or(JRTError::ClassNotFound)?;
-        let class = self.class_list.get(*class_iid).unwrap();
-        let method = class
-            .get_method_from_name(method_name)
-            .ok_or(JRTError::MethodNotFound)?;
-        for att in &method.attributes {
-            if let AttributeInfo::Code { code, .. } = &att.info {
-                //println!("{:02X?}", code);
-                self.code.pc = self.code.raw.len();
-                for b in code {
-                    self.code.raw.push(*b);
-                }
-                self.code.method_class = *class_iid;
-                self.run();
-                return Ok(JRTVar::Void);
-            }
+    pub fn pop(&mut self) -> Result<JRTVar, JRTError> {
+        let var = self.operands.pop().ok_or(JRTError::OperandStackUnderflow)?;
+        self.operand_slots -= var.slots();
+        Ok(var)
+    }
+
+    pub fn pop_int(&mut self) -> Result<i32, JRTError> {
+        match self.pop()? {
+            JRTVar::Int(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
         }
-        Result::Err(JRTError::FuckyWucky)
     }
 
-    pub fn load_method(&mut self) {}
+    pub fn pop_long(&mut self) -> Result<i64, JRTError> {
+        match self.pop()? {
+            JRTVar::Long(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
 
-    pub fn run(&mut self) -> Result<JRTVar, JRTError> {
-        use jvm_opcodes::*;
-        loop {
-            let op = self.code.raw[self.code.pc];
-            self.code.pc += 1;
-            match op {
-                NOP => {}
-                GETSTATIC => {
-                    let b1 = self.code.raw[self.code.pc];
-                    let b2 = self.code.raw[self.code.pc + 1];
-                    let index = b2 as u16 | ((b1 as u16) << 8);
-                    self.code.pc += 2;
-                    let c = self.class_list.get(self.code.method_class).unwrap();
-                    let co = c.constant_pool.get_constant(index).unwrap();
-                    todo!();
-                    //this should return a Fieldref so the following wont work
-                    match co {
-                        crate::jvm::class::constant::ConstantPoolEntry::String { string_index } => {
-                            todo!()
-                        }
-                        crate::jvm::class::constant::ConstantPoolEntry::Integer(i) => {
-                            self.stack.stack.push(JRTVar::Int(*i))
-                        }
-                        crate::jvm::class::constant::ConstantPoolEntry::Float(f) => {
-                            self.stack.stack.push(JRTVar::Float(*f))
-                        }
-                        crate::jvm::class::constant::ConstantPoolEntry::Long(l) => {
-                            self.stack.stack.push(JRTVar::Long(*l))
-                        }
-                        crate::jvm::class::constant::ConstantPoolEntry::Double(d) => {
-                            self.stack.stack.push(JRTVar::Double(*d))
-                        }
-                        // crate::jvm::class::constant::ConstantPoolEntry::Empty => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::Class { name_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::Fieldref { class_index, name_and_type_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::Methodref { class_index, name_and_type_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::InterfaceMethodref { class_index, name_and_type_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::NameAndType { name_index, descriptor_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::Utf8(_) => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::MethodHandle { reference_kind, reference_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::MethodType { descriptor_index } => todo!(),
-                        // crate::jvm::class::constant::ConstantPoolEntry::InvokeDynamic { bootstrap_method_attr_index, name_and_type_index } => todo!(),
-                        _ => {
-                            panic!()
-                        }
-                    }
-                }
-                LDC => {}
-                INVOKEVIRTUAL => {}
-                RETURN => return Ok(JRTVar::Void),
-                IRETURN => return Ok(JRTVar::Int(0)),
-                LRETURN => return Ok(JRTVar::Long(0)),
-                FRETURN => return Ok(JRTVar::Float(0.0)),
-                DRETURN => return Ok(JRTVar::Double(0.0)),
-                ARETURN => return Ok(JRTVar::Object(todo!())),
-
-                _ => {
-                    panic!();
-                }
+    pub fn pop_float(&mut self) -> Result<f32, JRTError> {
+        match self.pop()? {
+            JRTVar::Float(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    pub fn pop_double(&mut self) -> Result<f64, JRTError> {
+        match self.pop()? {
+            JRTVar::Double(v) => Ok(v),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    /// `None` for null
+    pub fn pop_reference(&mut self) -> Result<Option<JRTObject>, JRTError> {
+        self.pop()?.as_reference()
+    }
+
+    /// Pops a reference that is about to be dereferenced
+    pub fn pop_non_null(&mut self) -> Result<JRTObject, JRTError> {
+        self.pop_reference()?.ok_or(JRTError::NullPointerException)
+    }
+
+    /// Pops the values making up the top `slots` slots, bottom first. A
+    /// category 2 value can't be split, so a `long` on top counts as both
+    /// slots of `pop2` while two `int`s count as one each
+    fn pop_slots(&mut self, slots: usize) -> Result<Vec<JRTVar>, JRTError> {
+        let mut values = Vec::new();
+        let mut taken = 0;
+        while taken < slots {
+            let category = self.peek()?.category();
+            if taken + category > slots {
+                return Err(JRTError::InvalidOperand);
             }
+            taken += category;
+            values.push(self.pop()?);
+        }
+        values.reverse();
+        Ok(values)
+    }
+
+    fn push_all(&mut self, values: &[JRTVar]) -> Result<(), JRTError> {
+        for value in values {
+            self.push(value.clone())?;
+        }
+        Ok(())
+    }
+
+    pub fn peek(&self) -> Result<&JRTVar, JRTError> {
+        self.operands.last().ok_or(JRTError::OperandStackUnderflow)
+    }
+
+    pub fn clear_operands(&mut self) {
+        self.operands.clear();
+        self.operand_slots = 0;
+    }
+
+    pub fn operands(&self) -> &[JRTVar] {
+        &self.operands
+    }
+
+    pub fn get_local(&self, index: usize) -> Result<&JRTVar, JRTError> {
+        self.locals.get(index).ok_or(JRTError::InvalidLocal)
+    }
+
+    /// Longs and doubles take `index` and `index + 1`, the second slot is
+    /// left as `Void`. Writing over the second slot of a long or double
+    /// invalidates the whole pair
+    pub fn set_local(&mut self, index: usize, var: JRTVar) -> Result<(), JRTError> {
+        if index + var.slots() > self.locals.len() {
+            return Err(JRTError::InvalidLocal);
+        }
+        if index > 0 && self.locals[index - 1].category() == 2 {
+            self.locals[index - 1] = JRTVar::Void;
+        }
+        if var.slots() == 2 {
+            self.locals[index + 1] = JRTVar::Void;
+        }
+        self.locals[index] = var;
+        Ok(())
+    }
+
+    pub fn locals(&self) -> &[JRTVar] {
+        &self.locals
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Heap {
+    entries: Vec<HeapEntry>,
+}
+
+#[derive(Debug)]
+pub enum HeapEntry {
+    Object(JRTInstance),
+    Array(JRTArray),
+}
+
+/// An instance of a class
+#[derive(Debug)]
+pub struct JRTInstance {
+    /// index into the interpreter's `class_list`
+    pub class: usize,
+    /// laid out as in the class's `field_layout`
+    pub fields: Vec<JRTVar>,
+}
+
+#[derive(Debug, Clone)]
+pub enum JRTArray {
+    Boolean(Vec<i8>),
+    Byte(Vec<i8>),
+    Char(Vec<u16>),
+    Short(Vec<i16>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    Reference {
+        component: FieldType,
+        elements: Vec<JRTVar>,
+    },
+}
+
+impl Heap {
+    pub fn alloc(&mut self, entry: HeapEntry) -> JRTObject {
+        self.entries.push(entry);
+        JRTObject {
+            index: self.entries.len() - 1,
+        }
+    }
+
+    pub fn new_object(&mut self, class: usize, fields: Vec<JRTVar>) -> JRTObject {
+        self.alloc(HeapEntry::Object(JRTInstance { class, fields }))
+    }
+
+    pub fn new_array(&mut self, array: JRTArray) -> JRTObject {
+        self.alloc(HeapEntry::Array(array))
+    }
+
+    pub fn get(&self, object: JRTObject) -> Option<&HeapEntry> {
+        self.entries.get(object.index)
+    }
+
+    pub fn get_mut(&mut self, object: JRTObject) -> Option<&mut HeapEntry> {
+        self.entries.get_mut(object.index)
+    }
+
+    pub fn instance(&self, object: JRTObject) -> Result<&JRTInstance, JRTError> {
+        match self.get(object) {
+            Some(HeapEntry::Object(instance)) => Ok(instance),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    pub fn instance_mut(&mut self, object: JRTObject) -> Result<&mut JRTInstance, JRTError> {
+        match self.get_mut(object) {
+            Some(HeapEntry::Object(instance)) => Ok(instance),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    pub fn array(&self, object: JRTObject) -> Result<&JRTArray, JRTError> {
+        match self.get(object) {
+            Some(HeapEntry::Array(array)) => Ok(array),
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    pub fn array_mut(&mut self, object: JRTObject) -> Result<&mut JRTArray, JRTError> {
+        match self.get_mut(object) {
+            Some(HeapEntry::Array(array)) => Ok(array),
+            _ => Err(JRTError::InvalidOperand),
         }
     }
 }
-pub mod jvm_opcodes {
-
-    // Constants
-    pub const NOP: u8 = 0x00;
-    pub const ACONST_NULL: u8 = 0x01;
-    pub const ICONST_M1: u8 = 0x02;
-    pub const ICONST_0: u8 = 0x03;
-    pub const ICONST_1: u8 = 0x04;
-    pub const ICONST_2: u8 = 0x05;
-    pub const ICONST_3: u8 = 0x06;
-    pub const ICONST_4: u8 = 0x07;
-    pub const ICONST_5: u8 = 0x08;
-    pub const LCONST_0: u8 = 0x09;
-    pub const LCONST_1: u8 = 0x0a;
-    pub const FCONST_0: u8 = 0x0b;
-    pub const FCONST_1: u8 = 0x0c;
-    pub const FCONST_2: u8 = 0x0d;
-    pub const DCONST_0: u8 = 0x0e;
-    pub const DCONST_1: u8 = 0x0f;
-    pub const BIPUSH: u8 = 0x10;
-    pub const SIPUSH: u8 = 0x11;
-    pub const LDC: u8 = 0x12;
-    pub const LDC_W: u8 = 0x13;
-    pub const LDC2_W: u8 = 0x14;
-
-    //Loads
-
-    //Stores
-
-    //Stack
-    pub const POP: u8 = 0x57;
-    pub const POP2: u8 = 0x58;
-    pub const DUP: u8 = 0x59;
-    pub const DUP_X1: u8 = 0x5a;
-    pub const DUP_X2: u8 = 0x5b;
-    pub const DUP2: u8 = 0x5c;
-    pub const DUP2_X1: u8 = 0x5d;
-    pub const DUP2_X2: u8 = 0x5e;
-    pub const SWAP: u8 = 0x5f;
-
-    //Math
-
-    //Conversions
-
-    //Comparison
-
-    //References
-    pub const GETSTATIC: u8 = 0xb2;
-    pub const PUTSTATIC: u8 = 0xb3;
-    pub const GETFIELD: u8 = 0xb4;
-    pub const PUTFIELD: u8 = 0xb5;
-    pub const INVOKEVIRTUAL: u8 = 0xb6;
-    pub const INVOLESPECIAL: u8 = 0xb7;
-    pub const INVOKESTATIC: u8 = 0xb8;
-    pub const INVOKEINTERFACE: u8 = 0xb9;
-    pub const INVOKEDDYNAMIC: u8 = 0xba;
-    pub const NEW: u8 = 0xbb;
-    pub const NEWARRAY: u8 = 0xbc;
-    pub const ANEWARRAY: u8 = 0xbd;
-    pub const ARRAYLENGTH: u8 = 0xbe;
-    pub const ATHROW: u8 = 0xbf;
-    pub const CHECKCAST: u8 = 0xc0;
-    pub const INSTANCEOF: u8 = 0xc1;
-    pub const MONITORENTER: u8 = 0xc2;
-    pub const MONITOREXIT: u8 = 0xc3;
-
-    //Control
-    pub const GOTO: u8 = 0xa7;
-    pub const JSR: u8 = 0xa8;
-    pub const RET: u8 = 0xa9;
-    pub const TABLESWITCH: u8 = 0xaa;
-    pub const LOOKUPSWITCH: u8 = 0xab;
-    pub const IRETURN: u8 = 0xac;
-    pub const LRETURN: u8 = 0xad;
-    pub const FRETURN: u8 = 0xae;
-    pub const DRETURN: u8 = 0xaf;
-    pub const ARETURN: u8 = 0xb0;
-    pub const RETURN: u8 = 0xb1;
-
-    //Extended
-    pub const WIDE: u8 = 0xc4;
-    pub const MULTIANEWARRAY: u8 = 0xc5;
-    pub const IFNULL: u8 = 0xc6;
-    pub const IFNONNULL: u8 = 0xc7;
-    pub const GOTO_W: u8 = 0xc8;
-    pub const JSR_W: u8 = 0xc9;
-
-    //Reserved
-    pub const BREAKPOINT: u8 = 0xca;
-    pub const IMPDEP1: u8 = 0xfe;
-    pub const IMPDEP2: u8 = 0xff;
+
+impl JRTArray {
+    /// An array of `len` elements of `component` at their default value
+    pub fn new(component: &FieldType, len: usize) -> Self {
+        match component {
+            FieldType::Base(BaseType::Boolean) => Self::Boolean(vec![0; len]),
+            FieldType::Base(BaseType::Byte) => Self::Byte(vec![0; len]),
+            FieldType::Base(BaseType::Char) => Self::Char(vec![0; len]),
+            FieldType::Base(BaseType::Short) => Self::Short(vec![0; len]),
+            FieldType::Base(BaseType::Int) => Self::Int(vec![0; len]),
+            FieldType::Base(BaseType::Long) => Self::Long(vec![0; len]),
+            FieldType::Base(BaseType::Float) => Self::Float(vec![0.0; len]),
+            FieldType::Base(BaseType::Double) => Self::Double(vec![0.0; len]),
+            FieldType::Object(_) | FieldType::Array(_) => Self::Reference {
+                component: component.clone(),
+                elements: vec![JRTVar::Null; len],
+            },
+        }
+    }
+
+    pub fn component_type(&self) -> FieldType {
+        match self {
+            Self::Boolean(_) => FieldType::Base(BaseType::Boolean),
+            Self::Byte(_) => FieldType::Base(BaseType::Byte),
+            Self::Char(_) => FieldType::Base(BaseType::Char),
+            Self::Short(_) => FieldType::Base(BaseType::Short),
+            Self::Int(_) => FieldType::Base(BaseType::Int),
+            Self::Long(_) => FieldType::Base(BaseType::Long),
+            Self::Float(_) => FieldType::Base(BaseType::Float),
+            Self::Double(_) => FieldType::Base(BaseType::Double),
+            Self::Reference { component, .. } => component.clone(),
+        }
+    }
+
+    /// The array class, `[I` or `[Ljava/lang/String;` in descriptor form
+    pub fn array_type(&self) -> FieldType {
+        FieldType::Array(Box::new(self.component_type()))
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Boolean(a) | Self::Byte(a) => a.len(),
+            Self::Char(a) => a.len(),
+            Self::Short(a) => a.len(),
+            Self::Int(a) => a.len(),
+            Self::Long(a) => a.len(),
+            Self::Float(a) => a.len(),
+            Self::Double(a) => a.len(),
+            Self::Reference { elements, .. } => elements.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A copy of `len` elements starting at `start`, which must be in
+    /// bounds
+    pub fn slice(&self, start: usize, len: usize) -> Self {
+        let range = start..start + len;
+        match self {
+            Self::Boolean(a) => Self::Boolean(a[range].to_vec()),
+            Self::Byte(a) => Self::Byte(a[range].to_vec()),
+            Self::Char(a) => Self::Char(a[range].to_vec()),
+            Self::Short(a) => Self::Short(a[range].to_vec()),
+            Self::Int(a) => Self::Int(a[range].to_vec()),
+            Self::Long(a) => Self::Long(a[range].to_vec()),
+            Self::Float(a) => Self::Float(a[range].to_vec()),
+            Self::Double(a) => Self::Double(a[range].to_vec()),
+            Self::Reference {
+                component,
+                elements,
+            } => Self::Reference {
+                component: component.clone(),
+                elements: elements[range].to_vec(),
+            },
+        }
+    }
+
+    /// Overwrites the elements from `start` on with those of `src`, the
+    /// arrays must have the same kind of elements
+    pub fn copy_from(&mut self, start: usize, src: &Self) -> Result<(), JRTError> {
+        if start + src.len() > self.len() {
+            return Err(JRTError::ArrayIndexOutOfBoundsException(format!(
+                "Range [{}, {}) out of bounds for length {}",
+                start,
+                start + src.len(),
+                self.len()
+            )));
+        }
+        let range = start..start + src.len();
+        match (self, src) {
+            (Self::Boolean(a), Self::Boolean(b)) | (Self::Byte(a), Self::Byte(b)) => {
+                a[range].copy_from_slice(b)
+            }
+            (Self::Char(a), Self::Char(b)) => a[range].copy_from_slice(b),
+            (Self::Short(a), Self::Short(b)) => a[range].copy_from_slice(b),
+            (Self::Int(a), Self::Int(b)) => a[range].copy_from_slice(b),
+            (Self::Long(a), Self::Long(b)) => a[range].copy_from_slice(b),
+            (Self::Float(a), Self::Float(b)) => a[range].copy_from_slice(b),
+            (Self::Double(a), Self::Double(b)) => a[range].copy_from_slice(b),
+            (Self::Reference { elements: a, .. }, Self::Reference { elements: b, .. }) => {
+                a[range].clone_from_slice(b)
+            }
+            _ => return Err(JRTError::ArrayStoreException),
+        }
+        Ok(())
+    }
+
+    fn check_index(&self, index: i32) -> Result<usize, JRTError> {
+        if index < 0 || index as usize >= self.len() {
+            return Err(JRTError::ArrayIndexOutOfBoundsException(format!(
+                "Index {} out of bounds for length {}",
+                index,
+                self.len()
+            )));
+        }
+        Ok(index as usize)
+    }
+
+    /// Reads an element for one of the `*aload` instructions, byte, char
+    /// and short elements are widened to int
+    fn load(&self, ins: &Instruction, index: i32) -> Result<JRTVar, JRTError> {
+        let i = self.check_index(index)?;
+        Ok(match (ins, self) {
+            (Instruction::Iaload, Self::Int(a)) => JRTVar::Int(a[i]),
+            (Instruction::Laload, Self::Long(a)) => JRTVar::Long(a[i]),
+            (Instruction::Faload, Self::Float(a)) => JRTVar::Float(a[i]),
+            (Instruction::Daload, Self::Double(a)) => JRTVar::Double(a[i]),
+            (Instruction::Aaload, Self::Reference { elements, .. }) => elements[i].clone(),
+            (Instruction::Baload, Self::Byte(a) | Self::Boolean(a)) => JRTVar::Int(a[i] as i32),
+            (Instruction::Caload, Self::Char(a)) => JRTVar::Int(a[i] as i32),
+            (Instruction::Saload, Self::Short(a)) => JRTVar::Int(a[i] as i32),
+            _ => return Err(JRTError::InvalidOperand),
+        })
+    }
+
+    /// Writes an element for one of the `*astore` instructions, ints are
+    /// narrowed to the element type
+    fn store(&mut self, ins: &Instruction, index: i32, value: JRTVar) -> Result<(), JRTError> {
+        let i = self.check_index(index)?;
+        match (ins, self, value) {
+            (Instruction::Iastore, Self::Int(a), JRTVar::Int(v)) => a[i] = v,
+            (Instruction::Lastore, Self::Long(a), JRTVar::Long(v)) => a[i] = v,
+            (Instruction::Fastore, Self::Float(a), JRTVar::Float(v)) => a[i] = v,
+            (Instruction::Dastore, Self::Double(a), JRTVar::Double(v)) => a[i] = v,
+            (
+                Instruction::Aastore,
+                Self::Reference { elements, .. },
+                v @ (JRTVar::Object(_) | JRTVar::Null),
+            ) => elements[i] = v,
+            (Instruction::Bastore, Self::Byte(a), JRTVar::Int(v)) => a[i] = v as i8,
+            // boolean arrays only keep the low bit (JVMS bastore)
+            (Instruction::Bastore, Self::Boolean(a), JRTVar::Int(v)) => a[i] = (v & 1) as i8,
+            (Instruction::Castore, Self::Char(a), JRTVar::Int(v)) => a[i] = v as u16,
+            (Instruction::Sastore, Self::Short(a), JRTVar::Int(v)) => a[i] = v as i16,
+            _ => return Err(JRTError::InvalidOperand),
+        }
+        Ok(())
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+
+    pub fn with_class_path(class_path: ClassPath) -> Self {
+        Self {
+            class_path,
+            ..Default::default()
+        }
+    }
+
+    pub fn heap(&self) -> &Heap {
+        &self.heap
+    }
+
+    pub fn heap_mut(&mut self) -> &mut Heap {
+        &mut self.heap
+    }
+
+    pub fn class_path_mut(&mut self) -> &mut ClassPath {
+        &mut self.class_path
+    }
+
+    pub fn natives_mut(&mut self) -> &mut NativeRegistry {
+        &mut self.natives
+    }
+
+    /// Implements the native method `class.name descriptor` in Rust, see
+    /// `NativeRegistry::register`
+    pub fn register_native(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+        method: impl Fn(&mut Interpreter, &NativeArgs) -> Result<JRTVar, JRTError> + 'static,
+    ) {
+        self.natives.register(class, name, descriptor, method);
+    }
+
+    pub fn insert_class(&mut self, class: Class) -> Result<(), JRTError> {
+        self.insert_class_index(class).map(|_| ())
+    }
+
+    fn insert_class_index(&mut self, class: Class) -> Result<usize, JRTError> {
+        let name: String = class.name().ok_or(JRTError::ClassNotFound)?.into();
+        self.check_sealed(&class, &name)?;
+
+        self.class_list.push(class);
+        let index = self.class_list.len() - 1;
+        self.class_map.insert(name, index);
+        Ok(index)
+    }
+
+    /// Returns the index of `name` in `class_list`, loading it from the
+    /// class path the first time it is asked for
+    pub fn load_class(&mut self, name: &str) -> Result<usize, JRTError> {
+        if let Some(index) = self.class_map.get(name) {
+            return Ok(*index);
+        }
+        let class = match self.class_path.load_class(name) {
+            Ok(Some(class)) => class,
+            Ok(None) | Err(ClassPathError::Io(_)) | Err(ClassPathError::Jar(_)) => {
+                return Err(JRTError::ClassNotFound)
+            }
+            Err(ClassPathError::Class(_)) => return Err(JRTError::ClassFormatError),
+        };
+        // a file at the right path holding some other class (JVMS 5.3.1)
+        if class.name() != Some(name) {
+            return Err(JRTError::ClassNotFound);
+        }
+        self.insert_class_index(class)
+    }
+
+    /// Resolves the `CONSTANT_Class` at `cp_index` in the constant pool of
+    /// the class at `class_index`, loading the named class if needed
+    pub fn resolve_class(&mut self, class_index: usize, cp_index: u16) -> Result<usize, JRTError> {
+        let name = self
+            .class_list
+            .get(class_index)
+            .and_then(|c| c.constant_pool.get_class_name(cp_index))
+            .ok_or(JRTError::IncompatibleClassChange)?
+            .to_owned();
+        self.load_class(&name)
+    }
+
+    /// The superclass of the class at `class_index`, loaded on demand,
+    /// `None` for `java/lang/Object`
+    pub fn load_super_class(&mut self, class_index: usize) -> Result<Option<usize>, JRTError> {
+        let class = self
+            .class_list
+            .get(class_index)
+            .ok_or(JRTError::ClassNotFound)?;
+        if class.super_class == 0 {
+            return Ok(None);
+        }
+        let super_class = class.super_class;
+        self.resolve_class(class_index, super_class).map(Some)
+    }
+
+    pub fn get_class(&self, name: &str) -> Option<&Class> {
+        self.class_list.get(*self.class_map.get(name)?)
+    }
+
+    /// Sealed classes only allow the subclasses listed in their
+    /// `PermittedSubclasses`, check both directions since classes can be
+    /// inserted in any order
+    fn check_sealed(&self, class: &Class, name: &str) -> Result<(), JRTError> {
+        let supers = class
+            .super_class_name()
+            .into_iter()
+            .chain(class.interface_names());
+        for super_name in supers {
+            if let Some(super_class) = self.get_class(super_name) {
+                if !super_class.permits_subclass(name) {
+                    return Err(JRTError::IncompatibleClassChange);
+                }
+            }
+        }
+        if class.is_sealed() {
+            for other in &self.class_list {
+                let mut supers = other
+                    .super_class_name()
+                    .into_iter()
+                    .chain(other.interface_names());
+                if supers.any(|s| s == name) && !class.permits_subclass(other.name().unwrap_or(""))
+                {
+                    return Err(JRTError::IncompatibleClassChange);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Private members are accessible from the declaring class and from
+    /// its nestmates (JVMS 5.4.4), both classes must agree on the nest host
+    /// and the host must list the other as a member
+    pub fn can_access_private(&self, accessor: &str, declaring: &str) -> bool {
+        if accessor == declaring {
+            return true;
+        }
+        let (Some(accessor), Some(declaring)) =
+            (self.get_class(accessor), self.get_class(declaring))
+        else {
+            return false;
+        };
+        let (Some(host_a), Some(host_d)) = (accessor.nest_host_name(), declaring.nest_host_name())
+        else {
+            return false;
+        };
+        if host_a != host_d {
+            return false;
+        }
+        let Some(host) = self.get_class(host_a) else {
+            return false;
+        };
+        let members = host.nest_member_names();
+        [accessor, declaring].iter().all(|c| {
+            let name = c.name().unwrap_or("");
+            name == host_a || members.contains(&name)
+        })
+    }
+
+    /// Whether `accessor` may use a member of `declaring` with the access
+    /// `flags` (JVMS 5.4.4). Fields and methods keep their visibility in
+    /// the same bits, so either kind of flags works
+    fn can_access_member(
+        &mut self,
+        accessor: usize,
+        declaring: usize,
+        flags: u16,
+    ) -> Result<bool, JRTError> {
+        let flags = MethodAccessFlags::from_bits(flags);
+        if flags.get(MethodAccessFlags::PUBLIC) || accessor == declaring {
+            return Ok(true);
+        }
+        let accessor_name = self.class_list[accessor].name().unwrap_or("");
+        let declaring_name = self.class_list[declaring].name().unwrap_or("");
+        if flags.get(MethodAccessFlags::PRIVATE) {
+            return Ok(self.can_access_private(accessor_name, declaring_name));
+        }
+        // there is a single class loader, so the runtime package is the
+        // package name
+        let accessor_package = accessor_name.rsplit_once('/').map(|(p, _)| p);
+        let declaring_package = declaring_name.rsplit_once('/').map(|(p, _)| p);
+        if accessor_package == declaring_package {
+            return Ok(true);
+        }
+        Ok(flags.get(MethodAccessFlags::PROTECTED) && self.is_subclass_of(accessor, declaring)?)
+    }
+
+    fn is_interface(&self, class: usize) -> bool {
+        self.class_list[class]
+            .access_flags
+            .get(ClassAccessFlags::INTERFACE)
+    }
+
+    fn method_flags(&self, class: usize, method: usize) -> MethodAccessFlags {
+        self.class_list[class].method_info[method].access_flags
+    }
+
+    fn method_name_and_type(&self, class: usize, method: usize) -> (String, String) {
+        let class = &self.class_list[class];
+        let method = &class.method_info[method];
+        (
+            class
+                .constant_pool
+                .get_const_utd8_or_invalid(method.name_index)
+                .into(),
+            class
+                .constant_pool
+                .get_const_utd8_or_invalid(method.descriptor_index)
+                .into(),
+        )
+    }
+
+    /// The direct superinterfaces of `class`, loaded on demand
+    fn load_interfaces(&mut self, class: usize) -> Result<Vec<usize>, JRTError> {
+        let names: Vec<String> = self.class_list[class]
+            .interface_names()
+            .map(Into::into)
+            .collect();
+        names.iter().map(|n| self.load_class(n)).collect()
+    }
+
+    /// Every interface `class` implements, directly or through its
+    /// superclasses and superinterfaces
+    fn all_interfaces(&mut self, class: usize) -> Result<Vec<usize>, JRTError> {
+        let mut interfaces = Vec::new();
+        let mut pending = vec![class];
+        while let Some(current) = pending.pop() {
+            if !self.is_interface(current) {
+                pending.extend(self.load_super_class(current)?);
+            }
+            for interface in self.load_interfaces(current)? {
+                if !interfaces.contains(&interface) {
+                    interfaces.push(interface);
+                    pending.push(interface);
+                }
+            }
+        }
+        Ok(interfaces)
+    }
+
+    /// Whether `class` is `other`, a subclass of it or implements it
+    pub fn is_subclass_of(&mut self, class: usize, other: usize) -> Result<bool, JRTError> {
+        let mut current = Some(class);
+        while let Some(c) = current {
+            if c == other {
+                return Ok(true);
+            }
+            current = self.load_super_class(c)?;
+        }
+        Ok(self.is_interface(other) && self.all_interfaces(class)?.contains(&other))
+    }
+
+    /// Looks for the method in `class` and then its superclasses
+    fn find_method_in_supers(
+        &mut self,
+        class: usize,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Option<(usize, usize)>, JRTError> {
+        let mut current = Some(class);
+        while let Some(c) = current {
+            if let Some(method) = self.class_list[c].method_entry_index_from_name(name, descriptor)
+            {
+                return Ok(Some((c, method)));
+            }
+            current = self.load_super_class(c)?;
+        }
+        Ok(None)
+    }
+
+    /// The maximally-specific superinterface methods of `class` (JVMS
+    /// 5.4.3.3), the non private, non static interface methods no other
+    /// candidate's interface overrides
+    fn maximally_specific_methods(
+        &mut self,
+        class: usize,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Vec<(usize, usize)>, JRTError> {
+        let mut candidates = Vec::new();
+        for interface in self.all_interfaces(class)? {
+            let Some(method) =
+                self.class_list[interface].method_entry_index_from_name(name, descriptor)
+            else {
+                continue;
+            };
+            let flags = self.method_flags(interface, method);
+            if !flags.get(MethodAccessFlags::PRIVATE) && !flags.get(MethodAccessFlags::STATIC) {
+                candidates.push((interface, method));
+            }
+        }
+        let mut methods = Vec::new();
+        for &(interface, method) in &candidates {
+            let mut overridden = false;
+            for &(other, _) in &candidates {
+                if other != interface && self.is_subclass_of(other, interface)? {
+                    overridden = true;
+                    break;
+                }
+            }
+            if !overridden {
+                methods.push((interface, method));
+            }
+        }
+        Ok(methods)
+    }
+
+    /// Picks the single non abstract method out of the maximally-specific
+    /// ones, `None` when there is no such method
+    fn default_method(
+        &mut self,
+        class: usize,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Option<(usize, usize)>, JRTError> {
+        let methods = self.maximally_specific_methods(class, name, descriptor)?;
+        let mut concrete = methods
+            .into_iter()
+            .filter(|&(c, m)| !self.method_flags(c, m).get(MethodAccessFlags::ABSTRACT));
+        match (concrete.next(), concrete.next()) {
+            (Some(method), None) => Ok(Some(method)),
+            (Some(_), Some(_)) => Err(JRTError::IncompatibleClassChange),
+            _ => Ok(None),
+        }
+    }
+
+    /// Resolves the `Methodref` or `InterfaceMethodref` at `cp_index` in
+    /// the constant pool of `class_index` to a declaring class and method
+    /// index (JVMS 5.4.3.3, 5.4.3.4)
+    pub fn resolve_method(
+        &mut self,
+        class_index: usize,
+        cp_index: u16,
+    ) -> Result<(usize, usize), JRTError> {
+        let constants = &self
+            .class_list
+            .get(class_index)
+            .ok_or(JRTError::ClassNotFound)?
+            .constant_pool;
+        let interface_ref = match constants.get_constant(cp_index) {
+            Some(ConstantPoolEntry::Methodref { .. }) => false,
+            Some(ConstantPoolEntry::InterfaceMethodref { .. }) => true,
+            _ => return Err(JRTError::IncompatibleClassChange),
+        };
+        let (class_name, name, descriptor) = constants
+            .get_member_ref(cp_index)
+            .ok_or(JRTError::IncompatibleClassChange)?;
+        let (class_name, name, descriptor) = (
+            class_name.to_owned(),
+            name.to_owned(),
+            descriptor.to_owned(),
+        );
+
+        let class = self.load_class(&class_name)?;
+        if self.is_interface(class) != interface_ref {
+            return Err(JRTError::IncompatibleClassChange);
+        }
+        let found = if interface_ref {
+            // the interface itself, then the public instance methods of Object
+            match self.class_list[class].method_entry_index_from_name(&name, &descriptor) {
+                Some(method) => Some((class, method)),
+                None => {
+                    let object = self.load_class("java/lang/Object")?;
+                    self.class_list[object]
+                        .method_entry_index_from_name(&name, &descriptor)
+                        .filter(|m| {
+                            let flags = self.method_flags(object, *m);
+                            flags.get(MethodAccessFlags::PUBLIC)
+                                && !flags.get(MethodAccessFlags::STATIC)
+                        })
+                        .map(|m| (object, m))
+                }
+            }
+        } else {
+            self.find_method_in_supers(class, &name, &descriptor)?
+        };
+        let found = match found {
+            Some(found) => found,
+            None => match self.default_method(class, &name, &descriptor)? {
+                Some(found) => found,
+                None => self
+                    .maximally_specific_methods(class, &name, &descriptor)?
+                    .into_iter()
+                    .next()
+                    .ok_or(JRTError::MethodNotFound)?,
+            },
+        };
+        let flags = self.method_flags(found.0, found.1).bits();
+        if !self.can_access_member(class_index, found.0, flags)? {
+            return Err(JRTError::IllegalAccess);
+        }
+        Ok(found)
+    }
+
+    /// Selects the method `invokevirtual` and `invokeinterface` run for a
+    /// receiver of `runtime_class` (JVMS 5.4.6)
+    pub fn select_method(
+        &mut self,
+        runtime_class: usize,
+        resolved: (usize, usize),
+    ) -> Result<(usize, usize), JRTError> {
+        if self
+            .method_flags(resolved.0, resolved.1)
+            .get(MethodAccessFlags::PRIVATE)
+        {
+            return Ok(resolved);
+        }
+        let (name, descriptor) = self.method_name_and_type(resolved.0, resolved.1);
+        let mut current = Some(runtime_class);
+        while let Some(c) = current {
+            if let Some(method) =
+                self.class_list[c].method_entry_index_from_name(&name, &descriptor)
+            {
+                let flags = self.method_flags(c, method);
+                if !flags.get(MethodAccessFlags::STATIC) && !flags.get(MethodAccessFlags::PRIVATE) {
+                    return Ok((c, method));
+                }
+            }
+            current = self.load_super_class(c)?;
+        }
+        self.default_method(runtime_class, &name, &descriptor)?
+            .ok_or(JRTError::AbstractMethod)
+    }
+
+    /// `invokespecial` on a superclass method of the current class starts
+    /// the lookup at the current class's superclass (JVMS 6.5), otherwise
+    /// the resolved method runs
+    fn select_special(
+        &mut self,
+        current: usize,
+        resolved: (usize, usize),
+    ) -> Result<(usize, usize), JRTError> {
+        let (name, descriptor) = self.method_name_and_type(resolved.0, resolved.1);
+        if name == "<init>"
+            || self.is_interface(resolved.0)
+            || resolved.0 == current
+            || !self.is_subclass_of(current, resolved.0)?
+        {
+            return Ok(resolved);
+        }
+        let Some(super_class) = self.load_super_class(current)? else {
+            return Ok(resolved);
+        };
+        if let Some(found) = self.find_method_in_supers(super_class, &name, &descriptor)? {
+            return Ok(found);
+        }
+        Ok(self
+            .default_method(super_class, &name, &descriptor)?
+            .unwrap_or(resolved))
+    }
+
+    /// Looks for the field in `class`, its superinterfaces and then its
+    /// superclass (JVMS 5.4.3.2)
+    fn find_field(
+        &mut self,
+        class: usize,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Option<(usize, usize)>, JRTError> {
+        if let Some(field) = self.class_list[class].field_entry_index_from_name(name, descriptor) {
+            return Ok(Some((class, field)));
+        }
+        for interface in self.load_interfaces(class)? {
+            if let Some(found) = self.find_field(interface, name, descriptor)? {
+                return Ok(Some(found));
+            }
+        }
+        match self.load_super_class(class)? {
+            Some(super_class) => self.find_field(super_class, name, descriptor),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolves the `Fieldref` at `cp_index` in the constant pool of
+    /// `class_index` to the declaring class and field index
+    pub fn resolve_field(
+        &mut self,
+        class_index: usize,
+        cp_index: u16,
+    ) -> Result<(usize, usize), JRTError> {
+        let constants = &self
+            .class_list
+            .get(class_index)
+            .ok_or(JRTError::ClassNotFound)?
+            .constant_pool;
+        let Some(ConstantPoolEntry::Fieldref { .. }) = constants.get_constant(cp_index) else {
+            return Err(JRTError::IncompatibleClassChange);
+        };
+        let (class_name, name, descriptor) = constants
+            .get_member_ref(cp_index)
+            .ok_or(JRTError::IncompatibleClassChange)?;
+        let (class_name, name, descriptor) = (
+            class_name.to_owned(),
+            name.to_owned(),
+            descriptor.to_owned(),
+        );
+
+        let class = self.load_class(&class_name)?;
+        let found = self
+            .find_field(class, &name, &descriptor)?
+            .ok_or(JRTError::FieldNotFound)?;
+        let flags = self.class_list[found.0].field_info[found.1]
+            .access_flags
+            .bits();
+        if !self.can_access_member(class_index, found.0, flags)? {
+            return Err(JRTError::IllegalAccess);
+        }
+        Ok(found)
+    }
+
+    /// Resolves a `getfield`/`putfield` operand from the current frame to
+    /// its index in the instance's fields
+    fn resolve_instance_field(&mut self, cp_index: u16) -> Result<(usize, FieldSlot), JRTError> {
+        let current = self.frame()?.class;
+        let (class, field) = self.resolve_field(current, cp_index)?;
+        if self.class_list[class].field_info[field]
+            .access_flags
+            .get(FieldAccessFlags::STATIC)
+        {
+            return Err(JRTError::IncompatibleClassChange);
+        }
+        let slot = FieldSlot { class, field };
+        let index = self
+            .field_layout(class)?
+            .iter()
+            .position(|s| *s == slot)
+            .ok_or(JRTError::FieldNotFound)?;
+        Ok((index, slot))
+    }
+
+    /// Final fields may only be set by their own class's initializer,
+    /// `<clinit>` for statics and `<init>` for instance fields
+    fn check_final_write(&self, slot: FieldSlot, initializer: &str) -> Result<(), JRTError> {
+        let class = &self.class_list[slot.class];
+        if !class.field_info[slot.field]
+            .access_flags
+            .get(FieldAccessFlags::FINAL)
+        {
+            return Ok(());
+        }
+        let frame = self.stack.current().ok_or(JRTError::NoFrame)?;
+        let (name, _) = self.method_name_and_type(frame.class, frame.method);
+        if frame.class != slot.class || name != initializer {
+            return Err(JRTError::IllegalAccess);
+        }
+        Ok(())
+    }
+
+    pub fn field_type(&self, slot: FieldSlot) -> Result<FieldType, JRTError> {
+        let class = &self.class_list[slot.class];
+        class.field_info[slot.field]
+            .field_type(&class.constant_pool)
+            .map_err(|_| JRTError::ClassFormatError)
+    }
+
+    fn method_descriptor(&self, class: usize, method: usize) -> Result<MethodDescriptor, JRTError> {
+        let class = &self.class_list[class];
+        class.method_info[method]
+            .descriptor(&class.constant_pool)
+            .map_err(|_| JRTError::ClassFormatError)
+    }
+
+    /// Resolves a `getstatic`/`putstatic` operand from the current frame,
+    /// initializing the class that declares the field
+    fn resolve_static_field(&mut self, cp_index: u16) -> Result<FieldSlot, JRTError> {
+        let current = self.frame()?.class;
+        let (class, field) = self.resolve_field(current, cp_index)?;
+        if !self.class_list[class].field_info[field]
+            .access_flags
+            .get(FieldAccessFlags::STATIC)
+        {
+            return Err(JRTError::IncompatibleClassChange);
+        }
+        self.initialize_class(class)?;
+        Ok(FieldSlot { class, field })
+    }
+
+    pub fn init_state(&self, class: usize) -> Option<InitState> {
+        self.init_states.get(&class).copied()
+    }
+
+    /// Initializes `class` on its first active use (JVMS 5.5): the
+    /// superclass and superinterfaces with default methods first, then the
+    /// statics get their defaults and `ConstantValue`s, then `<clinit>`
+    /// runs
+    pub fn initialize_class(&mut self, class: usize) -> Result<(), JRTError> {
+        match self.init_state(class) {
+            Some(InitState::InProgress | InitState::Initialized) => return Ok(()),
+            Some(InitState::Erroneous) => return Err(JRTError::NoClassDefFound),
+            None => {}
+        }
+        self.init_states.insert(class, InitState::InProgress);
+        let result = self.run_initializers(class);
+        let state = match result {
+            Ok(()) => InitState::Initialized,
+            Err(_) => InitState::Erroneous,
+        };
+        self.init_states.insert(class, state);
+        result
+    }
+
+    fn run_initializers(&mut self, class: usize) -> Result<(), JRTError> {
+        if !self.is_interface(class) {
+            if let Some(super_class) = self.load_super_class(class)? {
+                self.initialize_class(super_class)?;
+            }
+            for interface in self.all_interfaces(class)? {
+                let declares_default = self.class_list[interface].method_info.iter().any(|m| {
+                    !m.access_flags.get(MethodAccessFlags::ABSTRACT)
+                        && !m.access_flags.get(MethodAccessFlags::STATIC)
+                });
+                if declares_default {
+                    self.initialize_class(interface)?;
+                }
+            }
+        }
+
+        for field in 0..self.class_list[class].field_info.len() {
+            let entry = &self.class_list[class].field_info[field];
+            if !entry.access_flags.get(FieldAccessFlags::STATIC) {
+                continue;
+            }
+            let constant = entry.attributes.iter().find_map(|a| match a.info {
+                AttributeInfo::ConstantValue { constantvalue_indx } => Some(constantvalue_indx),
+                _ => None,
+            });
+            let value = match constant {
+                Some(index) => self.constant_value(class, index)?,
+                None => {
+                    let class = &self.class_list[class];
+                    let ty = entry
+                        .field_type(&class.constant_pool)
+                        .map_err(|_| JRTError::ClassFormatError)?;
+                    JRTVar::default_for(&ty)
+                }
+            };
+            self.statics.insert(FieldSlot { class, field }, value);
+        }
+
+        let Some(clinit) = self.class_list[class].method_entry_index_from_name("<clinit>", "()V")
+        else {
+            return Ok(());
+        };
+        let depth = self.stack.depth();
+        let result = self
+            .invoke(class, clinit, Vec::new())
+            .and_then(|result| match result {
+                Some(value) => Ok(value),
+                None => self.run(depth),
+            });
+        if let Err(err) = result {
+            self.stack.truncate(depth);
+            // errors pass through, exceptions get wrapped (JVMS 5.5 step 11)
+            return Err(if self.is_error(&err) {
+                err
+            } else {
+                JRTError::ExceptionInInitializerError
+            });
+        }
+        Ok(())
+    }
+
+    /// The value of the loadable constant at `cp_index` in the constant
+    /// pool of `class` (JVMS 5.4.3), resolving it if needed
+    pub fn constant_value(&mut self, class: usize, cp_index: u16) -> Result<JRTVar, JRTError> {
+        let constants = &self.class_list[class].constant_pool;
+        match constants.get_constant(cp_index) {
+            Some(ConstantPoolEntry::Integer(v)) => Ok(JRTVar::Int(*v)),
+            Some(ConstantPoolEntry::Long(v)) => Ok(JRTVar::Long(*v)),
+            Some(ConstantPoolEntry::Float(v)) => Ok(JRTVar::Float(*v)),
+            Some(ConstantPoolEntry::Double(v)) => Ok(JRTVar::Double(*v)),
+            Some(ConstantPoolEntry::String { string_index }) => {
+                let units = constants
+                    .get_constant(*string_index)
+                    .and_then(ConstantPoolEntry::get_utf16)
+                    .ok_or(JRTError::ClassFormatError)?;
+                Ok(JRTVar::Object(self.intern_utf16(&units)?))
+            }
+            Some(ConstantPoolEntry::Class { .. }) => {
+                let ty = self.resolve_type(class, cp_index)?;
+                Ok(JRTVar::Object(self.class_mirror(Some(&ty))?))
+            }
+            Some(ConstantPoolEntry::MethodType { .. } | ConstantPoolEntry::MethodHandle { .. }) => {
+                Ok(JRTVar::Object(
+                    self.resolve_method_constant(class, cp_index)?,
+                ))
+            }
+            Some(ConstantPoolEntry::Dynamic { .. }) => {
+                self.resolve_dynamic_constant(class, cp_index)
+            }
+            _ => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    /// Whether `err` is thrown as a `java.lang.Error`, these pass through
+    /// the places that wrap exceptions
+    fn is_error(&mut self, err: &JRTError) -> bool {
+        match err {
+            JRTError::UncaughtException(exception) => {
+                let error = FieldType::Object("java/lang/Error".into());
+                self.is_instance_of(exception.object, &error)
+                    .unwrap_or(false)
+            }
+            err => err.java_class().is_none_or(|c| c.ends_with("Error")),
+        }
+    }
+
+    /// Resolves a `CONSTANT_Dynamic` by calling its bootstrap method with a
+    /// lookup on `class`, the constant's name and type and the static
+    /// arguments (JVMS 5.4.3.6). Bootstrap methods are called directly
+    /// rather than through a `MethodHandle`, so only `REF_invokeStatic`
+    /// ones are supported, which is what `ConstantBootstraps` uses
+    fn resolve_dynamic_constant(
+        &mut self,
+        class: usize,
+        cp_index: u16,
+    ) -> Result<JRTVar, JRTError> {
+        if let Some(value) = self.resolved_constants.get(&(class, cp_index)) {
+            return Ok(value.clone());
+        }
+        let constants = &self.class_list[class].constant_pool;
+        let Some(ConstantPoolEntry::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }) = constants.get_constant(cp_index)
+        else {
+            return Err(JRTError::InvalidOperand);
+        };
+        let (name, descriptor) = constants
+            .get_name_and_type(*name_and_type_index)
+            .ok_or(JRTError::ClassFormatError)?;
+        let name = name.to_owned();
+        let ty = FieldType::parse(descriptor).map_err(|_| JRTError::ClassFormatError)?;
+        let bootstrap = self.class_list[class]
+            .bootstrap_method(*bootstrap_method_attr_index)
+            .ok_or(JRTError::ClassFormatError)?;
+        let static_arguments = bootstrap.bootstrap_arguments.clone();
+        let Some(ConstantPoolEntry::MethodHandle {
+            reference_kind: ReferenceKind::InvokeStatic,
+            reference_index,
+        }) = constants.get_constant(bootstrap.bootstrap_method_ref)
+        else {
+            return Err(JRTError::BootstrapMethodError);
+        };
+        let (bsm_class, bsm_method) = self.resolve_method(class, *reference_index)?;
+        let flags = self.method_flags(bsm_class, bsm_method);
+        if !flags.get(MethodAccessFlags::STATIC) {
+            return Err(JRTError::IncompatibleClassChange);
+        }
+
+        let lookup = self.lookup(class)?;
+        let name = self.intern_string(&name)?;
+        let mirror = self.class_mirror(Some(&ty))?;
+        let mut arguments = vec![
+            JRTVar::Object(lookup),
+            JRTVar::Object(name),
+            JRTVar::Object(mirror),
+        ];
+        for index in static_arguments {
+            arguments.push(self.constant_value(class, index)?);
+        }
+        let parameters = self.method_descriptor(bsm_class, bsm_method)?.parameters;
+        // a varargs bootstrap method takes the trailing arguments as an
+        // array, like `MethodHandle.asVarargsCollector` would pass them,
+        // unless an array is already in its place
+        if flags.get(MethodAccessFlags::VARARGS) && arguments.len() + 1 >= parameters.len() {
+            let Some(array_type @ FieldType::Array(component)) = parameters.last() else {
+                return Err(JRTError::ClassFormatError);
+            };
+            let passed_array = match arguments.last() {
+                Some(JRTVar::Object(object)) if arguments.len() == parameters.len() => {
+                    self.is_instance_of(*object, array_type)?
+                }
+                _ => false,
+            };
+            if !passed_array {
+                let trailing = arguments.split_off(parameters.len() - 1);
+                let mut elements = Vec::with_capacity(trailing.len());
+                for argument in trailing {
+                    elements.push(self.adapt(argument, component)?);
+                }
+                let array = JRTArray::Reference {
+                    component: (**component).clone(),
+                    elements,
+                };
+                arguments.push(JRTVar::Object(self.heap.new_array(array)));
+            }
+        }
+        if arguments.len() != parameters.len() {
+            return Err(JRTError::BootstrapMethodError);
+        }
+        let arguments = arguments
+            .into_iter()
+            .zip(&parameters)
+            .map(|(argument, ty)| self.adapt(argument, ty))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let (method_name, method_descriptor) = self.method_name_and_type(bsm_class, bsm_method);
+        let bsm_class_name = self.class_list[bsm_class]
+            .name()
+            .ok_or(JRTError::ClassFormatError)?
+            .to_owned();
+        let value = match self.run_static_method(
+            &bsm_class_name,
+            &method_name,
+            &method_descriptor,
+            &arguments,
+        ) {
+            Ok(value) => self.adapt(value, &ty)?,
+            // exceptions from the bootstrap method get wrapped (JVMS 5.4.3.6)
+            Err(err) if self.is_error(&err) => return Err(err),
+            Err(_) => return Err(JRTError::BootstrapMethodError),
+        };
+        self.resolved_constants
+            .insert((class, cp_index), value.clone());
+        Ok(value)
+    }
+
+    /// A `MethodHandles.Lookup` with full privileges on `class`, the kind
+    /// the JVM hands bootstrap methods
+    fn lookup(&mut self, class: usize) -> Result<JRTObject, JRTError> {
+        // MethodHandles.Lookup.FULL_POWER_MODES
+        const FULL_POWER_MODES: i32 = 95;
+        let lookup_class = FieldType::Object(
+            self.class_list[class]
+                .name()
+                .ok_or(JRTError::ClassFormatError)?
+                .into(),
+        );
+        let lookup_class = self.class_mirror(Some(&lookup_class))?;
+        let class = self.load_class("java/lang/invoke/MethodHandles$Lookup")?;
+        let lookup = self.new_object(class)?;
+        self.set_field(
+            lookup,
+            "lookupClass",
+            "Ljava/lang/Class;",
+            JRTVar::Object(lookup_class),
+        )?;
+        self.set_field(lookup, "allowedModes", "I", JRTVar::Int(FULL_POWER_MODES))?;
+        Ok(lookup)
+    }
+
+    /// Converts a value between primitive and reference form where a
+    /// `MethodHandle` call would box or unbox it, references are cast
+    fn adapt(&mut self, value: JRTVar, ty: &FieldType) -> Result<JRTVar, JRTError> {
+        match (ty, value.as_reference()) {
+            (FieldType::Base(base), Ok(object)) => {
+                let object = object.ok_or(JRTError::NullPointerException)?;
+                self.unbox(object, *base)
+            }
+            (FieldType::Base(_), Err(_)) => value.convert_to(ty),
+            (_, Err(_)) => Ok(JRTVar::Object(self.box_value(value)?)),
+            (_, Ok(None)) => Ok(JRTVar::Null),
+            (_, Ok(Some(object))) => {
+                self.check_cast(object, ty)?;
+                Ok(value)
+            }
+        }
+    }
+
+    /// The wrapper class of a primitive type
+    fn box_class(base: BaseType) -> &'static str {
+        match base {
+            BaseType::Boolean => "java/lang/Boolean",
+            BaseType::Byte => "java/lang/Byte",
+            BaseType::Char => "java/lang/Character",
+            BaseType::Short => "java/lang/Short",
+            BaseType::Int => "java/lang/Integer",
+            BaseType::Long => "java/lang/Long",
+            BaseType::Float => "java/lang/Float",
+            BaseType::Double => "java/lang/Double",
+        }
+    }
+
+    /// Wraps a primitive in a new instance of its wrapper class
+    fn box_value(&mut self, value: JRTVar) -> Result<JRTObject, JRTError> {
+        let base = match value {
+            JRTVar::Boolean(_) => BaseType::Boolean,
+            JRTVar::Byte(_) => BaseType::Byte,
+            JRTVar::Char(_) => BaseType::Char,
+            JRTVar::Short(_) => BaseType::Short,
+            JRTVar::Int(_) => BaseType::Int,
+            JRTVar::Long(_) => BaseType::Long,
+            JRTVar::Float(_) => BaseType::Float,
+            JRTVar::Double(_) => BaseType::Double,
+            _ => return Err(JRTError::InvalidOperand),
+        };
+        let class = self.load_class(Self::box_class(base))?;
+        let object = self.new_object(class)?;
+        let descriptor = base.to_char().to_string();
+        self.set_field(object, "value", &descriptor, value)?;
+        Ok(object)
+    }
+
+    /// The primitive a wrapper object holds, it must wrap exactly `base`
+    fn unbox(&mut self, object: JRTObject, base: BaseType) -> Result<JRTVar, JRTError> {
+        let class = self.object_class(object)?;
+        if self.class_list[class].name() != Some(Self::box_class(base)) {
+            let ty = FieldType::Object(Self::box_class(base).into());
+            return Err(self.class_cast_exception(object, &ty));
+        }
+        let descriptor = base.to_char().to_string();
+        self.get_field(object, "value", &descriptor)
+    }
+
+    /// The interned `java.lang.String` holding `value`, every string
+    /// constant with the same contents is the same object (JVMS 5.1)
+    pub fn intern_string(&mut self, value: &str) -> Result<JRTObject, JRTError> {
+        self.intern_utf16(&value.encode_utf16().collect::<Vec<_>>())
+    }
+
+    /// Like `intern_string`, for contents that aren't valid UTF-16
+    pub fn intern_utf16(&mut self, units: &[u16]) -> Result<JRTObject, JRTError> {
+        if let Some(string) = self.strings.get(units) {
+            return Ok(*string);
+        }
+        let string = self.new_string_utf16(units)?;
+        self.strings.insert(units.to_vec(), string);
+        Ok(string)
+    }
+
+    /// What `String.intern()` returns, the interned string with the same
+    /// contents, `string` itself becomes the interned one when there is
+    /// none yet
+    pub fn intern(&mut self, string: JRTObject) -> Result<JRTObject, JRTError> {
+        let units = self.string_utf16(string)?;
+        Ok(*self.strings.entry(units).or_insert(string))
+    }
+
+    /// Allocates a new `java.lang.String`, unlike `intern_string` every
+    /// call gives a different object
+    pub fn new_string(&mut self, value: &str) -> Result<JRTObject, JRTError> {
+        self.new_string_utf16(&value.encode_utf16().collect::<Vec<_>>())
+    }
+
+    /// Allocates a `java.lang.String` laid out like HotSpot's compact
+    /// strings, Latin-1 when every char fits in a byte and UTF-16 in native
+    /// byte order otherwise
+    pub fn new_string_utf16(&mut self, units: &[u16]) -> Result<JRTObject, JRTError> {
+        let (value, coder) = if units.iter().all(|u| *u <= 0xFF) {
+            (units.iter().map(|u| *u as i8).collect(), 0)
+        } else {
+            let bytes = units.iter().flat_map(|u| u.to_ne_bytes());
+            (bytes.map(|b| b as i8).collect(), 1)
+        };
+        let class = self.load_class("java/lang/String")?;
+        let string = self.new_object(class)?;
+        let value = self.heap.new_array(JRTArray::Byte(value));
+        self.set_field(string, "value", "[B", JRTVar::Object(value))?;
+        self.set_field(string, "coder", "B", JRTVar::Int(coder))?;
+        Ok(string)
+    }
+
+    /// The UTF-16 contents of a `java.lang.String`, whichever coder it
+    /// uses
+    pub fn string_utf16(&mut self, string: JRTObject) -> Result<Vec<u16>, JRTError> {
+        let class = self.heap.instance(string)?.class;
+        if self.class_list[class].name() != Some("java/lang/String") {
+            return Err(JRTError::InvalidOperand);
+        }
+        let JRTVar::Object(value) = self.get_field(string, "value", "[B")? else {
+            return Err(JRTError::NullPointerException);
+        };
+        let coder = self.get_field(string, "coder", "B")?;
+        let JRTArray::Byte(bytes) = self.heap.array(value)? else {
+            return Err(JRTError::InvalidOperand);
+        };
+        Ok(match coder {
+            JRTVar::Int(0) => bytes.iter().map(|b| *b as u8 as u16).collect(),
+            JRTVar::Int(1) => bytes
+                .chunks_exact(2)
+                .map(|c| u16::from_ne_bytes([c[0] as u8, c[1] as u8]))
+                .collect(),
+            _ => return Err(JRTError::InvalidOperand),
+        })
+    }
+
+    /// The contents of a `java.lang.String` as a Rust string, unpaired
+    /// surrogates become U+FFFD
+    pub fn string_value(&mut self, string: JRTObject) -> Result<String, JRTError> {
+        Ok(String::from_utf16_lossy(&self.string_utf16(string)?))
+    }
+
+    /// The position of an instance field in the fields of `object`
+    fn field_index(
+        &mut self,
+        object: JRTObject,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<usize, JRTError> {
+        let class = self.heap.instance(object)?.class;
+        let (declaring, field) = self
+            .find_field(class, name, descriptor)?
+            .ok_or(JRTError::FieldNotFound)?;
+        let slot = FieldSlot {
+            class: declaring,
+            field,
+        };
+        self.field_layout(class)?
+            .iter()
+            .position(|s| *s == slot)
+            .ok_or(JRTError::FieldNotFound)
+    }
+
+    /// Reads an instance field by name, for objects the interpreter looks
+    /// into itself
+    fn get_field(
+        &mut self,
+        object: JRTObject,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<JRTVar, JRTError> {
+        let index = self.field_index(object, name, descriptor)?;
+        Ok(self.heap.instance(object)?.fields[index].clone())
+    }
+
+    /// Sets an instance field by name, for objects the interpreter builds
+    /// itself
+    fn set_field(
+        &mut self,
+        object: JRTObject,
+        name: &str,
+        descriptor: &str,
+        value: JRTVar,
+    ) -> Result<(), JRTError> {
+        let index = self.field_index(object, name, descriptor)?;
+        self.heap.instance_mut(object)?.fields[index] = value;
+        Ok(())
+    }
+
+    /// The `java.lang.Class` of a type, `None` for void. There is one
+    /// mirror per type, array mirrors know their component type
+    pub fn class_mirror(&mut self, ty: Option<&FieldType>) -> Result<JRTObject, JRTError> {
+        let descriptor = ty.map_or_else(|| "V".into(), FieldType::descriptor);
+        if let Some(mirror) = self.mirrors.get(&descriptor) {
+            return Ok(*mirror);
+        }
+        if let Some(ty) = ty {
+            self.load_element_class(ty)?;
+        }
+        let class = self.load_class("java/lang/Class")?;
+        let mirror = self.new_object(class)?;
+        self.mirrors.insert(descriptor, mirror);
+        if let Some(FieldType::Array(component)) = ty {
+            let component = self.class_mirror(Some(component))?;
+            self.set_field(
+                mirror,
+                "componentType",
+                "Ljava/lang/Class;",
+                JRTVar::Object(component),
+            )?;
+        }
+        Ok(mirror)
+    }
+
+    /// Resolves a `CONSTANT_MethodType` or `CONSTANT_MethodHandle` through
+    /// `java.lang.invoke.MethodHandleNatives` the way HotSpot does
+    fn resolve_method_constant(
+        &mut self,
+        class: usize,
+        cp_index: u16,
+    ) -> Result<JRTObject, JRTError> {
+        if let Some(JRTVar::Object(object)) = self.resolved_constants.get(&(class, cp_index)) {
+            return Ok(*object);
+        }
+        let constants = &self.class_list[class].constant_pool;
+        let object = match constants.get_constant(cp_index) {
+            Some(ConstantPoolEntry::MethodType { descriptor_index }) => {
+                let descriptor = constants
+                    .get_const_utd8(*descriptor_index)
+                    .ok_or(JRTError::ClassFormatError)?
+                    .to_owned();
+                self.method_type(&descriptor)?
+            }
+            Some(ConstantPoolEntry::MethodHandle {
+                reference_kind,
+                reference_index,
+            }) => {
+                let kind = *reference_kind;
+                let (owner, name, descriptor) = constants
+                    .get_member_ref(*reference_index)
+                    .ok_or(JRTError::IncompatibleClassChange)?;
+                let (owner, name, descriptor) = (
+                    FieldType::Object(owner.into()),
+                    name.to_owned(),
+                    descriptor.to_owned(),
+                );
+                let caller = FieldType::Object(
+                    self.class_list[class]
+                        .name()
+                        .ok_or(JRTError::ClassFormatError)?
+                        .into(),
+                );
+                let caller = self.class_mirror(Some(&caller))?;
+                let owner = self.class_mirror(Some(&owner))?;
+                let name = self.intern_string(&name)?;
+                // field handles are typed by the field's class, method
+                // handles by their MethodType
+                let ty = match kind {
+                    ReferenceKind::GetField
+                    | ReferenceKind::GetStatic
+                    | ReferenceKind::PutField
+                    | ReferenceKind::PutStatic => {
+                        let ty = FieldType::parse(&descriptor)
+                            .map_err(|_| JRTError::ClassFormatError)?;
+                        self.class_mirror(Some(&ty))?
+                    }
+                    _ => self.method_type(&descriptor)?,
+                };
+                self.run_static_method(
+                    "java/lang/invoke/MethodHandleNatives",
+                    "linkMethodHandleConstant",
+                    "(Ljava/lang/Class;ILjava/lang/Class;Ljava/lang/String;Ljava/lang/Object;)Ljava/lang/invoke/MethodHandle;",
+                    &[
+                        JRTVar::Object(caller),
+                        JRTVar::Int(kind.to_u8() as i32),
+                        JRTVar::Object(owner),
+                        JRTVar::Object(name),
+                        JRTVar::Object(ty),
+                    ],
+                )?
+                .as_reference()?
+                .ok_or(JRTError::NullPointerException)?
+            }
+            _ => return Err(JRTError::InvalidOperand),
+        };
+        self.resolved_constants
+            .insert((class, cp_index), JRTVar::Object(object));
+        Ok(object)
+    }
+
+    /// The `java.lang.invoke.MethodType` of a method descriptor
+    fn method_type(&mut self, descriptor: &str) -> Result<JRTObject, JRTError> {
+        let descriptor =
+            MethodDescriptor::parse(descriptor).map_err(|_| JRTError::ClassFormatError)?;
+        let return_type = self.class_mirror(descriptor.return_type.as_ref())?;
+        let mut parameters = Vec::with_capacity(descriptor.parameters.len());
+        for parameter in &descriptor.parameters {
+            parameters.push(JRTVar::Object(self.class_mirror(Some(parameter))?));
+        }
+        let parameters = self.heap.new_array(JRTArray::Reference {
+            component: FieldType::Object("java/lang/Class".into()),
+            elements: parameters,
+        });
+        self.run_static_method(
+            "java/lang/invoke/MethodHandleNatives",
+            "findMethodHandleType",
+            "(Ljava/lang/Class;[Ljava/lang/Class;)Ljava/lang/invoke/MethodType;",
+            &[JRTVar::Object(return_type), JRTVar::Object(parameters)],
+        )?
+        .as_reference()?
+        .ok_or(JRTError::NullPointerException)
+    }
+
+    /// The instance fields of `class`, those of its superclasses come
+    /// first so a field has the same index in every subclass
+    pub fn field_layout(&mut self, class: usize) -> Result<&[FieldSlot], JRTError> {
+        if !self.field_layouts.contains_key(&class) {
+            let mut layout = match self.load_super_class(class)? {
+                Some(super_class) => self.field_layout(super_class)?.to_vec(),
+                None => Vec::new(),
+            };
+            layout.extend(
+                self.class_list[class]
+                    .field_info
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, f)| !f.access_flags.get(FieldAccessFlags::STATIC))
+                    .map(|(field, _)| FieldSlot { class, field }),
+            );
+            self.field_layouts.insert(class, layout);
+        }
+        Ok(&self.field_layouts[&class])
+    }
+
+    /// Allocates an instance of `class` with every field at its default
+    /// value, constructors are left to the caller
+    pub fn new_object(&mut self, class: usize) -> Result<JRTObject, JRTError> {
+        let flags = self.class_list[class].access_flags;
+        if flags.get(ClassAccessFlags::INTERFACE) || flags.get(ClassAccessFlags::ABSTRACT) {
+            return Err(JRTError::Instantiation);
+        }
+        let layout = self.field_layout(class)?.to_vec();
+        let fields = layout
+            .iter()
+            .map(|slot| {
+                let class = &self.class_list[slot.class];
+                class.field_info[slot.field]
+                    .field_type(&class.constant_pool)
+                    .map(|ty| JRTVar::default_for(&ty))
+                    .map_err(|_| JRTError::ClassFormatError)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(self.heap.new_object(class, fields))
+    }
+
+    /// Resolves the `CONSTANT_Class` at `cp_index` as a type, it names
+    /// an array class when it starts with `[`. The element class of an
+    /// array is loaded like a plain class would be
+    pub fn resolve_type(
+        &mut self,
+        class_index: usize,
+        cp_index: u16,
+    ) -> Result<FieldType, JRTError> {
+        let name = self
+            .class_list
+            .get(class_index)
+            .and_then(|c| c.constant_pool.get_class_name(cp_index))
+            .ok_or(JRTError::IncompatibleClassChange)?
+            .to_owned();
+        let ty = if name.starts_with('[') {
+            FieldType::parse(&name).map_err(|_| JRTError::ClassFormatError)?
+        } else {
+            FieldType::Object(name)
+        };
+        self.load_element_class(&ty)?;
+        Ok(ty)
+    }
+
+    /// Loads the class an object or array type is made of
+    fn load_element_class(&mut self, ty: &FieldType) -> Result<(), JRTError> {
+        let mut element = ty;
+        while let FieldType::Array(component) = element {
+            element = component;
+        }
+        if let FieldType::Object(name) = element {
+            self.load_class(name)?;
+        }
+        Ok(())
+    }
+
+    /// The runtime type of a heap object
+    pub fn object_type(&self, object: JRTObject) -> Result<FieldType, JRTError> {
+        match self.heap.get(object) {
+            Some(HeapEntry::Object(instance)) => Ok(FieldType::Object(
+                self.class_list[instance.class]
+                    .name()
+                    .ok_or(JRTError::ClassNotFound)?
+                    .into(),
+            )),
+            Some(HeapEntry::Array(array)) => Ok(array.array_type()),
+            None => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    /// Whether a value of type `from` can be cast to `to` (JVMS checkcast)
+    pub fn is_assignable(&mut self, from: &FieldType, to: &FieldType) -> Result<bool, JRTError> {
+        if from == to {
+            return Ok(true);
+        }
+        match (from, to) {
+            (FieldType::Object(from), FieldType::Object(to)) => {
+                let from = self.load_class(from)?;
+                let to = self.load_class(to)?;
+                self.is_subclass_of(from, to)
+            }
+            // arrays are Objects that implement Cloneable and Serializable
+            (FieldType::Array(_), FieldType::Object(to)) => Ok(matches!(
+                to.as_str(),
+                "java/lang/Object" | "java/lang/Cloneable" | "java/io/Serializable"
+            )),
+            (FieldType::Array(from), FieldType::Array(to)) => match (&**from, &**to) {
+                (FieldType::Base(from), FieldType::Base(to)) => Ok(from == to),
+                (FieldType::Base(_), _) | (_, FieldType::Base(_)) => Ok(false),
+                (from, to) => self.is_assignable(from, to),
+            },
+            (from, to) => Ok(from == to),
+        }
+    }
+
+    /// Whether the object can be cast to `ty`
+    /// `checkcast` of a non-null reference
+    fn check_cast(&mut self, object: JRTObject, ty: &FieldType) -> Result<(), JRTError> {
+        if self.is_instance_of(object, ty)? {
+            Ok(())
+        } else {
+            Err(self.class_cast_exception(object, ty))
+        }
+    }
+
+    fn class_cast_exception(&self, object: JRTObject, ty: &FieldType) -> JRTError {
+        let from = match self.object_type(object) {
+            Ok(from) => external_name(&from),
+            Err(err) => return err,
+        };
+        JRTError::ClassCastException(format!(
+            "class {} cannot be cast to class {}",
+            from,
+            external_name(ty)
+        ))
+    }
+
+    pub fn is_instance_of(&mut self, object: JRTObject, ty: &FieldType) -> Result<bool, JRTError> {
+        let object_type = self.object_type(object)?;
+        self.is_assignable(&object_type, ty)
+    }
+
+    /// Allocates nested arrays for `multianewarray`, only the dimensions
+    /// given a count are created, deeper ones are left null
+    fn new_multi_array(&mut self, ty: &FieldType, counts: &[usize]) -> JRTObject {
+        let FieldType::Array(component) = ty else {
+            unreachable!("multianewarray checks the dimensions first")
+        };
+        let mut array = JRTArray::new(component, counts[0]);
+        if counts.len() > 1 {
+            if let JRTArray::Reference { elements, .. } = &mut array {
+                for element in elements.iter_mut() {
+                    *element = JRTVar::Object(self.new_multi_array(component, &counts[1..]));
+                }
+            }
+        }
+        self.heap.new_array(array)
+    }
+
+    /// The class whose methods an object answers to, arrays answer to
+    /// `java/lang/Object`
+    fn object_class(&mut self, object: JRTObject) -> Result<usize, JRTError> {
+        match self.heap.get(object) {
+            Some(HeapEntry::Object(instance)) => Ok(instance.class),
+            Some(HeapEntry::Array(_)) => self.load_class("java/lang/Object"),
+            None => Err(JRTError::InvalidOperand),
+        }
+    }
+
+    /// Pops the arguments of the method off the current operand stack,
+    /// `this` first for instance methods
+    fn pop_arguments(&mut self, class: usize, method: usize) -> Result<Vec<JRTVar>, JRTError> {
+        let class = &self.class_list[class];
+        let entry = &class.method_info[method];
+        let descriptor = entry
+            .descriptor(&class.constant_pool)
+            .map_err(|_| JRTError::ClassFormatError)?;
+        let this = usize::from(!entry.access_flags.get(MethodAccessFlags::STATIC));
+        // arguments are taken by slots, so a long can't stand in for two
+        // ints or the other way around
+        let frame = self.stack.current_mut().ok_or(JRTError::NoFrame)?;
+        let arguments = frame.pop_slots(descriptor.arg_slots() + this)?;
+        if arguments.len() != descriptor.parameters.len() + this {
+            return Err(JRTError::InvalidOperand);
+        }
+        Ok(arguments)
+    }
+
+    pub fn run_static_method(
+        &mut self,
+        class: &str,
+        method_name: &str,
+        descriptor: &str,
+        arguments: &[JRTVar],
+    ) -> Result<JRTVar, JRTError> {
+        let class_iid = self.load_class(class)?;
+        let class = self.class_list.get(class_iid).unwrap();
+        let method_iid = class
+            .method_entry_index_from_name(method_name, descriptor)
+            .ok_or(JRTError::MethodNotFound)?;
+        if !class.method_info[method_iid]
+            .access_flags
+            .get(MethodAccessFlags::STATIC)
+        {
+            return Err(JRTError::MethodNotStatic);
+        }
+        let arguments = self.convert_arguments(class_iid, method_iid, arguments)?;
+        self.initialize_class(class_iid)?;
+        let depth = self.stack.depth();
+        let result =
+            self.invoke(class_iid, method_iid, arguments)
+                .and_then(|result| match result {
+                    Some(value) => Ok(value),
+                    None => self.run(depth),
+                });
+        // drop whatever frames the failure left behind
+        if result.is_err() {
+            self.stack.truncate(depth);
+        }
+        result
+    }
+
+    /// Checks host supplied arguments against the method's descriptor and
+    /// converts them to how the interpreter holds them, so a `Byte` or an
+    /// `Int` can be passed for a byte parameter
+    fn convert_arguments(
+        &mut self,
+        class: usize,
+        method: usize,
+        arguments: &[JRTVar],
+    ) -> Result<Vec<JRTVar>, JRTError> {
+        let parameters = self.method_descriptor(class, method)?.parameters;
+        if parameters.len() != arguments.len() {
+            return Err(JRTError::InvalidOperand);
+        }
+        let mut converted = Vec::with_capacity(arguments.len());
+        for (ty, arg) in parameters.iter().zip(arguments) {
+            let arg = arg.clone().convert_to(ty)?;
+            if let JRTVar::Object(object) = arg {
+                if !self.is_instance_of(object, ty)? {
+                    return Err(JRTError::InvalidOperand);
+                }
+            }
+            converted.push(arg);
+        }
+        Ok(converted)
+    }
+
+    /// Pushes a frame for the method with its arguments, `this` first for
+    /// instance methods, laid out in the locals
+    pub fn invoke(
+        &mut self,
+        class: usize,
+        method: usize,
+        arguments: Vec<JRTVar>,
+    ) -> Result<Option<JRTVar>, JRTError> {
+        let entry = self
+            .class_list
+            .get(class)
+            .and_then(|c| c.method_info.get(method))
+            .ok_or(JRTError::MethodNotFound)?;
+        if entry.access_flags.get(MethodAccessFlags::ABSTRACT) {
+            return Err(JRTError::AbstractMethod);
+        }
+        if entry.access_flags.get(MethodAccessFlags::NATIVE) {
+            return self.invoke_native(class, method, arguments).map(Some);
+        }
+        let (max_stack, max_locals) = entry
+            .attributes
+            .iter()
+            .find_map(|a| match &a.info {
+                AttributeInfo::Code {
+                    max_stack,
+                    max_locals,
+                    ..
+                } => Some((*max_stack as usize, *max_locals as usize)),
+                _ => None,
+            })
+            // only abstract and native methods go without code (JVMS 4.7.3)
+            .ok_or(JRTError::ClassFormatError)?;
+
+        let mut frame = Frame::new(class, method, max_locals, max_stack);
+        let mut slot = 0;
+        for arg in arguments {
+            let slots = arg.slots();
+            frame.set_local(slot, arg)?;
+            slot += slots;
+        }
+        if let Some(caller) = self.stack.current() {
+            frame.return_pc = caller.pc;
+        }
+        self.stack.push(frame)?;
+        Ok(None)
+    }
+
+    /// Runs a native method from the registry. Arguments are handed over
+    /// in their declared types and the result is narrowed to the return
+    /// type like `ireturn` does
+    fn invoke_native(
+        &mut self,
+        class: usize,
+        method: usize,
+        mut arguments: Vec<JRTVar>,
+    ) -> Result<JRTVar, JRTError> {
+        let (name, descriptor) = self.method_name_and_type(class, method);
+        let class_name = self.class_list[class]
+            .name()
+            .ok_or(JRTError::ClassFormatError)?;
+        let native = self
+            .natives
+            .get(class_name, &name, &descriptor)
+            .ok_or(JRTError::UnsatisfiedLink)?;
+        let descriptor = self.method_descriptor(class, method)?;
+
+        // `this` has no parameter type
+        let receiver = arguments.len() - descriptor.parameters.len();
+        for (argument, ty) in arguments[receiver..].iter_mut().zip(&descriptor.parameters) {
+            if let (FieldType::Base(base), JRTVar::Int(v)) = (ty, &*argument) {
+                *argument = JRTVar::from_int(*base, *v);
+            }
+        }
+        let value = native(self, &NativeArgs::new(arguments))?;
+        match (&descriptor.return_type, value) {
+            (None, JRTVar::Void) => Ok(JRTVar::Void),
+            (None, _) => Err(JRTError::InvalidOperand),
+            (Some(ty), value) => match value.convert_to(ty)? {
+                JRTVar::Int(v) => match ty {
+                    FieldType::Base(base) => Ok(JRTVar::from_int(*base, v)),
+                    _ => Err(JRTError::InvalidOperand),
+                },
+                value => Ok(value),
+            },
+        }
+    }
+
+    /// Hands the result of a native method invoked from the current frame
+    /// to its operand stack, calls that pushed a frame have nothing to hand
+    fn push_result(&mut self, result: Option<JRTVar>) -> Result<(), JRTError> {
+        match result {
+            Some(JRTVar::Void) | None => Ok(()),
+            Some(value) => self.frame()?.push(value.to_computational()),
+        }
+    }
+
+    /// Pops the current frame handing its return value to the caller,
+    /// returns the value once the frame stack is back at `base`
+    fn return_from_frame(
+        &mut self,
+        base: usize,
+        value: JRTVar,
+    ) -> Result<Option<JRTVar>, JRTError> {
+        let frame = self.stack.pop().ok_or(JRTError::NoFrame)?;
+        if self.stack.depth() <= base {
+            return Ok(Some(value));
+        }
+        let caller = self.stack.current_mut().ok_or(JRTError::NoFrame)?;
+        caller.pc = frame.return_pc;
+        if !matches!(value, JRTVar::Void) {
+            caller.push(value.to_computational())?;
+        }
+        Ok(None)
+    }
+
+    fn frame(&mut self) -> Result<&mut Frame, JRTError> {
+        self.stack.current_mut().ok_or(JRTError::NoFrame)
+    }
+
+    /// Runs until the frame stack unwinds back to `base` frames
+    pub fn run(&mut self, base: usize) -> Result<JRTVar, JRTError> {
+        loop {
+            let frame = self.stack.current().ok_or(JRTError::NoFrame)?;
+            let pc = frame.pc;
+            let code = self.decoded_code(frame.class, frame.method)?;
+            let Some(Some((ins, len))) = code.get(pc) else {
+                return Err(JRTError::InvalidInstruction);
+            };
+            self.frame()?.pc = pc + len;
+
+            let ret = match self.execute(ins, pc) {
+                Ok(ret) => ret,
+                Err(err) => {
+                    let object = match err {
+                        JRTError::UncaughtException(exception) => exception.object,
+                        err => self.new_vm_exception(err, pc)?,
+                    };
+                    self.throw(base, object, pc)?;
+                    continue;
+                }
+            };
+            if let Some(value) = ret {
+                if let Some(value) = self.return_from_frame(base, value)? {
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    fn decoded_code(&mut self, class: usize, method: usize) -> Result<Rc<DecodedCode>, JRTError> {
+        if let Some(code) = self.decoded_code.get(&(class, method)) {
+            return Ok(code.clone());
+        }
+        let code = self.class_list[class]
+            .method_code(method)
+            .ok_or(JRTError::ClassFormatError)?;
+        let mut decoded = vec![None; code.len()];
+        let mut pc = 0;
+        while let Ok((ins, len)) = Instruction::decode(code, pc) {
+            decoded[pc] = Some((ins, len));
+            pc += len;
+        }
+        let decoded: Rc<DecodedCode> = decoded.into();
+        self.decoded_code.insert((class, method), decoded.clone());
+        Ok(decoded)
+    }
+
+    /// Executes one instruction of the current frame, `pc` is where it
+    /// starts. Returns the value when the frame returns
+    fn execute(&mut self, ins: &Instruction, pc: usize) -> Result<Option<JRTVar>, JRTError> {
+        Ok(match *ins {
+            Instruction::Nop => None,
+            Instruction::Wide(ref ins) => return self.execute(ins, pc),
+            Instruction::Athrow => {
+                let object = self.frame()?.pop_non_null()?;
+                return Err(self.exception(object, pc));
+            }
+            // with a single thread every monitor is free, only the null
+            // check is left
+            Instruction::Monitorenter | Instruction::Monitorexit => {
+                self.frame()?.pop_non_null()?;
+                None
+            }
+            Instruction::Getstatic(index) => {
+                let slot = self.resolve_static_field(index)?;
+                let value = self
+                    .statics
+                    .get(&slot)
+                    .cloned()
+                    .ok_or(JRTError::FieldNotFound)?;
+                self.frame()?.push(value)?;
+                None
+            }
+            Instruction::Putstatic(index) => {
+                let slot = self.resolve_static_field(index)?;
+                self.check_final_write(slot, "<clinit>")?;
+                let ty = self.field_type(slot)?;
+                let value = self.frame()?.pop()?.convert_to(&ty)?;
+                self.statics.insert(slot, value);
+                None
+            }
+            Instruction::Ldc(index) | Instruction::LdcW(index) | Instruction::Ldc2W(index) => {
+                let current = self.frame()?.class;
+                let value = self.constant_value(current, index)?;
+                // ldc2_w loads exactly the category 2 constants
+                if (value.category() == 2) != matches!(ins, Instruction::Ldc2W(_)) {
+                    return Err(JRTError::InvalidOperand);
+                }
+                self.frame()?.push(value)?;
+                None
+            }
+            Instruction::Invokestatic(index) => {
+                let current = self.frame()?.class;
+                let (class, method) = self.resolve_method(current, index)?;
+                if !self
+                    .method_flags(class, method)
+                    .get(MethodAccessFlags::STATIC)
+                {
+                    return Err(JRTError::IncompatibleClassChange);
+                }
+                self.initialize_class(class)?;
+                let arguments = self.pop_arguments(class, method)?;
+                let result = self.invoke(class, method, arguments)?;
+                self.push_result(result)?;
+                None
+            }
+            Instruction::Invokespecial(index) => {
+                let current = self.frame()?.class;
+                let resolved = self.resolve_method(current, index)?;
+                let (class, method) = self.select_special(current, resolved)?;
+                if self
+                    .method_flags(class, method)
+                    .get(MethodAccessFlags::STATIC)
+                {
+                    return Err(JRTError::IncompatibleClassChange);
+                }
+                let arguments = self.pop_arguments(class, method)?;
+                if arguments[0].as_reference()?.is_none() {
+                    return Err(JRTError::NullPointerException);
+                }
+                let result = self.invoke(class, method, arguments)?;
+                self.push_result(result)?;
+                None
+            }
+            Instruction::Invokevirtual(index) | Instruction::Invokeinterface { index, .. } => {
+                let current = self.frame()?.class;
+                let resolved = self.resolve_method(current, index)?;
+                if self
+                    .method_flags(resolved.0, resolved.1)
+                    .get(MethodAccessFlags::STATIC)
+                {
+                    return Err(JRTError::IncompatibleClassChange);
+                }
+                let arguments = self.pop_arguments(resolved.0, resolved.1)?;
+                let receiver = arguments[0]
+                    .as_reference()?
+                    .ok_or(JRTError::NullPointerException)?;
+                let runtime_class = self.object_class(receiver)?;
+                let (class, method) = self.select_method(runtime_class, resolved)?;
+                let result = self.invoke(class, method, arguments)?;
+                self.push_result(result)?;
+                None
+            }
+            Instruction::Return => Some(JRTVar::Void),
+            // ireturn narrows to the declared return type (JVMS 6.5)
+            Instruction::Ireturn => {
+                let frame = self.stack.current().ok_or(JRTError::NoFrame)?;
+                let return_type = self
+                    .method_descriptor(frame.class, frame.method)?
+                    .return_type;
+                let v = self.frame()?.pop_int()?;
+                Some(match return_type {
+                    Some(FieldType::Base(base)) => JRTVar::from_int(base, v),
+                    _ => JRTVar::Int(v),
+                })
+            }
+            Instruction::Lreturn => Some(JRTVar::Long(self.frame()?.pop_long()?)),
+            Instruction::Freturn => Some(JRTVar::Float(self.frame()?.pop_float()?)),
+            Instruction::Dreturn => Some(JRTVar::Double(self.frame()?.pop_double()?)),
+            Instruction::Areturn => Some(self.frame()?.pop_reference()?.into()),
+
+            Instruction::New(index) => {
+                let current = self.frame()?.class;
+                let class = self.resolve_class(current, index)?;
+                self.initialize_class(class)?;
+                let object = self.new_object(class)?;
+                self.frame()?.push(JRTVar::Object(object))?;
+                None
+            }
+            Instruction::Getfield(index) => {
+                let (slot, _) = self.resolve_instance_field(index)?;
+                let object = self.frame()?.pop_non_null()?;
+                let value = self
+                    .heap
+                    .instance(object)?
+                    .fields
+                    .get(slot)
+                    .ok_or(JRTError::IncompatibleClassChange)?
+                    .clone();
+                self.frame()?.push(value)?;
+                None
+            }
+            Instruction::Putfield(index) => {
+                let (slot, field) = self.resolve_instance_field(index)?;
+                self.check_final_write(field, "<init>")?;
+                let ty = self.field_type(field)?;
+                let frame = self.stack.current_mut().ok_or(JRTError::NoFrame)?;
+                let value = frame.pop()?.convert_to(&ty)?;
+                let object = frame.pop_non_null()?;
+                *self
+                    .heap
+                    .instance_mut(object)?
+                    .fields
+                    .get_mut(slot)
+                    .ok_or(JRTError::IncompatibleClassChange)? = value;
+                None
+            }
+            Instruction::Instanceof(index) => {
+                let current = self.frame()?.class;
+                let ty = self.resolve_type(current, index)?;
+                let result = match self.frame()?.pop_reference()? {
+                    Some(object) => self.is_instance_of(object, &ty)?,
+                    None => false,
+                };
+                self.frame()?.push(JRTVar::Int(result as i32))?;
+                None
+            }
+            Instruction::Checkcast(index) => {
+                let current = self.frame()?.class;
+                let ty = self.resolve_type(current, index)?;
+                // null passes any cast, the reference stays on the stack
+                if let Some(object) = self.frame()?.peek()?.as_reference()? {
+                    self.check_cast(object, &ty)?;
+                }
+                None
+            }
+            Instruction::Newarray(atype) => {
+                let len = array_length(self.frame()?.pop_int()?)?;
+                let array = JRTArray::new(&FieldType::Base(atype.base_type()), len);
+                let array = self.heap.new_array(array);
+                self.frame()?.push(JRTVar::Object(array))?;
+                None
+            }
+            Instruction::Anewarray(index) => {
+                let current = self.frame()?.class;
+                let component = self.resolve_type(current, index)?;
+                let len = array_length(self.frame()?.pop_int()?)?;
+                let array = self.heap.new_array(JRTArray::new(&component, len));
+                self.frame()?.push(JRTVar::Object(array))?;
+                None
+            }
+            Instruction::Multianewarray { index, dimensions } => {
+                let current = self.frame()?.class;
+                let ty = self.resolve_type(current, index)?;
+                if dimensions == 0 || ty.dimensions() < dimensions as usize {
+                    return Err(JRTError::IncompatibleClassChange);
+                }
+                let frame = self.frame()?;
+                let mut counts = (0..dimensions)
+                    .map(|_| frame.pop_int())
+                    .collect::<Result<Vec<_>, _>>()?;
+                counts.reverse();
+                // every count is checked before anything is allocated
+                let counts = counts
+                    .into_iter()
+                    .map(array_length)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let array = self.new_multi_array(&ty, &counts);
+                self.frame()?.push(JRTVar::Object(array))?;
+                None
+            }
+            Instruction::Arraylength => {
+                let array = self.frame()?.pop_non_null()?;
+                let len = self.heap.array(array)?.len();
+                self.frame()?.push(JRTVar::Int(len as i32))?;
+                None
+            }
+
+            Instruction::Iaload
+            | Instruction::Laload
+            | Instruction::Faload
+            | Instruction::Daload
+            | Instruction::Aaload
+            | Instruction::Baload
+            | Instruction::Caload
+            | Instruction::Saload => {
+                let frame = self.stack.current_mut().ok_or(JRTError::NoFrame)?;
+                let index = frame.pop_int()?;
+                let array = frame.pop_non_null()?;
+                let value = self.heap.array(array)?.load(ins, index)?;
+                frame.push(value)?;
+                None
+            }
+            Instruction::Iastore
+            | Instruction::Lastore
+            | Instruction::Fastore
+            | Instruction::Dastore
+            | Instruction::Aastore
+            | Instruction::Bastore
+            | Instruction::Castore
+            | Instruction::Sastore => {
+                let frame = self.stack.current_mut().ok_or(JRTError::NoFrame)?;
+                let value = frame.pop()?;
+                let index = frame.pop_int()?;
+                let array = frame.pop_non_null()?;
+                // reference arrays are covariant, check the element
+                // against the array's actual component type
+                if let (Instruction::Aastore, JRTVar::Object(object)) = (ins, &value) {
+                    let array = self.heap.array(array)?;
+                    array.check_index(index)?;
+                    let component = array.component_type();
+                    if !self.is_instance_of(*object, &component)? {
+                        return Err(JRTError::ArrayStoreException);
+                    }
+                }
+                self.heap.array_mut(array)?.store(ins, index, value)?;
+                None
+            }
+            _ => {
+                if !self.frame()?.execute_local(ins)?
+                    && !self.frame()?.execute_numeric(ins, pc)?
+                    && !self.frame()?.execute_control(ins, pc)?
+                    && !self.frame()?.execute_stack(ins)?
+                    && !self.frame()?.execute_constant(ins)?
+                {
+                    return Err(JRTError::InvalidInstruction);
+                }
+                None
+            }
+        })
+    }
+
+    /// The frames on the stack as a java stack trace, `pc` is the
+    /// instruction executing in the top frame
+    fn capture_stack_trace(&self, pc: usize) -> Vec<StackTraceElement> {
+        let frames = self.stack.frames();
+        frames
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, frame)| {
+                // callers have already moved past their invoke
+                let pc = if i == 0 {
+                    pc
+                } else {
+                    frame.pc.saturating_sub(1)
+                };
+                let class = &self.class_list[frame.class];
+                let (method_name, _) = self.method_name_and_type(frame.class, frame.method);
+                StackTraceElement {
+                    class_name: class.name().unwrap_or_default().into(),
+                    method_name,
+                    file_name: class.source_file().map(Into::into),
+                    line_number: class.line_number(frame.method, pc),
+                }
+            })
+            .collect()
+    }
+
+    /// Takes the stack trace of `throwable` from the current frames, the
+    /// way `Throwable.fillInStackTrace` does. The frames filling in the
+    /// trace and the throwable's constructors are left out, so the trace
+    /// starts where the throwable was created
+    pub fn fill_in_stack_trace(&mut self, throwable: JRTObject) -> Result<(), JRTError> {
+        let pc = self.stack.current().ok_or(JRTError::NoFrame)?.pc;
+        let mut trace = self.capture_stack_trace(pc);
+        let mut skip = 0;
+        while trace
+            .get(skip)
+            .is_some_and(|e| e.method_name == "fillInStackTrace")
+        {
+            skip += 1;
+        }
+        while let Some(element) = trace.get(skip) {
+            let class = FieldType::Object(element.class_name.clone());
+            if element.method_name != "<init>" || !self.is_instance_of(throwable, &class)? {
+                break;
+            }
+            skip += 1;
+        }
+        trace.drain(..skip);
+        self.stack_traces.insert(throwable, trace);
+        Ok(())
+    }
+
+    pub fn stack_trace(&self, object: JRTObject) -> Option<&[StackTraceElement]> {
+        self.stack_traces.get(&object).map(Vec::as_slice)
+    }
+
+    /// Wraps a throwable as the error that carries it out of `run`, its
+    /// stack trace is taken the first time it is thrown
+    fn exception(&mut self, object: JRTObject, pc: usize) -> JRTError {
+        if !self.stack_traces.contains_key(&object) {
+            let trace = self.capture_stack_trace(pc);
+            self.stack_traces.insert(object, trace);
+        }
+        let class_name = match self.object_type(object) {
+            Ok(FieldType::Object(name)) => name,
+            Ok(ty) => ty.descriptor(),
+            Err(_) => String::new(),
+        };
+        JRTError::UncaughtException(Box::new(JavaException {
+            object,
+            class_name,
+            stack_trace: self.stack_traces[&object].clone(),
+        }))
+    }
+
+    /// Creates the java throwable for an error raised by the interpreter,
+    /// the error itself comes back when it has no java equivalent or the
+    /// throwable's class can't be loaded, initialized or constructed
+    fn new_vm_exception(&mut self, err: JRTError, pc: usize) -> Result<JRTObject, JRTError> {
+        let Some(class_name) = err.java_class() else {
+            return Err(err);
+        };
+        let headroom = std::mem::replace(&mut self.stack.headroom, true);
+        let object = self.new_throwable(class_name, err.message());
+        self.stack.headroom = headroom;
+        let object = object.map_err(|_| err)?;
+        let trace = self.capture_stack_trace(pc);
+        self.stack_traces.insert(object, trace);
+        Ok(object)
+    }
+
+    /// Allocates a throwable and runs its constructor like HotSpot does,
+    /// `(Ljava/lang/String;)V` when there is a message and `()V` otherwise
+    fn new_throwable(
+        &mut self,
+        class_name: &str,
+        message: Option<String>,
+    ) -> Result<JRTObject, JRTError> {
+        let class = self.load_class(class_name)?;
+        self.initialize_class(class)?;
+        let object = self.new_object(class)?;
+        match message {
+            Some(message) => {
+                let message = self.new_string(&message)?;
+                self.construct(object, "(Ljava/lang/String;)V", vec![message.into()])?;
+            }
+            None => self.construct(object, "()V", Vec::new())?,
+        }
+        Ok(object)
+    }
+
+    /// Runs the `<init>` of `object`'s own class with `descriptor` to
+    /// completion, `arguments` come after `this`
+    fn construct(
+        &mut self,
+        object: JRTObject,
+        descriptor: &str,
+        arguments: Vec<JRTVar>,
+    ) -> Result<(), JRTError> {
+        let class = self.heap.instance(object)?.class;
+        let constructor = self.class_list[class]
+            .method_entry_index_from_name("<init>", descriptor)
+            .ok_or(JRTError::MethodNotFound)?;
+        let mut arguments = arguments;
+        arguments.insert(0, JRTVar::Object(object));
+        let depth = self.stack.depth();
+        let result = self
+            .invoke(class, constructor, arguments)
+            .and_then(|result| match result {
+                Some(value) => Ok(value),
+                None => self.run(depth),
+            });
+        if let Err(err) = result {
+            self.stack.truncate(depth);
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    /// The thread everything runs on, created the first time it's asked
+    /// for like HotSpot's `create_initial_thread` does: a normal priority
+    /// thread named "main" in the "main" group of the "system" group
+    pub fn current_thread(&mut self) -> Result<JRTObject, JRTError> {
+        if let Some(thread) = self.main_thread {
+            return Ok(thread);
+        }
+        let group_class = self.load_class("java/lang/ThreadGroup")?;
+        self.initialize_class(group_class)?;
+        let system_group = self.new_object(group_class)?;
+        self.construct(system_group, "()V", Vec::new())?;
+        let main_group = self.new_object(group_class)?;
+        let name = self.new_string("main")?;
+        self.construct(
+            main_group,
+            "(Ljava/lang/ThreadGroup;Ljava/lang/String;)V",
+            vec![system_group.into(), name.into()],
+        )?;
+
+        let thread_class = self.load_class("java/lang/Thread")?;
+        self.initialize_class(thread_class)?;
+        let thread = self.new_object(thread_class)?;
+        // the constructor takes its priority from the current thread, which
+        // is this one
+        self.set_field(thread, "priority", "I", JRTVar::Int(THREAD_NORM_PRIORITY))?;
+        self.main_thread = Some(thread);
+        let result = self.construct(
+            thread,
+            "(Ljava/lang/ThreadGroup;Ljava/lang/String;)V",
+            vec![main_group.into(), name.into()],
+        );
+        if let Err(err) = result {
+            self.main_thread = None;
+            return Err(err);
+        }
+        self.set_field(thread, "threadStatus", "I", JRTVar::Int(THREAD_RUNNABLE))?;
+        Ok(thread)
+    }
+
+    /// Unwinds frames down to `base` looking for a handler of `object`,
+    /// `pc` is the instruction that threw in the top frame. Execution
+    /// continues at the handler when one is found
+    fn throw(&mut self, base: usize, object: JRTObject, pc: usize) -> Result<(), JRTError> {
+        let error = self.exception(object, pc);
+        let mut pc = pc;
+        loop {
+            let frame = self.stack.current().ok_or(JRTError::NoFrame)?;
+            if let Some(handler) = self.find_handler(frame.class, frame.method, pc, object)? {
+                let frame = self.frame()?;
+                frame.clear_operands();
+                frame.push(JRTVar::Object(object))?;
+                frame.pc = handler;
+                return Ok(());
+            }
+            self.stack.pop();
+            if self.stack.depth() <= base {
+                return Err(error);
+            }
+            // the caller's pc is already past the invoke, any pc inside
+            // the instruction selects the same handlers
+            pc = self.frame()?.pc - 1;
+        }
+    }
+
+    /// The first entry of the method's exception table covering `pc` whose
+    /// catch type the exception is an instance of
+    fn find_handler(
+        &mut self,
+        class: usize,
+        method: usize,
+        pc: usize,
+        object: JRTObject,
+    ) -> Result<Option<usize>, JRTError> {
+        let table = self.class_list[class]
+            .method_exception_table(method)
+            .to_vec();
+        for entry in table {
+            if !entry.covers(pc) {
+                continue;
+            }
+            if entry.catch_type == 0 {
+                return Ok(Some(entry.handler_pc as usize));
+            }
+            let catch_type = self.resolve_type(class, entry.catch_type)?;
+            if self.is_instance_of(object, &catch_type)? {
+                return Ok(Some(entry.handler_pc as usize));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// A class name the way `Class.getName` gives it, `java.lang.String` and
+/// `[Ljava.lang.String;`
+fn external_name(ty: &FieldType) -> String {
+    match ty {
+        FieldType::Object(name) => name.replace('/', "."),
+        ty => ty.descriptor().replace('/', "."),
+    }
+}
+
+/// The length operand of the array creation instructions
+fn array_length(count: i32) -> Result<usize, JRTError> {
+    usize::try_from(count).map_err(|_| JRTError::NegativeArraySizeException)
+}
+
+fn int_binary(frame: &mut Frame, op: impl Fn(i32, i32) -> i32) -> Result<(), JRTError> {
+    let b = frame.pop_int()?;
+    let a = frame.pop_int()?;
+    frame.push(JRTVar::Int(op(a, b)))
+}
+
+fn long_binary(frame: &mut Frame, op: impl Fn(i64, i64) -> i64) -> Result<(), JRTError> {
+    let b = frame.pop_long()?;
+    let a = frame.pop_long()?;
+    frame.push(JRTVar::Long(op(a, b)))
+}
+
+/// lshl, lshr and lushr take an int shift distance
+fn long_shift(frame: &mut Frame, op: impl Fn(i64, u32) -> i64) -> Result<(), JRTError> {
+    let b = frame.pop_int()?;
+    let a = frame.pop_long()?;
+    frame.push(JRTVar::Long(op(a, b as u32 & 0x3F)))
+}
+
+fn float_binary(frame: &mut Frame, op: impl Fn(f32, f32) -> f32) -> Result<(), JRTError> {
+    let b = frame.pop_float()?;
+    let a = frame.pop_float()?;
+    frame.push(JRTVar::Float(op(a, b)))
+}
+
+fn double_binary(frame: &mut Frame, op: impl Fn(f64, f64) -> f64) -> Result<(), JRTError> {
+    let b = frame.pop_double()?;
+    let a = frame.pop_double()?;
+    frame.push(JRTVar::Double(op(a, b)))
+}
+
+/// fcmpl/dcmpl push -1 and fcmpg/dcmpg push 1 when either side is NaN
+fn compare<T: PartialOrd>(a: T, b: T, nan: i32) -> i32 {
+    match a.partial_cmp(&b) {
+        Some(std::cmp::Ordering::Less) => -1,
+        Some(std::cmp::Ordering::Equal) => 0,
+        Some(std::cmp::Ordering::Greater) => 1,
+        None => nan,
+    }
+}
+
+/// The kind of value a local variable instruction works on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LocalKind {
+    Int,
+    Long,
+    Float,
+    Double,
+    Reference,
+}
+
+impl LocalKind {
+    fn matches(&self, var: &JRTVar) -> bool {
+        matches!(
+            (self, var),
+            (Self::Int, JRTVar::Int(_))
+                | (Self::Long, JRTVar::Long(_))
+                | (Self::Float, JRTVar::Float(_))
+                | (Self::Double, JRTVar::Double(_))
+                | (Self::Reference, JRTVar::Object(_) | JRTVar::Null)
+        )
+    }
+}
+
+/// Splits a load or store into its kind, local index and whether it
+/// stores. `execute` unwraps the `wide` forms before they get here
+fn local_access(ins: &Instruction) -> Option<(LocalKind, u16, bool)> {
+    use Instruction::*;
+    use LocalKind::*;
+    Some(match *ins {
+        Iload(i) => (Int, i, false),
+        Lload(i) => (Long, i, false),
+        Fload(i) => (Float, i, false),
+        Dload(i) => (Double, i, false),
+        Aload(i) => (Reference, i, false),
+        Iload0 => (Int, 0, false),
+        Iload1 => (Int, 1, false),
+        Iload2 => (Int, 2, false),
+        Iload3 => (Int, 3, false),
+        Lload0 => (Long, 0, false),
+        Lload1 => (Long, 1, false),
+        Lload2 => (Long, 2, false),
+        Lload3 => (Long, 3, false),
+        Fload0 => (Float, 0, false),
+        Fload1 => (Float, 1, false),
+        Fload2 => (Float, 2, false),
+        Fload3 => (Float, 3, false),
+        Dload0 => (Double, 0, false),
+        Dload1 => (Double, 1, false),
+        Dload2 => (Double, 2, false),
+        Dload3 => (Double, 3, false),
+        Aload0 => (Reference, 0, false),
+        Aload1 => (Reference, 1, false),
+        Aload2 => (Reference, 2, false),
+        Aload3 => (Reference, 3, false),
+        Istore(i) => (Int, i, true),
+        Lstore(i) => (Long, i, true),
+        Fstore(i) => (Float, i, true),
+        Dstore(i) => (Double, i, true),
+        Astore(i) => (Reference, i, true),
+        Istore0 => (Int, 0, true),
+        Istore1 => (Int, 1, true),
+        Istore2 => (Int, 2, true),
+        Istore3 => (Int, 3, true),
+        Lstore0 => (Long, 0, true),
+        Lstore1 => (Long, 1, true),
+        Lstore2 => (Long, 2, true),
+        Lstore3 => (Long, 3, true),
+        Fstore0 => (Float, 0, true),
+        Fstore1 => (Float, 1, true),
+        Fstore2 => (Float, 2, true),
+        Fstore3 => (Float, 3, true),
+        Dstore0 => (Double, 0, true),
+        Dstore1 => (Double, 1, true),
+        Dstore2 => (Double, 2, true),
+        Dstore3 => (Double, 3, true),
+        Astore0 => (Reference, 0, true),
+        Astore1 => (Reference, 1, true),
+        Astore2 => (Reference, 2, true),
+        Astore3 => (Reference, 3, true),
+        _ => return None,
+    })
+}
+
+impl Frame {
+    /// Local variable loads, stores and iinc. Returns false for anything
+    /// else
+    fn execute_local(&mut self, ins: &Instruction) -> Result<bool, JRTError> {
+        if let Instruction::Iinc { index, value } = *ins {
+            let JRTVar::Int(v) = *self.get_local(index as usize)? else {
+                return Err(JRTError::InvalidOperand);
+            };
+            self.set_local(index as usize, JRTVar::Int(v.wrapping_add(value as i32)))?;
+            return Ok(true);
+        }
+        let Some((kind, index, store)) = local_access(ins) else {
+            return Ok(false);
+        };
+        if store {
+            let var = self.pop()?;
+            // astore also stores the return address jsr pushes
+            let return_address =
+                kind == LocalKind::Reference && matches!(var, JRTVar::ReturnAddress(_));
+            if !kind.matches(&var) && !return_address {
+                return Err(JRTError::InvalidOperand);
+            }
+            self.set_local(index as usize, var)?;
+        } else {
+            let var = self.get_local(index as usize)?.clone();
+            if !kind.matches(&var) {
+                return Err(JRTError::InvalidOperand);
+            }
+            self.push(var)?;
+        }
+        Ok(true)
+    }
+
+    /// aconst_null, the `*const_*` shorthands, bipush and sipush. Returns
+    /// false for anything else
+    fn execute_constant(&mut self, ins: &Instruction) -> Result<bool, JRTError> {
+        use Instruction::*;
+        let value = match *ins {
+            AconstNull => JRTVar::Null,
+            IconstM1 => JRTVar::Int(-1),
+            Iconst0 => JRTVar::Int(0),
+            Iconst1 => JRTVar::Int(1),
+            Iconst2 => JRTVar::Int(2),
+            Iconst3 => JRTVar::Int(3),
+            Iconst4 => JRTVar::Int(4),
+            Iconst5 => JRTVar::Int(5),
+            Lconst0 => JRTVar::Long(0),
+            Lconst1 => JRTVar::Long(1),
+            Fconst0 => JRTVar::Float(0.0),
+            Fconst1 => JRTVar::Float(1.0),
+            Fconst2 => JRTVar::Float(2.0),
+            Dconst0 => JRTVar::Double(0.0),
+            Dconst1 => JRTVar::Double(1.0),
+            Bipush(v) => JRTVar::Int(v as i32),
+            Sipush(v) => JRTVar::Int(v as i32),
+            _ => return Ok(false),
+        };
+        self.push(value)?;
+        Ok(true)
+    }
+
+    /// pop, dup, swap and their variants. Each form is described by the
+    /// slots it moves, the value categories decide how many values that is
+    /// (JVMS 6.5). Returns false for anything else
+    fn execute_stack(&mut self, ins: &Instruction) -> Result<bool, JRTError> {
+        use Instruction::*;
+        // (slots duplicated, slots they are inserted below)
+        let (top, under) = match ins {
+            Pop => {
+                self.pop_slots(1)?;
+                return Ok(true);
+            }
+            Pop2 => {
+                self.pop_slots(2)?;
+                return Ok(true);
+            }
+            Swap => {
+                let a = self.pop_slots(1)?;
+                let b = self.pop_slots(1)?;
+                self.push_all(&a)?;
+                self.push_all(&b)?;
+                return Ok(true);
+            }
+            Dup => (1, 0),
+            DupX1 => (1, 1),
+            DupX2 => (1, 2),
+            Dup2 => (2, 0),
+            Dup2X1 => (2, 1),
+            Dup2X2 => (2, 2),
+            _ => return Ok(false),
+        };
+        let top = self.pop_slots(top)?;
+        let under = self.pop_slots(under)?;
+        self.push_all(&top)?;
+        self.push_all(&under)?;
+        self.push_all(&top)?;
+        Ok(true)
+    }
+
+    fn branch(&mut self, pc: usize, offset: i16, taken: bool) {
+        if taken {
+            self.jump(pc, offset as i32);
+        }
+    }
+
+    fn jump(&mut self, pc: usize, offset: i32) {
+        self.pc = (pc as isize + offset as isize) as usize;
+    }
+
+    /// Unconditional jumps, switches, subroutines and the null tests.
+    /// Offsets are relative to `pc`, the start of the instruction within
+    /// the method's code. Returns false for anything else
+    fn execute_control(&mut self, ins: &Instruction, pc: usize) -> Result<bool, JRTError> {
+        use Instruction::*;
+        match ins {
+            Goto(offset) => self.jump(pc, *offset as i32),
+            GotoW(offset) => self.jump(pc, *offset),
+            Jsr(_) | JsrW(_) => {
+                let offset = match *ins {
+                    Jsr(offset) => offset as i32,
+                    JsrW(offset) => offset,
+                    _ => unreachable!(),
+                };
+                // the return address is the instruction following the jsr
+                self.push(JRTVar::ReturnAddress(self.pc))?;
+                self.jump(pc, offset);
+            }
+            Ret(index) => {
+                let JRTVar::ReturnAddress(address) = *self.get_local(*index as usize)? else {
+                    return Err(JRTError::InvalidOperand);
+                };
+                self.pc = address;
+            }
+            Tableswitch {
+                default,
+                low,
+                high,
+                offsets,
+            } => {
+                let JRTVar::Int(key) = self.pop()? else {
+                    return Err(JRTError::InvalidOperand);
+                };
+                let offset = if key < *low || key > *high {
+                    *default
+                } else {
+                    offsets[(key as i64 - *low as i64) as usize]
+                };
+                self.jump(pc, offset);
+            }
+            Lookupswitch { default, pairs } => {
+                let JRTVar::Int(key) = self.pop()? else {
+                    return Err(JRTError::InvalidOperand);
+                };
+                let offset = pairs
+                    .binary_search_by_key(&key, |(k, _)| *k)
+                    .map_or(*default, |i| pairs[i].1);
+                self.jump(pc, offset);
+            }
+            Ifnull(offset) | Ifnonnull(offset) => {
+                let is_null = self.pop_reference()?.is_none();
+                self.branch(pc, *offset, is_null == matches!(ins, Ifnull(_)));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Math, conversion and comparison instructions, these only touch the
+    /// operand stack and pc. Returns false for anything else
+    fn execute_numeric(&mut self, ins: &Instruction, pc: usize) -> Result<bool, JRTError> {
+        use Instruction::*;
+        match *ins {
+            Iadd => int_binary(self, i32::wrapping_add)?,
+            Ladd => long_binary(self, i64::wrapping_add)?,
+            Fadd => float_binary(self, |a, b| a + b)?,
+            Dadd => double_binary(self, |a, b| a + b)?,
+            Isub => int_binary(self, i32::wrapping_sub)?,
+            Lsub => long_binary(self, i64::wrapping_sub)?,
+            Fsub => float_binary(self, |a, b| a - b)?,
+            Dsub => double_binary(self, |a, b| a - b)?,
+            Imul => int_binary(self, i32::wrapping_mul)?,
+            Lmul => long_binary(self, i64::wrapping_mul)?,
+            Fmul => float_binary(self, |a, b| a * b)?,
+            Dmul => double_binary(self, |a, b| a * b)?,
+            Idiv | Irem => {
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                if b == 0 {
+                    return Err(JRTError::ArithmeticException);
+                }
+                // wrapping so MIN / -1 gives MIN like java
+                let v = if let Idiv = ins {
+                    a.wrapping_div(b)
+                } else {
+                    a.wrapping_rem(b)
+                };
+                self.push(JRTVar::Int(v))?;
+            }
+            Ldiv | Lrem => {
+                let b = self.pop_long()?;
+                let a = self.pop_long()?;
+                if b == 0 {
+                    return Err(JRTError::ArithmeticException);
+                }
+                let v = if let Ldiv = ins {
+                    a.wrapping_div(b)
+                } else {
+                    a.wrapping_rem(b)
+                };
+                self.push(JRTVar::Long(v))?;
+            }
+            Fdiv => float_binary(self, |a, b| a / b)?,
+            Ddiv => double_binary(self, |a, b| a / b)?,
+            // rust's % truncates like java's fmod
+            Frem => float_binary(self, |a, b| a % b)?,
+            Drem => double_binary(self, |a, b| a % b)?,
+            Ineg => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Int(v.wrapping_neg()))?;
+            }
+            Lneg => {
+                let v = self.pop_long()?;
+                self.push(JRTVar::Long(v.wrapping_neg()))?;
+            }
+            Fneg => {
+                let v = self.pop_float()?;
+                self.push(JRTVar::Float(-v))?;
+            }
+            Dneg => {
+                let v = self.pop_double()?;
+                self.push(JRTVar::Double(-v))?;
+            }
+            Ishl => int_binary(self, |a, b| a.wrapping_shl(b as u32))?,
+            Ishr => int_binary(self, |a, b| a.wrapping_shr(b as u32))?,
+            Iushr => int_binary(self, |a, b| (a as u32).wrapping_shr(b as u32) as i32)?,
+            Lshl => long_shift(self, |a, b| a << b)?,
+            Lshr => long_shift(self, |a, b| a >> b)?,
+            Lushr => long_shift(self, |a, b| ((a as u64) >> b) as i64)?,
+            Iand => int_binary(self, |a, b| a & b)?,
+            Land => long_binary(self, |a, b| a & b)?,
+            Ior => int_binary(self, |a, b| a | b)?,
+            Lor => long_binary(self, |a, b| a | b)?,
+            Ixor => int_binary(self, |a, b| a ^ b)?,
+            Lxor => long_binary(self, |a, b| a ^ b)?,
+
+            // rust's float to int casts saturate and map NaN to 0 like java
+            I2l => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Long(v as i64))?;
+            }
+            I2f => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Float(v as f32))?;
+            }
+            I2d => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Double(v as f64))?;
+            }
+            L2i => {
+                let v = self.pop_long()?;
+                self.push(JRTVar::Int(v as i32))?;
+            }
+            L2f => {
+                let v = self.pop_long()?;
+                self.push(JRTVar::Float(v as f32))?;
+            }
+            L2d => {
+                let v = self.pop_long()?;
+                self.push(JRTVar::Double(v as f64))?;
+            }
+            F2i => {
+                let v = self.pop_float()?;
+                self.push(JRTVar::Int(v as i32))?;
+            }
+            F2l => {
+                let v = self.pop_float()?;
+                self.push(JRTVar::Long(v as i64))?;
+            }
+            F2d => {
+                let v = self.pop_float()?;
+                self.push(JRTVar::Double(v as f64))?;
+            }
+            D2i => {
+                let v = self.pop_double()?;
+                self.push(JRTVar::Int(v as i32))?;
+            }
+            D2l => {
+                let v = self.pop_double()?;
+                self.push(JRTVar::Long(v as i64))?;
+            }
+            D2f => {
+                let v = self.pop_double()?;
+                self.push(JRTVar::Float(v as f32))?;
+            }
+            I2b => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Int(v as i8 as i32))?;
+            }
+            I2c => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Int(v as u16 as i32))?;
+            }
+            I2s => {
+                let v = self.pop_int()?;
+                self.push(JRTVar::Int(v as i16 as i32))?;
+            }
+
+            Lcmp => {
+                let b = self.pop_long()?;
+                let a = self.pop_long()?;
+                self.push(JRTVar::Int(compare(a, b, 0)))?;
+            }
+            Fcmpl | Fcmpg => {
+                let b = self.pop_float()?;
+                let a = self.pop_float()?;
+                let nan = if let Fcmpl = ins { -1 } else { 1 };
+                self.push(JRTVar::Int(compare(a, b, nan)))?;
+            }
+            Dcmpl | Dcmpg => {
+                let b = self.pop_double()?;
+                let a = self.pop_double()?;
+                let nan = if let Dcmpl = ins { -1 } else { 1 };
+                self.push(JRTVar::Int(compare(a, b, nan)))?;
+            }
+            Ifeq(offset) => {
+                let v = self.pop_int()?;
+                self.branch(pc, offset, v == 0);
+            }
+            Ifne(offset) => {
+                let v = self.pop_int()?;
+                self.branch(pc, offset, v != 0);
+            }
+            Iflt(offset) => {
+                let v = self.pop_int()?;
+                self.branch(pc, offset, v < 0);
+            }
+            Ifge(offset) => {
+                let v = self.pop_int()?;
+                self.branch(pc, offset, v >= 0);
+            }
+            Ifgt(offset) => {
+                let v = self.pop_int()?;
+                self.branch(pc, offset, v > 0);
+            }
+            Ifle(offset) => {
+                let v = self.pop_int()?;
+                self.branch(pc, offset, v <= 0);
+            }
+            IfIcmpeq(offset) | IfIcmpne(offset) | IfIcmplt(offset) | IfIcmpge(offset)
+            | IfIcmpgt(offset) | IfIcmple(offset) => {
+                let b = self.pop_int()?;
+                let a = self.pop_int()?;
+                let taken = match ins {
+                    IfIcmpeq(_) => a == b,
+                    IfIcmpne(_) => a != b,
+                    IfIcmplt(_) => a < b,
+                    IfIcmpge(_) => a >= b,
+                    IfIcmpgt(_) => a > b,
+                    _ => a <= b,
+                };
+                self.branch(pc, offset, taken);
+            }
+            IfAcmpeq(offset) | IfAcmpne(offset) => {
+                let b = self.pop_reference()?;
+                let a = self.pop_reference()?;
+                self.branch(pc, offset, (a == b) == matches!(ins, IfAcmpeq(_)));
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jvm_opcodes::*, *};
+    use crate::jvm::class::{
+        attribute::AttributeEntry, constant::ConstantPool, method::MethodEntry,
+    };
+
+    /// A class `T` whose one static method `m` runs `code`. The class
+    /// comes after `constants` in its constant pool, so the first of them
+    /// is at index 1
+    fn class_with(
+        constants: Vec<ConstantPoolEntry>,
+        descriptor: &str,
+        max_stack: u16,
+        max_locals: u16,
+        code: Vec<u8>,
+    ) -> Class {
+        let mut pool = Vec::new();
+        for constant in constants {
+            let wide = matches!(
+                constant,
+                ConstantPoolEntry::Long(_) | ConstantPoolEntry::Double(_)
+            );
+            pool.push(constant);
+            if wide {
+                pool.push(ConstantPoolEntry::Empty);
+            }
+        }
+        let base = pool.len() as u16;
+        pool.extend([
+            ConstantPoolEntry::Utf8("T".into()),
+            ConstantPoolEntry::Class {
+                name_index: base + 1,
+            },
+            ConstantPoolEntry::Utf8("java/lang/Object".into()),
+            ConstantPoolEntry::Class {
+                name_index: base + 3,
+            },
+            ConstantPoolEntry::Utf8("m".into()),
+            ConstantPoolEntry::Utf8(descriptor.into()),
+            ConstantPoolEntry::Utf8("Code".into()),
+        ]);
+        Class {
+            // old enough for jsr and ret
+            minor_version: 0,
+            major_version: 49,
+            constant_pool: ConstantPool::new(pool),
+            access_flags: ClassAccessFlags::from_bits(0x0021),
+            this_class: base + 2,
+            super_class: base + 4,
+            interfaces: Vec::new(),
+            field_info: Vec::new(),
+            method_info: vec![MethodEntry {
+                access_flags: MethodAccessFlags::from_bits(0x0009),
+                name_index: base + 5,
+                descriptor_index: base + 6,
+                attributes: vec![AttributeEntry {
+                    name_index: base + 7,
+                    info: AttributeInfo::Code {
+                        max_stack,
+                        max_locals,
+                        code,
+                        exception_table: Vec::new(),
+                        attributes: Vec::new(),
+                    },
+                }],
+            }],
+            attribute_info: Vec::new(),
+        }
+    }
+
+    /// An interpreter on the stand-in java.lang classes of `res/stubs`
+    fn interpreter_with(class: Class) -> Interpreter {
+        let mut class_path = ClassPath::new();
+        class_path.push_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/res/stubs"));
+        let mut interpreter = Interpreter::with_class_path(class_path);
+        interpreter.insert_class(class).unwrap();
+        interpreter
+    }
+
+    /// Runs `code` as `T.m` with up to 6 stack slots and `arguments` in
+    /// the locals
+    fn try_run_code(
+        descriptor: &str,
+        code: Vec<u8>,
+        arguments: &[JRTVar],
+    ) -> Result<JRTVar, JRTError> {
+        let class = class_with(Vec::new(), descriptor, 6, 4, code);
+        interpreter_with(class).run_static_method("T", "m", descriptor, arguments)
+    }
+
+    fn run_code(descriptor: &str, code: Vec<u8>, arguments: &[JRTVar]) -> JRTVar {
+        try_run_code(descriptor, code, arguments).unwrap()
+    }
+
+    fn thrown(result: Result<JRTVar, JRTError>) -> String {
+        match result {
+            Err(JRTError::UncaughtException(exception)) => exception.class_name,
+            result => panic!("expected an exception, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn integer_division() {
+        let idiv = vec![ILOAD_0, ILOAD_1, IDIV, IRETURN];
+        let irem = vec![ILOAD_0, ILOAD_1, IREM, IRETURN];
+        let ldiv = vec![LLOAD_0, LLOAD_2, LDIV, LRETURN];
+        let lrem = vec![LLOAD_0, LLOAD_2, LREM, LRETURN];
+        let ints = |a: i32, b: i32| [JRTVar::Int(a), JRTVar::Int(b)];
+        let longs = |a: i64, b: i64| [JRTVar::Long(a), JRTVar::Long(b)];
+
+        assert_eq!(
+            run_code("(II)I", idiv.clone(), &ints(-7, 2)),
+            JRTVar::Int(-3)
+        );
+        assert_eq!(
+            run_code("(II)I", irem.clone(), &ints(-7, 2)),
+            JRTVar::Int(-1)
+        );
+        assert_eq!(
+            run_code("(II)I", idiv.clone(), &ints(i32::MIN, -1)),
+            JRTVar::Int(i32::MIN)
+        );
+        assert_eq!(
+            run_code("(II)I", irem.clone(), &ints(i32::MIN, -1)),
+            JRTVar::Int(0)
+        );
+        assert_eq!(
+            run_code("(JJ)J", ldiv.clone(), &longs(i64::MIN, -1)),
+            JRTVar::Long(i64::MIN)
+        );
+        assert_eq!(
+            run_code("(JJ)J", lrem.clone(), &longs(i64::MIN, -1)),
+            JRTVar::Long(0)
+        );
+        for (descriptor, code, arguments) in [
+            ("(II)I", idiv, ints(1, 0)),
+            ("(II)I", irem, ints(1, 0)),
+            ("(JJ)J", ldiv, longs(1, 0)),
+            ("(JJ)J", lrem, longs(1, 0)),
+        ] {
+            assert_eq!(
+                thrown(try_run_code(descriptor, code, &arguments)),
+                "java/lang/ArithmeticException"
+            );
+        }
+    }
+
+    #[test]
+    fn float_to_integer_saturates() {
+        let f2i = |v: f32| run_code("(F)I", vec![FLOAD_0, F2I, IRETURN], &[JRTVar::Float(v)]);
+        assert_eq!(f2i(f32::NAN), JRTVar::Int(0));
+        assert_eq!(f2i(f32::INFINITY), JRTVar::Int(i32::MAX));
+        assert_eq!(f2i(f32::NEG_INFINITY), JRTVar::Int(i32::MIN));
+        assert_eq!(f2i(-1.9), JRTVar::Int(-1));
+
+        let d2l = |v: f64| run_code("(D)J", vec![DLOAD_0, D2L, LRETURN], &[JRTVar::Double(v)]);
+        assert_eq!(d2l(f64::NAN), JRTVar::Long(0));
+        assert_eq!(d2l(f64::INFINITY), JRTVar::Long(i64::MAX));
+        assert_eq!(d2l(f64::NEG_INFINITY), JRTVar::Long(i64::MIN));
+        assert_eq!(d2l(1e300), JRTVar::Long(i64::MAX));
+    }
+
+    #[test]
+    fn comparisons_with_nan() {
+        let fcmp = |op: u8, a: f32, b: f32| {
+            run_code(
+                "(FF)I",
+                vec![FLOAD_0, FLOAD_1, op, IRETURN],
+                &[JRTVar::Float(a), JRTVar::Float(b)],
+            )
+        };
+        assert_eq!(fcmp(FCMPL, f32::NAN, 1.0), JRTVar::Int(-1));
+        assert_eq!(fcmp(FCMPG, f32::NAN, 1.0), JRTVar::Int(1));
+        assert_eq!(fcmp(FCMPL, 1.0, f32::NAN), JRTVar::Int(-1));
+        assert_eq!(fcmp(FCMPG, 1.0, f32::NAN), JRTVar::Int(1));
+        assert_eq!(fcmp(FCMPL, 0.0, -0.0), JRTVar::Int(0));
+
+        let dcmp = |op: u8, a: f64, b: f64| {
+            run_code(
+                "(DD)I",
+                vec![DLOAD_0, DLOAD_2, op, IRETURN],
+                &[JRTVar::Double(a), JRTVar::Double(b)],
+            )
+        };
+        assert_eq!(dcmp(DCMPL, f64::NAN, f64::NAN), JRTVar::Int(-1));
+        assert_eq!(dcmp(DCMPG, f64::NAN, f64::NAN), JRTVar::Int(1));
+    }
+
+    /// Folds the `count + 1` ints on top of the stack into one, the top
+    /// being the most significant decimal digit
+    fn fold_digits(count: usize) -> Vec<u8> {
+        [BIPUSH, 10, IMUL, IADD].repeat(count)
+    }
+
+    #[test]
+    fn dup2_by_category() {
+        // one long
+        assert_eq!(
+            run_code(
+                "(J)J",
+                vec![LLOAD_0, DUP2, LADD, LRETURN],
+                &[JRTVar::Long(1 << 40)]
+            ),
+            JRTVar::Long(1 << 41)
+        );
+        // two ints: 1 2 -> 1 2 1 2
+        let mut code = vec![ILOAD_0, ILOAD_1, DUP2];
+        code.extend(fold_digits(3));
+        code.push(IRETURN);
+        assert_eq!(
+            run_code("(II)I", code, &[JRTVar::Int(1), JRTVar::Int(2)]),
+            JRTVar::Int(2121)
+        );
+    }
+
+    #[test]
+    fn dup2_x1_by_category() {
+        // an int under a long: 3 L -> L 3 L
+        assert_eq!(
+            run_code(
+                "(IJ)J",
+                vec![ILOAD_0, LLOAD_1, DUP2_X1, POP2, I2L, LADD, LRETURN],
+                &[JRTVar::Int(3), JRTVar::Long(10)]
+            ),
+            JRTVar::Long(13)
+        );
+        // three ints: 1 2 3 -> 2 3 1 2 3
+        let mut code = vec![ILOAD_0, ILOAD_1, ILOAD_2, DUP2_X1];
+        code.extend(fold_digits(4));
+        code.push(IRETURN);
+        assert_eq!(
+            run_code(
+                "(III)I",
+                code,
+                &[JRTVar::Int(1), JRTVar::Int(2), JRTVar::Int(3)]
+            ),
+            JRTVar::Int(32132)
+        );
+        // a long where two ints are expected is rejected
+        assert!(try_run_code(
+            "(J)I",
+            vec![LLOAD_0, ICONST_0, DUP2_X1, POP2, POP2, ICONST_0, IRETURN],
+            &[JRTVar::Long(1)]
+        )
+        .is_err());
+    }
+
+    /// One `bipush <case>; ireturn` per case, then the default returning -1
+    fn switch_targets(cases: usize) -> Vec<u8> {
+        (0..cases)
+            .flat_map(|case| [BIPUSH, case as u8, IRETURN])
+            .chain([BIPUSH, -1i8 as u8, IRETURN])
+            .collect()
+    }
+
+    /// `tableswitch` on the first argument returning the case's position,
+    /// the switch is at pc 1 so its table starts at pc 4
+    fn tableswitch(low: i32, high: i32) -> Vec<u8> {
+        let cases = (high as i64 - low as i64 + 1) as usize;
+        let targets = 16 + cases * 4;
+        let mut code = vec![ILOAD_0, TABLESWITCH, 0, 0];
+        code.extend(((targets + cases * 3 - 1) as i32).to_be_bytes());
+        code.extend(low.to_be_bytes());
+        code.extend(high.to_be_bytes());
+        for case in 0..cases {
+            code.extend(((targets + case * 3 - 1) as i32).to_be_bytes());
+        }
+        code.extend(switch_targets(cases));
+        code
+    }
+
+    /// `lookupswitch` on the first argument returning the matching key's
+    /// position
+    fn lookupswitch(keys: &[i32]) -> Vec<u8> {
+        let targets = 12 + keys.len() * 8;
+        let mut code = vec![ILOAD_0, LOOKUPSWITCH, 0, 0];
+        code.extend(((targets + keys.len() * 3 - 1) as i32).to_be_bytes());
+        code.extend((keys.len() as i32).to_be_bytes());
+        for (case, key) in keys.iter().enumerate() {
+            code.extend(key.to_be_bytes());
+            code.extend(((targets + case * 3 - 1) as i32).to_be_bytes());
+        }
+        code.extend(switch_targets(keys.len()));
+        code
+    }
+
+    #[test]
+    fn tableswitch_bounds() {
+        let switch = |code: &[u8], key: i32| run_code("(I)I", code.to_vec(), &[JRTVar::Int(key)]);
+        let code = tableswitch(10, 12);
+        for (key, case) in [
+            (9, -1),
+            (10, 0),
+            (11, 1),
+            (12, 2),
+            (13, -1),
+            (i32::MIN, -1),
+            (i32::MAX, -1),
+        ] {
+            assert_eq!(switch(&code, key), JRTVar::Int(case), "{}", key);
+        }
+        // `high - low` and `key - low` don't fit an i32
+        let code = tableswitch(i32::MAX - 1, i32::MAX);
+        for (key, case) in [(i32::MAX - 1, 0), (i32::MAX, 1), (i32::MIN, -1), (0, -1)] {
+            assert_eq!(switch(&code, key), JRTVar::Int(case), "{}", key);
+        }
+        let code = tableswitch(i32::MIN, i32::MIN + 1);
+        for (key, case) in [(i32::MIN, 0), (i32::MIN + 1, 1), (i32::MAX, -1)] {
+            assert_eq!(switch(&code, key), JRTVar::Int(case), "{}", key);
+        }
+    }
+
+    #[test]
+    fn lookupswitch_bounds() {
+        let code = lookupswitch(&[i32::MIN, 0, i32::MAX]);
+        for (key, case) in [
+            (i32::MIN, 0),
+            (0, 1),
+            (i32::MAX, 2),
+            (i32::MIN + 1, -1),
+            (i32::MAX - 1, -1),
+            (1, -1),
+        ] {
+            assert_eq!(
+                run_code("(I)I", code.clone(), &[JRTVar::Int(key)]),
+                JRTVar::Int(case),
+                "{}",
+                key
+            );
+        }
+        assert_eq!(
+            run_code("(I)I", lookupswitch(&[]), &[JRTVar::Int(0)]),
+            JRTVar::Int(-1)
+        );
+    }
+
+    #[test]
+    fn jsr_and_ret() {
+        // the subroutine at 7 adds 41 to local 0 and returns to 5
+        let code = vec![
+            ICONST_1, ISTORE_0, JSR, 0, 5, ILOAD_0, IRETURN, ASTORE_1, IINC, 0, 41, RET, 1,
+        ];
+        assert_eq!(run_code("()I", code, &[]), JRTVar::Int(42));
+        // only astore takes the return address, istore doesn't
+        let code = vec![JSR, 0, 3, ISTORE_0, ICONST_0, IRETURN];
+        assert!(try_run_code("()I", code, &[]).is_err());
+    }
+
+    #[test]
+    fn ldc2_w() {
+        let constants = vec![
+            ConstantPoolEntry::Long(0x0123_4567_89AB_CDEF),
+            ConstantPoolEntry::Double(-0.0),
+        ];
+        let class = class_with(constants, "()J", 2, 0, vec![LDC2_W, 0, 1, LRETURN]);
+        assert_eq!(
+            interpreter_with(class)
+                .run_static_method("T", "m", "()J", &[])
+                .unwrap(),
+            JRTVar::Long(0x0123_4567_89AB_CDEF)
+        );
+
+        let constants = vec![ConstantPoolEntry::Long(0), ConstantPoolEntry::Double(-0.0)];
+        let class = class_with(constants, "()D", 2, 0, vec![LDC2_W, 0, 3, DRETURN]);
+        let value = interpreter_with(class).run_static_method("T", "m", "()D", &[]);
+        assert!(matches!(value, Ok(JRTVar::Double(d)) if d.to_bits() == (-0.0f64).to_bits()));
+    }
+
+    #[test]
+    fn string_literals_are_interned() {
+        // two `String` constants with equal contents
+        let constants = vec![
+            ConstantPoolEntry::Utf8("hi".into()),
+            ConstantPoolEntry::String { string_index: 1 },
+            ConstantPoolEntry::Utf8("hi".into()),
+            ConstantPoolEntry::String { string_index: 3 },
+        ];
+        let code = vec![
+            LDC, 2, LDC, 4, IF_ACMPNE, 0, 5, ICONST_1, IRETURN, ICONST_0, IRETURN,
+        ];
+        let class = class_with(constants, "()Z", 2, 0, code);
+        assert_eq!(
+            interpreter_with(class)
+                .run_static_method("T", "m", "()Z", &[])
+                .unwrap(),
+            JRTVar::Boolean(true)
+        );
+
+        let constants = vec![
+            ConstantPoolEntry::Utf8("hi".into()),
+            ConstantPoolEntry::String { string_index: 1 },
+        ];
+        let descriptor = "()Ljava/lang/String;";
+        let class = class_with(constants, descriptor, 1, 0, vec![LDC, 2, ARETURN]);
+        let mut interpreter = interpreter_with(class);
+        let Ok(JRTVar::Object(literal)) = interpreter.run_static_method("T", "m", descriptor, &[])
+        else {
+            panic!("expected a string");
+        };
+        assert_eq!(
+            interpreter
+                .run_static_method("T", "m", descriptor, &[])
+                .unwrap(),
+            JRTVar::Object(literal)
+        );
+        assert_eq!(interpreter.intern_string("hi").unwrap(), literal);
+        let fresh = interpreter.new_string("hi").unwrap();
+        assert_ne!(fresh, literal);
+        assert_eq!(interpreter.intern(fresh).unwrap(), literal);
+    }
+
+    #[test]
+    fn storing_over_half_a_wide_local_clears_it() {
+        let mut frame = Frame::new(0, 0, 4, 0);
+        frame.set_local(0, JRTVar::Long(1)).unwrap();
+        frame.set_local(1, JRTVar::Int(2)).unwrap();
+        assert!(matches!(frame.get_local(0), Ok(JRTVar::Void)));
+        assert!(matches!(frame.get_local(1), Ok(JRTVar::Int(2))));
+
+        frame.set_local(1, JRTVar::Double(1.0)).unwrap();
+        frame.set_local(2, JRTVar::Long(3)).unwrap();
+        assert!(matches!(frame.get_local(1), Ok(JRTVar::Void)));
+        assert!(matches!(frame.get_local(2), Ok(JRTVar::Long(3))));
+        assert!(matches!(frame.get_local(3), Ok(JRTVar::Void)));
+
+        assert!(matches!(
+            frame.set_local(3, JRTVar::Long(4)),
+            Err(JRTError::InvalidLocal)
+        ));
+    }
 }
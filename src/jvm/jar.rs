@@ -0,0 +1,228 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use zip::{result::ZipError, ZipArchive};
+
+use super::class::{Class, ClassBuilderError};
+
+#[derive(Debug)]
+pub enum JarError {
+    Io(std::io::Error),
+    Zip(ZipError),
+    Class(ClassBuilderError),
+    InvalidManifest,
+}
+
+impl From<std::io::Error> for JarError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ZipError> for JarError {
+    fn from(err: ZipError) -> Self {
+        Self::Zip(err)
+    }
+}
+
+impl From<ClassBuilderError> for JarError {
+    fn from(err: ClassBuilderError) -> Self {
+        Self::Class(err)
+    }
+}
+
+/// The main section of `META-INF/MANIFEST.MF`, per-entry sections are
+/// ignored
+#[derive(Debug, Default)]
+pub struct Manifest {
+    attributes: HashMap<String, String>,
+}
+
+impl Manifest {
+    pub fn parse(data: &[u8]) -> Result<Self, JarError> {
+        let text = std::str::from_utf8(data).map_err(|_| JarError::InvalidManifest)?;
+        let mut attributes = HashMap::new();
+        let mut current: Option<(String, String)> = None;
+
+        for line in text.split("\r\n").flat_map(|l| l.split(['\n', '\r'])) {
+            // a line starting with a single space continues the previous value
+            if let Some(rest) = line.strip_prefix(' ') {
+                let (_, value) = current.as_mut().ok_or(JarError::InvalidManifest)?;
+                value.push_str(rest);
+                continue;
+            }
+            if let Some((key, value)) = current.take() {
+                attributes.insert(key, value);
+            }
+            // the main section ends at the first blank line
+            if line.is_empty() {
+                if attributes.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            let (key, value) = line.split_once(':').ok_or(JarError::InvalidManifest)?;
+            current = Some((key.into(), value.strip_prefix(' ').unwrap_or(value).into()));
+        }
+        if let Some((key, value)) = current {
+            attributes.insert(key, value);
+        }
+        Ok(Self { attributes })
+    }
+
+    /// Attribute names are case insensitive
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The `Main-Class` as a binary name with `/` separators
+    pub fn main_class(&self) -> Option<String> {
+        self.get("Main-Class").map(|c| c.trim().replace('.', "/"))
+    }
+
+    /// The space separated `Class-Path` entries as written, they are
+    /// relative to the directory holding the jar
+    pub fn class_path(&self) -> Vec<&str> {
+        self.get("Class-Path")
+            .map(|c| c.split(' ').filter(|p| !p.is_empty()).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A jar opened for loading classes, entries are only read and parsed
+/// when a class is asked for
+pub struct JarClassSource {
+    path: PathBuf,
+    archive: ZipArchive<File>,
+    manifest: Manifest,
+}
+
+impl std::fmt::Debug for JarClassSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JarClassSource")
+            .field("path", &self.path)
+            .field("entries", &self.archive.len())
+            .field("manifest", &self.manifest)
+            .finish()
+    }
+}
+
+impl JarClassSource {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, JarError> {
+        let path = path.as_ref().to_path_buf();
+        let mut archive = ZipArchive::new(File::open(&path)?)?;
+        let manifest = match archive.by_name("META-INF/MANIFEST.MF") {
+            Ok(mut file) => {
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)?;
+                Manifest::parse(&data)?
+            }
+            Err(ZipError::FileNotFound) => Manifest::default(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            path,
+            archive,
+            manifest,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    pub fn main_class(&self) -> Option<String> {
+        self.manifest.main_class()
+    }
+
+    /// The `Class-Path` entries resolved against the jar's directory
+    pub fn class_path(&self) -> Vec<PathBuf> {
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        self.manifest
+            .class_path()
+            .into_iter()
+            .map(|p| dir.join(p))
+            .collect()
+    }
+
+    /// Binary names of every class in the jar
+    pub fn class_names(&self) -> impl Iterator<Item = &str> {
+        self.archive
+            .file_names()
+            .filter_map(|n| n.strip_suffix(".class"))
+            .filter(|n| !n.starts_with("META-INF/"))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.class_names().any(|n| n == name)
+    }
+
+    /// Reads the raw bytes of `name`, `None` when the jar doesn't hold it
+    pub fn read_class_bytes(&mut self, name: &str) -> Result<Option<Vec<u8>>, JarError> {
+        let mut file = match self.archive.by_name(&format!("{}.class", name)) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut data = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut data)?;
+        Ok(Some(data))
+    }
+
+    /// Reads and parses `name`, a binary name like `java/lang/Object`
+    pub fn load_class(&mut self, name: &str) -> Result<Option<Class>, JarError> {
+        match self.read_class_bytes(name)? {
+            Some(data) => Ok(Some(Class::new(&data)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuation_lines() {
+        let manifest = Manifest::parse(
+            b"Manifest-Version: 1.0\r\nClass-Path: a.jar b\r\n .jar\r\n  c.jar\r\nMain-Class: pkg.\r\n Main\r\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.class_path(), ["a.jar", "b.jar", "c.jar"]);
+        assert_eq!(manifest.main_class().as_deref(), Some("pkg/Main"));
+        assert_eq!(manifest.get("class-path"), Some("a.jar b.jar c.jar"));
+    }
+
+    #[test]
+    fn main_section_ends_at_first_blank_line() {
+        let manifest = Manifest::parse(
+            b"Manifest-Version: 1.0\nMain-Class: Main\n\nName: pkg/Other.class\nMain-Class: Other\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.main_class().as_deref(), Some("Main"));
+        assert_eq!(manifest.get("Name"), None);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(matches!(
+            Manifest::parse(b" leading continuation\n"),
+            Err(JarError::InvalidManifest)
+        ));
+        assert!(matches!(
+            Manifest::parse(b"no colon\n"),
+            Err(JarError::InvalidManifest)
+        ));
+    }
+}
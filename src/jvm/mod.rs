@@ -1,2 +1,5 @@
 pub mod class;
+pub mod class_path;
 pub mod interpreter;
+pub mod jar;
+pub mod native;
@@ -0,0 +1,329 @@
+//! Java methods implemented in Rust, the `native` methods of the core
+//! library and whatever an embedder plugs in
+
+use std::{collections::HashMap, rc::Rc};
+
+use super::{
+    class::descriptor::{BaseType, FieldType},
+    interpreter::{Interpreter, JRTArray, JRTError, JRTObject, JRTVar},
+};
+
+/// A native method. The result is converted to the declared return type,
+/// void methods return `JRTVar::Void`
+pub type NativeMethod = dyn Fn(&mut Interpreter, &NativeArgs) -> Result<JRTVar, JRTError>;
+
+/// The arguments of a native call, `this` first for instance methods.
+/// Every argument has its declared type, a boolean parameter arrives as
+/// `JRTVar::Boolean` rather than an `Int`
+#[derive(Debug)]
+pub struct NativeArgs {
+    values: Vec<JRTVar>,
+}
+
+impl NativeArgs {
+    pub fn new(values: Vec<JRTVar>) -> Self {
+        Self { values }
+    }
+
+    pub fn values(&self) -> &[JRTVar] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The argument at `index` as a Rust value, `args.get::<i32>(1)`
+    pub fn get<T>(&self, index: usize) -> Result<T, JRTError>
+    where
+        T: TryFrom<JRTVar, Error = JRTError>,
+    {
+        self.values
+            .get(index)
+            .cloned()
+            .ok_or(JRTError::InvalidOperand)?
+            .try_into()
+    }
+}
+
+/// Native methods keyed by class, name and descriptor. The default
+/// registry holds the natives the core library can't run without
+pub struct NativeRegistry {
+    methods: HashMap<String, Rc<NativeMethod>>,
+}
+
+impl std::fmt::Debug for NativeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.methods.keys()).finish()
+    }
+}
+
+impl Default for NativeRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register_core();
+        registry
+    }
+}
+
+impl NativeRegistry {
+    pub fn empty() -> Self {
+        Self {
+            methods: HashMap::new(),
+        }
+    }
+
+    fn key(class: &str, name: &str, descriptor: &str) -> String {
+        format!("{}.{}{}", class, name, descriptor)
+    }
+
+    /// Binds the native method `class.name descriptor` to `method`,
+    /// replacing whatever was bound before
+    pub fn register(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+        method: impl Fn(&mut Interpreter, &NativeArgs) -> Result<JRTVar, JRTError> + 'static,
+    ) {
+        self.methods
+            .insert(Self::key(class, name, descriptor), Rc::new(method));
+    }
+
+    pub fn get(&self, class: &str, name: &str, descriptor: &str) -> Option<Rc<NativeMethod>> {
+        self.methods
+            .get(&Self::key(class, name, descriptor))
+            .cloned()
+    }
+
+    pub fn contains(&self, class: &str, name: &str, descriptor: &str) -> bool {
+        self.methods
+            .contains_key(&Self::key(class, name, descriptor))
+    }
+
+    fn register_core(&mut self) {
+        // natives the registry binds itself, there is nothing to register
+        for class in [
+            "java/lang/Class",
+            "java/lang/System",
+            "java/lang/Thread",
+            "java/lang/invoke/MethodHandleNatives",
+            "jdk/internal/misc/Unsafe",
+        ] {
+            self.register(class, "registerNatives", "()V", |_, _| Ok(JRTVar::Void));
+        }
+
+        self.register("java/lang/Object", "hashCode", "()I", |_, args| {
+            Ok(args.get::<JRTObject>(0)?.identity_hash().into())
+        });
+        self.register(
+            "java/lang/System",
+            "identityHashCode",
+            "(Ljava/lang/Object;)I",
+            |_, args| {
+                let object = args.get::<Option<JRTObject>>(0)?;
+                Ok(object.map_or(0, |o| o.identity_hash()).into())
+            },
+        );
+        self.register(
+            "java/lang/Object",
+            "getClass",
+            "()Ljava/lang/Class;",
+            |interpreter, args| {
+                let ty = interpreter.object_type(args.get(0)?)?;
+                Ok(interpreter.class_mirror(Some(&ty))?.into())
+            },
+        );
+        self.register(
+            "java/lang/System",
+            "arraycopy",
+            "(Ljava/lang/Object;ILjava/lang/Object;II)V",
+            array_copy,
+        );
+
+        self.register(
+            "java/lang/Thread",
+            "currentThread",
+            "()Ljava/lang/Thread;",
+            |interpreter, _| Ok(interpreter.current_thread()?.into()),
+        );
+        // there is only ever the one thread, nothing to schedule
+        self.register("java/lang/Thread", "setPriority0", "(I)V", |_, _| {
+            Ok(JRTVar::Void)
+        });
+        // no frames run with a restricted context
+        self.register(
+            "java/security/AccessController",
+            "getStackAccessControlContext",
+            "()Ljava/security/AccessControlContext;",
+            |_, _| Ok(None.into()),
+        );
+
+        self.register(
+            "java/lang/Throwable",
+            "fillInStackTrace",
+            "(I)Ljava/lang/Throwable;",
+            |interpreter, args| {
+                let throwable = args.get(0)?;
+                interpreter.fill_in_stack_trace(throwable)?;
+                Ok(throwable.into())
+            },
+        );
+
+        self.register(
+            "java/lang/Class",
+            "getPrimitiveClass",
+            "(Ljava/lang/String;)Ljava/lang/Class;",
+            |interpreter, args| {
+                let name = interpreter.string_value(args.get(0)?)?;
+                let ty = match name.as_str() {
+                    "void" => None,
+                    name => Some(FieldType::Base(
+                        "BCDFIJSZ"
+                            .bytes()
+                            .filter_map(BaseType::from_char)
+                            .find(|b| b.name() == name)
+                            .ok_or(JRTError::InvalidOperand)?,
+                    )),
+                };
+                Ok(interpreter.class_mirror(ty.as_ref())?.into())
+            },
+        );
+        self.register(
+            "java/lang/Class",
+            "desiredAssertionStatus0",
+            "(Ljava/lang/Class;)Z",
+            |_, _| Ok(false.into()),
+        );
+        self.register(
+            "java/lang/String",
+            "intern",
+            "()Ljava/lang/String;",
+            |interpreter, args| Ok(interpreter.intern(args.get(0)?)?.into()),
+        );
+        // UTF-16 strings are stored in native byte order
+        self.register("java/lang/StringUTF16", "isBigEndian", "()Z", |_, _| {
+            Ok(cfg!(target_endian = "big").into())
+        });
+
+        self.register("java/lang/Float", "floatToRawIntBits", "(F)I", |_, args| {
+            Ok((args.get::<f32>(0)?.to_bits() as i32).into())
+        });
+        self.register("java/lang/Float", "intBitsToFloat", "(I)F", |_, args| {
+            Ok(f32::from_bits(args.get::<i32>(0)? as u32).into())
+        });
+        self.register(
+            "java/lang/Double",
+            "doubleToRawLongBits",
+            "(D)J",
+            |_, args| Ok((args.get::<f64>(0)?.to_bits() as i64).into()),
+        );
+        self.register("java/lang/Double", "longBitsToDouble", "(J)D", |_, args| {
+            Ok(f64::from_bits(args.get::<i64>(0)? as u64).into())
+        });
+
+        self.register("java/lang/System", "nanoTime", "()J", |_, _| {
+            let start = *START.get_or_init(std::time::Instant::now);
+            Ok((start.elapsed().as_nanos() as i64).into())
+        });
+        self.register("java/lang/System", "currentTimeMillis", "()J", |_, _| {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+            Ok((now.as_millis() as i64).into())
+        });
+    }
+}
+
+/// What `System.nanoTime` counts from
+static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+
+/// `System.arraycopy`, the arrays may be the same one and overlap.
+/// Reference elements are checked one at a time, elements before the first
+/// one that doesn't fit stay copied
+fn array_copy(interpreter: &mut Interpreter, args: &NativeArgs) -> Result<JRTVar, JRTError> {
+    let src = args.get::<JRTObject>(0)?;
+    let src_pos = args.get::<i32>(1)?;
+    let dest = args.get::<JRTObject>(2)?;
+    let dest_pos = args.get::<i32>(3)?;
+    let length = args.get::<i32>(4)?;
+
+    let (Ok(src_array), Ok(dest_array)) = (
+        interpreter.heap().array(src),
+        interpreter.heap().array(dest),
+    ) else {
+        return Err(JRTError::ArrayStoreException);
+    };
+    let src_component = src_array.component_type();
+    let dest_component = dest_array.component_type();
+    if src_component.is_reference() != dest_component.is_reference()
+        || !src_component.is_reference() && src_component != dest_component
+    {
+        return Err(JRTError::ArrayStoreException);
+    }
+    // HotSpot's messages, `object array[3]` or `int[3]`
+    let describe = |component: &FieldType, len: usize| match component {
+        FieldType::Base(base) => format!("{}[{}]", base.name(), len),
+        _ => format!("object array[{}]", len),
+    };
+    let out_of_bounds = |what: &str, index: i64, component: &FieldType, len: usize| {
+        Err(JRTError::ArrayIndexOutOfBoundsException(format!(
+            "arraycopy: {} {} out of bounds for {}",
+            what,
+            index,
+            describe(component, len)
+        )))
+    };
+    let (src_len, dest_len) = (src_array.len(), dest_array.len());
+    if src_pos < 0 {
+        return out_of_bounds("source index", src_pos as i64, &src_component, src_len);
+    }
+    if dest_pos < 0 {
+        return out_of_bounds(
+            "destination index",
+            dest_pos as i64,
+            &dest_component,
+            dest_len,
+        );
+    }
+    if length < 0 {
+        return Err(JRTError::ArrayIndexOutOfBoundsException(format!(
+            "arraycopy: length {} is negative",
+            length
+        )));
+    }
+    if src_pos as i64 + length as i64 > src_len as i64 {
+        let last = src_pos as i64 + length as i64;
+        return out_of_bounds("last source index", last, &src_component, src_len);
+    }
+    if dest_pos as i64 + length as i64 > dest_len as i64 {
+        let last = dest_pos as i64 + length as i64;
+        return out_of_bounds("last destination index", last, &dest_component, dest_len);
+    }
+    let (src_pos, dest_pos, length) = (src_pos as usize, dest_pos as usize, length as usize);
+
+    let mut elements = src_array.slice(src_pos, length);
+    let mut result = Ok(JRTVar::Void);
+    if let JRTArray::Reference { elements, .. } = &mut elements {
+        if !interpreter.is_assignable(&src_component, &dest_component)? {
+            for (i, element) in elements.iter().enumerate() {
+                if let Some(object) = element.as_reference()? {
+                    if !interpreter.is_instance_of(object, &dest_component)? {
+                        elements.truncate(i);
+                        result = Err(JRTError::ArrayStoreException);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    interpreter
+        .heap_mut()
+        .array_mut(dest)?
+        .copy_from(dest_pos, &elements)?;
+    result
+}
@@ -1,14 +1,99 @@
-use rusty_jvm::jvm::{class::Class, interpreter::Interpreter};
+use rusty_jvm::jvm::{
+    class::{descriptor::FieldType, Class},
+    class_path::ClassPath,
+    interpreter::{Interpreter, JRTArray, JRTError, JRTVar},
+    jar::JarClassSource,
+};
+
+const USAGE: &str = "usage: rusty_jvm [-cp <path>] <classfile | jarfile> [args...]";
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("rusty_jvm: {}", message);
+    std::process::exit(1);
+}
 
 fn main() {
-    let file = std::fs::read("res/HelloWorld.class").unwrap();
-    let class = Class::new(&file).unwrap();
-    println!("{:-#?}", class);
-    println!("{:#?}", class);
-    let mut interpreter = Interpreter::new();
-    interpreter.insert_class(class);
-    let ret = interpreter
-        .run_static_method("HelloWorld", "main", &[])
-        .unwrap();
-    println!("{:#?}", ret);
+    let mut args = std::env::args().skip(1);
+    let mut user_path = None;
+    let path = loop {
+        match args.next() {
+            Some(flag) if flag == "-cp" || flag == "--class-path" => match args.next() {
+                Some(path) => user_path = Some(path),
+                None => {
+                    eprintln!("rusty_jvm: {} requires a path", flag);
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            },
+            Some(flag) if flag.starts_with('-') => {
+                eprintln!("rusty_jvm: unknown option {}", flag);
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+            Some(path) => break path,
+            None => break "res/HelloWorld.class".into(),
+        }
+    };
+
+    let mut class_path = ClassPath::new();
+    let name = if path.ends_with(".jar") {
+        let main_class = match JarClassSource::open(&path) {
+            Ok(jar) => jar.main_class(),
+            Err(err) => fail(format!("{}: {:?}", path, err)),
+        };
+        let Some(main_class) = main_class else {
+            fail(format!("{}: no Main-Class in the manifest", path))
+        };
+        if let Err(err) = class_path.push_jar(&path) {
+            fail(format!("{}: {:?}", path, err));
+        }
+        main_class
+    } else {
+        let file = std::fs::read(&path).unwrap_or_else(|err| fail(format!("{}: {}", path, err)));
+        let class = Class::new(&file).unwrap_or_else(|err| fail(format!("{}: {:?}", path, err)));
+        let Some(name) = class.name().map(str::to_owned) else {
+            fail(format!("{}: no class name", path))
+        };
+        class_path.insert_bytes(name.clone(), file);
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            class_path.push_dir(dir);
+        }
+        name
+    };
+    // `-cp` holds what the program links against, java.base included,
+    // searched after the program itself
+    for entry in user_path.iter().flat_map(std::env::split_paths) {
+        if entry.is_file() {
+            if let Err(err) = class_path.push_jar(&entry) {
+                fail(format!("{}: {:?}", entry.display(), err));
+            }
+        } else {
+            class_path.push_dir(entry);
+        }
+    }
+
+    let mut interpreter = Interpreter::with_class_path(class_path);
+    // everything after the class or jar is handed to main
+    let args = args
+        .map(|arg| interpreter.new_string(&arg).map(JRTVar::Object))
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|err| fail(format!("{:?}", err)));
+    let args = interpreter.heap_mut().new_array(JRTArray::Reference {
+        component: FieldType::Object("java/lang/String".into()),
+        elements: args,
+    });
+    let ret = interpreter.run_static_method(
+        &name,
+        "main",
+        "([Ljava/lang/String;)V",
+        &[JRTVar::Object(args)],
+    );
+    match ret {
+        Ok(_) => {}
+        Err(JRTError::UncaughtException(exception)) => {
+            eprintln!("Exception in thread \"main\" {}", exception);
+            std::process::exit(1);
+        }
+        Err(err) => fail(format!("{:?}", err)),
+    }
 }
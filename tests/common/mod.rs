@@ -0,0 +1,56 @@
+//! Interpreters for the classes in `res`, on top of the stand-in classes of
+//! `res/stubs` or of the JDK's own classes. Those are extracted with
+//! `jimage extract --dir <dir> $JAVA_HOME/lib/modules` and
+//! `RUSTY_JVM_JAVA_BASE` points at `<dir>/java.base`, tests that need them
+//! are ignored by default, run them with `cargo test -- --ignored`.
+
+use rusty_jvm::jvm::{
+    class_path::ClassPath,
+    interpreter::{Interpreter, JRTError, JRTVar},
+};
+
+fn interpreter(java_base: &str) -> Interpreter {
+    let mut class_path = ClassPath::new();
+    class_path.push_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/res"));
+    class_path.push_dir(java_base);
+    Interpreter::with_class_path(class_path)
+}
+
+pub fn stubs() -> Interpreter {
+    interpreter(concat!(env!("CARGO_MANIFEST_DIR"), "/res/stubs"))
+}
+
+pub fn java_base() -> Interpreter {
+    let java_base = std::env::var("RUSTY_JVM_JAVA_BASE").expect("RUSTY_JVM_JAVA_BASE is not set");
+    interpreter(&java_base)
+}
+
+/// Runs a static method, panicking with the trace of anything uncaught
+pub fn run(
+    interpreter: &mut Interpreter,
+    class: &str,
+    method: &str,
+    descriptor: &str,
+    arguments: &[JRTVar],
+) -> JRTVar {
+    match interpreter.run_static_method(class, method, descriptor, arguments) {
+        Ok(value) => value,
+        Err(JRTError::UncaughtException(exception)) => panic!("{}", exception),
+        Err(err) => panic!("{:?}", err),
+    }
+}
+
+/// The string a static method returns, `None` for null
+pub fn run_string(
+    interpreter: &mut Interpreter,
+    class: &str,
+    method: &str,
+    descriptor: &str,
+    arguments: &[JRTVar],
+) -> Option<String> {
+    match run(interpreter, class, method, descriptor, arguments) {
+        JRTVar::Object(string) => Some(interpreter.string_value(string).unwrap()),
+        JRTVar::Null => None,
+        value => panic!("{:?}", value),
+    }
+}
@@ -0,0 +1,149 @@
+//! Java exceptions thrown and caught by `res/Exceptions.java`, every test
+//! runs on the stand-in classes of `res/stubs` and again on the JDK's own
+//! classes, see `common`.
+
+mod common;
+
+use common::{java_base, stubs};
+use rusty_jvm::jvm::interpreter::{Interpreter, JRTError, JRTVar, MAX_FRAMES};
+
+fn run(interpreter: &mut Interpreter, method: &str, descriptor: &str) -> JRTVar {
+    common::run(interpreter, "Exceptions", method, descriptor, &[])
+}
+
+fn run_string(interpreter: &mut Interpreter, method: &str) -> String {
+    common::run_string(
+        interpreter,
+        "Exceptions",
+        method,
+        "()Ljava/lang/String;",
+        &[],
+    )
+    .unwrap()
+}
+
+fn catches_exceptions(mut interpreter: Interpreter) {
+    assert!(matches!(
+        run(&mut interpreter, "caught", "()I"),
+        JRTVar::Int(9)
+    ));
+    assert!(matches!(
+        run(&mut interpreter, "fillReturnsThis", "()Z"),
+        JRTVar::Boolean(true)
+    ));
+    assert!(matches!(
+        run(&mut interpreter, "caughtDeeper", "()I"),
+        JRTVar::Int(42)
+    ));
+}
+
+fn uncaught_trace_starts_where_thrown(mut interpreter: Interpreter) {
+    let Err(JRTError::UncaughtException(exception)) =
+        interpreter.run_static_method("Exceptions", "uncaught", "()V", &[])
+    else {
+        panic!("expected an uncaught exception");
+    };
+    assert_eq!(exception.class_name, "java/lang/IllegalArgumentException");
+    // the constructors and fillInStackTrace are left out
+    let top = &exception.stack_trace[0];
+    assert_eq!(
+        (top.class_name.as_str(), top.method_name.as_str()),
+        ("Exceptions", "uncaught")
+    );
+    assert_eq!(exception.stack_trace.len(), 1);
+}
+
+fn stack_overflow_is_catchable(mut interpreter: Interpreter) {
+    // the error's classes are initialized for the first time at the limit
+    assert!(matches!(
+        run(&mut interpreter, "overflow", "()I"),
+        JRTVar::Int(depth) if depth as usize == MAX_FRAMES - 1
+    ));
+}
+
+fn uncaught_stack_overflow_is_a_java_error(mut interpreter: Interpreter) {
+    let Err(JRTError::UncaughtException(exception)) =
+        interpreter.run_static_method("Exceptions", "overflowUncaught", "()V", &[])
+    else {
+        panic!("expected an uncaught exception");
+    };
+    assert_eq!(exception.class_name, "java/lang/StackOverflowError");
+}
+
+fn vm_exceptions_are_constructed(mut interpreter: Interpreter) {
+    assert_eq!(run_string(&mut interpreter, "divideByZero"), "/ by zero");
+    assert_eq!(
+        run_string(&mut interpreter, "indexOutOfBounds"),
+        "Index 3 out of bounds for length 2"
+    );
+    assert_eq!(
+        run_string(&mut interpreter, "classCast"),
+        "class java.lang.Object cannot be cast to class java.lang.String"
+    );
+}
+
+#[test]
+fn catches_stub_exceptions() {
+    catches_exceptions(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn catches_jdk_exceptions() {
+    catches_exceptions(java_base());
+}
+
+#[test]
+fn stub_trace_starts_where_thrown() {
+    uncaught_trace_starts_where_thrown(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_trace_starts_where_thrown() {
+    uncaught_trace_starts_where_thrown(java_base());
+}
+
+#[test]
+fn stub_stack_overflow_is_catchable() {
+    stack_overflow_is_catchable(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_stack_overflow_is_catchable() {
+    stack_overflow_is_catchable(java_base());
+}
+
+#[test]
+fn stub_uncaught_stack_overflow_is_a_java_error() {
+    uncaught_stack_overflow_is_a_java_error(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_uncaught_stack_overflow_is_a_java_error() {
+    uncaught_stack_overflow_is_a_java_error(java_base());
+}
+
+#[test]
+fn stub_vm_exceptions_are_constructed() {
+    vm_exceptions_are_constructed(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_vm_exceptions_are_constructed() {
+    vm_exceptions_are_constructed(java_base());
+}
+
+// the JDK's getSuppressed copies the list through Class.isArray, which has
+// no native yet
+#[test]
+fn stub_vm_exceptions_record_suppressed() {
+    // addSuppressed needs the fields the constructor sets up
+    assert!(matches!(
+        run(&mut stubs(), "suppressed", "()I"),
+        JRTVar::Int(1)
+    ));
+}
@@ -0,0 +1,164 @@
+//! The core natives as `res/Natives.java` sees them, every test runs on the
+//! stand-in classes of `res/stubs` and again on the JDK's own classes, see
+//! `common`.
+
+mod common;
+
+use common::{java_base, stubs};
+use rusty_jvm::jvm::interpreter::{Interpreter, JRTArray, JRTVar};
+
+fn run(interpreter: &mut Interpreter, method: &str, descriptor: &str) -> JRTVar {
+    common::run(interpreter, "Natives", method, descriptor, &[])
+}
+
+fn run_string(interpreter: &mut Interpreter, method: &str) -> Option<String> {
+    common::run_string(interpreter, "Natives", method, "()Ljava/lang/String;", &[])
+}
+
+fn run_array(interpreter: &mut Interpreter, method: &str, descriptor: &str) -> JRTArray {
+    match run(interpreter, method, descriptor) {
+        JRTVar::Object(array) => interpreter.heap().array(array).unwrap().clone(),
+        value => panic!("{:?}", value),
+    }
+}
+
+fn copy_out_of_bounds(
+    interpreter: &mut Interpreter,
+    src_pos: i32,
+    dest_pos: i32,
+    length: i32,
+) -> Option<String> {
+    common::run_string(
+        interpreter,
+        "Natives",
+        "copyOutOfBounds",
+        "(III)Ljava/lang/String;",
+        &[src_pos.into(), dest_pos.into(), length.into()],
+    )
+}
+
+fn main_thread_is_set_up(mut interpreter: Interpreter) {
+    assert_eq!(
+        run_string(&mut interpreter, "threadName").as_deref(),
+        Some("main")
+    );
+    assert!(matches!(
+        run(&mut interpreter, "threadPriority", "()I"),
+        JRTVar::Int(5)
+    ));
+    assert_eq!(
+        run_string(&mut interpreter, "threadGroup").as_deref(),
+        Some("main")
+    );
+    assert_eq!(
+        run_string(&mut interpreter, "parentThreadGroup").as_deref(),
+        Some("system")
+    );
+    assert!(matches!(
+        run(&mut interpreter, "sameThread", "()Z"),
+        JRTVar::Boolean(true)
+    ));
+}
+
+fn array_copy_overlaps(mut interpreter: Interpreter) {
+    assert!(matches!(
+        run_array(&mut interpreter, "copyForward", "()[I"),
+        JRTArray::Int(a) if a == [1, 1, 2, 3, 4]
+    ));
+    assert!(matches!(
+        run_array(&mut interpreter, "copyBackward", "()[I"),
+        JRTArray::Int(a) if a == [2, 3, 4, 5, 5]
+    ));
+}
+
+fn array_copy_checks_bounds(mut interpreter: Interpreter) {
+    let interpreter = &mut interpreter;
+    assert_eq!(copy_out_of_bounds(interpreter, 2, 0, 3), None);
+    assert_eq!(
+        copy_out_of_bounds(interpreter, -1, 0, 1).as_deref(),
+        Some("arraycopy: source index -1 out of bounds for int[5]")
+    );
+    assert_eq!(
+        copy_out_of_bounds(interpreter, 0, -1, 1).as_deref(),
+        Some("arraycopy: destination index -1 out of bounds for int[3]")
+    );
+    assert_eq!(
+        copy_out_of_bounds(interpreter, 0, 0, -1).as_deref(),
+        Some("arraycopy: length -1 is negative")
+    );
+    assert_eq!(
+        copy_out_of_bounds(interpreter, 3, 0, 3).as_deref(),
+        Some("arraycopy: last source index 6 out of bounds for int[5]")
+    );
+    assert_eq!(
+        copy_out_of_bounds(interpreter, 0, 1, 3).as_deref(),
+        Some("arraycopy: last destination index 4 out of bounds for int[3]")
+    );
+}
+
+fn array_copy_checks_stores(mut interpreter: Interpreter) {
+    // the elements before the one that doesn't fit are copied
+    let JRTArray::Reference { elements, .. } = run_array(
+        &mut interpreter,
+        "copyUntilStoreFails",
+        "()[Ljava/lang/Object;",
+    ) else {
+        panic!("expected an object array");
+    };
+    let copied = elements
+        .iter()
+        .map(|e| match e {
+            JRTVar::Object(s) => Some(interpreter.string_value(*s).unwrap()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(copied, [Some("a".into()), Some("b".into()), None, None]);
+    assert!(matches!(
+        run(&mut interpreter, "copyMismatchedPrimitives", "()Z"),
+        JRTVar::Boolean(true)
+    ));
+}
+
+#[test]
+fn stub_main_thread_is_set_up() {
+    main_thread_is_set_up(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_main_thread_is_set_up() {
+    main_thread_is_set_up(java_base());
+}
+
+#[test]
+fn stub_array_copy_overlaps() {
+    array_copy_overlaps(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_array_copy_overlaps() {
+    array_copy_overlaps(java_base());
+}
+
+#[test]
+fn stub_array_copy_checks_bounds() {
+    array_copy_checks_bounds(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_array_copy_checks_bounds() {
+    array_copy_checks_bounds(java_base());
+}
+
+#[test]
+fn stub_array_copy_checks_stores() {
+    array_copy_checks_stores(stubs());
+}
+
+#[test]
+#[ignore = "needs RUSTY_JVM_JAVA_BASE"]
+fn jdk_array_copy_checks_stores() {
+    array_copy_checks_stores(java_base());
+}